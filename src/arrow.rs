@@ -0,0 +1,285 @@
+//! Apache Arrow record batches and Parquet files (requires the `arrow` feature)
+//!
+//! The conversions produce typed columns: dates are `Date32`, values are nullable `Float64` with missing observations as nulls.
+//! * `observations_batch()` keeps the realtime_start and realtime_end of every observation so real-time period downloads (output type 1) round-trip.  Vintage matrices (output types 2 to 4) are not supported.
+//! * `panel_batch()` writes a date column followed by one column per series.  The column metadata (title, units, frequency, seasonal_adjustment) is stored in the field metadata.
+//! * `series_batch()` converts series information such as `series::Response::seriess`.
+//!
+//! `write_parquet()` writes any of these batches to a Parquet file.
+//!
+//! ```
+//! use fred_rs::arrow::{panel_batch, write_parquet};
+//! use fred_rs::panel::Builder;
+//! use fred_rs::timeseries::TimeSeries;
+//! use chrono::NaiveDate;
+//!
+//! let date = |m| NaiveDate::from_ymd_opt(2020, m, 1).unwrap();
+//! let mut builder = Builder::new();
+//! builder.series("UNRATE", TimeSeries::new(vec![(date(1), Some(3.6)), (date(2), None)]));
+//!
+//! let batch = panel_batch(&builder.build()).unwrap();
+//! assert_eq!(batch.num_rows(), 2);
+//! assert_eq!(batch.column(1).null_count(), 1);
+//!
+//! let path = std::env::temp_dir().join("fred_rs_arrow_doc.parquet");
+//! write_parquet(&path, &batch).unwrap();
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use arrow_array::{ArrayRef, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::panel::Panel;
+use crate::series::Series;
+use crate::series::observation::{parse_date, Response};
+
+/// Converts the observations of a series to a record batch
+///
+/// The columns are realtime_start, realtime_end and date (`Date32`) and value (nullable `Float64`).  The first data point that cannot be parsed is returned as an error.
+///
+/// # Arguments
+/// * `resp` - observations returned by fred/series/observations with output type 1
+pub fn observations_batch(resp: &Response) -> Result<RecordBatch, Error> {
+    let mut observations = Vec::with_capacity(resp.observations.len());
+    for point in &resp.observations {
+        observations.push(point.to_observation()?);
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("realtime_start", DataType::Date32, false),
+        Field::new("realtime_end", DataType::Date32, false),
+        Field::new("date", DataType::Date32, false),
+        Field::new("value", DataType::Float64, true),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        dates(observations.iter().map(|o| o.realtime_start)),
+        dates(observations.iter().map(|o| o.realtime_end)),
+        dates(observations.iter().map(|o| o.date)),
+        Arc::new(observations.iter().map(|o| o.value).collect::<Float64Array>()),
+    ];
+
+    batch(schema, columns)
+}
+
+/// Converts aligned series to a record batch
+///
+/// The first column is the date (`Date32`), followed by one nullable `Float64` column per series named after it.  Non-empty column metadata is stored in the field metadata under the keys title, units, frequency and seasonal_adjustment.
+///
+/// # Arguments
+/// * `panel` - the aligned series
+pub fn panel_batch(panel: &Panel) -> Result<RecordBatch, Error> {
+    let mut fields = vec![Field::new("date", DataType::Date32, false)];
+    let mut columns: Vec<ArrayRef> = vec![dates(panel.dates().iter().cloned())];
+
+    for (idx, column) in panel.columns().iter().enumerate() {
+        let metadata: HashMap<String, String> = [
+            ("title", &column.title),
+            ("units", &column.units),
+            ("frequency", &column.frequency),
+            ("seasonal_adjustment", &column.seasonal_adjustment),
+        ]
+            .iter()
+            .filter_map(|(key, value)| value.as_ref().map(|v| (String::from(*key), v.clone())))
+            .collect();
+
+        fields.push(Field::new(column.name.as_str(), DataType::Float64, true).with_metadata(metadata));
+        let values = panel.column_at(idx).unwrap_or_default();
+        columns.push(Arc::new(values.iter().cloned().collect::<Float64Array>()));
+    }
+
+    batch(Schema::new(fields), columns)
+}
+
+/// Converts series information to a record batch
+///
+/// Dates are `Date32`, popularity scores are `Int64` and every other field is text.  group_popularity and notes are nullable.
+///
+/// # Arguments
+/// * `seriess` - series returned by fred/series, a series search or a list endpoint
+pub fn series_batch(seriess: &[Series]) -> Result<RecordBatch, Error> {
+    let text = |f: &dyn Fn(&Series) -> &str| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(seriess.iter().map(f)))
+    };
+    let date_column = |field: &str, f: &dyn Fn(&Series) -> &str| -> Result<ArrayRef, Error> {
+        let mut values = Vec::with_capacity(seriess.len());
+        for s in seriess {
+            values.push(parse_date(field, f(s))?);
+        }
+        Ok(dates(values.into_iter()))
+    };
+
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("realtime_start", DataType::Date32, false),
+        Field::new("realtime_end", DataType::Date32, false),
+        Field::new("title", DataType::Utf8, false),
+        Field::new("observation_start", DataType::Date32, false),
+        Field::new("observation_end", DataType::Date32, false),
+        Field::new("frequency", DataType::Utf8, false),
+        Field::new("frequency_short", DataType::Utf8, false),
+        Field::new("units", DataType::Utf8, false),
+        Field::new("units_short", DataType::Utf8, false),
+        Field::new("seasonal_adjustment", DataType::Utf8, false),
+        Field::new("seasonal_adjustment_short", DataType::Utf8, false),
+        Field::new("last_updated", DataType::Utf8, false),
+        Field::new("popularity", DataType::Int64, false),
+        Field::new("group_popularity", DataType::Int64, true),
+        Field::new("notes", DataType::Utf8, true),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        text(&|s| &s.id),
+        date_column("realtime_start", &|s| &s.realtime_start)?,
+        date_column("realtime_end", &|s| &s.realtime_end)?,
+        text(&|s| &s.title),
+        date_column("observation_start", &|s| &s.observation_start)?,
+        date_column("observation_end", &|s| &s.observation_end)?,
+        text(&|s| &s.frequency),
+        text(&|s| &s.frequency_short),
+        text(&|s| &s.units),
+        text(&|s| &s.units_short),
+        text(&|s| &s.seasonal_adjustment),
+        text(&|s| &s.seasonal_adjustment_short),
+        text(&|s| &s.last_updated),
+        Arc::new(Int64Array::from_iter_values(seriess.iter().map(|s| s.popularity as i64))),
+        Arc::new(seriess.iter().map(|s| s.group_popularity.map(|p| p as i64)).collect::<Int64Array>()),
+        Arc::new(seriess.iter().map(|s| s.notes.as_deref()).collect::<StringArray>()),
+    ];
+
+    batch(schema, columns)
+}
+
+/// Writes a record batch to a Parquet file
+///
+/// # Arguments
+/// * `path` - the file to create or replace
+/// * `batch` - the data to write
+pub fn write_parquet<P: AsRef<Path>>(path: P, batch: &RecordBatch) -> Result<(), Error> {
+    let path = path.as_ref();
+    let file = match File::create(path) {
+        Ok(f) => f,
+        Err(e) => return Err(Error::Io(format!("Unable to create {}: {}", path.display(), e))),
+    };
+    write_parquet_to(file, batch)
+}
+
+/// Writes a record batch in Parquet format to a writer
+///
+/// # Arguments
+/// * `out` - destination of the Parquet data
+/// * `batch` - the data to write
+pub fn write_parquet_to<W: Write + Send>(out: W, batch: &RecordBatch) -> Result<(), Error> {
+    let mut writer = match ArrowWriter::try_new(out, batch.schema(), None) {
+        Ok(w) => w,
+        Err(e) => return Err(Error::Io(e.to_string())),
+    };
+    if let Err(e) = writer.write(batch) {
+        return Err(Error::Io(e.to_string()));
+    }
+    match writer.close() {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::Io(e.to_string())),
+    }
+}
+
+/// Converts dates to days since 1970-01-01
+fn dates<I: Iterator<Item = NaiveDate>>(dates: I) -> ArrayRef {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    Arc::new(Date32Array::from_iter_values(dates.map(|d| (d - epoch).num_days() as i32)))
+}
+
+fn batch(schema: Schema, columns: Vec<ArrayRef>) -> Result<RecordBatch, Error> {
+    match RecordBatch::try_new(Arc::new(schema), columns) {
+        Ok(b) => Ok(b),
+        Err(e) => Err(Error::Convert(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panel::{Builder, Column};
+    use crate::timeseries::TimeSeries;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn arrow_observations_round_trip() {
+        let resp: Response = serde_json::from_str(r#"{"realtime_start":"2020-01-01","realtime_end":"9999-12-31","observation_start":"1776-07-04","observation_end":"9999-12-31",
+            "units":"lin","output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":2,"offset":0,"limit":100000,
+            "observations":[
+                {"realtime_start":"2020-01-01","realtime_end":"2020-06-30","date":"2019-01-01","value":"1.5"},
+                {"realtime_start":"2020-07-01","realtime_end":"9999-12-31","date":"2019-01-01","value":"."}
+            ]}"#).unwrap();
+
+        let batch = observations_batch(&resp).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Date32);
+        let realtime_end = batch.column(1).as_any().downcast_ref::<Date32Array>().unwrap();
+        assert_eq!(realtime_end.value_as_date(0), Some(date(2020, 6, 30)));
+        assert_eq!(realtime_end.value_as_date(1), Some(date(9999, 12, 31)));
+        let values = batch.column(3).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(values.value(0), 1.5);
+        assert!(values.is_null(1));
+
+        let path = std::env::temp_dir().join(format!("fred_rs_arrow_observations_round_trip_{}.parquet", std::process::id()));
+        write_parquet(&path, &batch).unwrap();
+        let read: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|b| b.unwrap())
+            .collect();
+        assert_eq!(read, vec![batch]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn arrow_panel_and_series() {
+        let column = Column {
+            units: Some(String::from("Percent")),
+            ..Column::new("UNRATE")
+        };
+        let mut builder = Builder::new();
+        builder.column(column, TimeSeries::new(vec![(date(2020, 1, 1), Some(3.6)), (date(2020, 2, 1), None)]));
+        let batch = panel_batch(&builder.build()).unwrap();
+
+        let schema = batch.schema();
+        assert_eq!(schema.field(1).name(), "UNRATE");
+        assert_eq!(schema.field(1).metadata().get("units").map(|u| u.as_str()), Some("Percent"));
+        assert!(!schema.field(1).metadata().contains_key("title"));
+        assert_eq!(batch.column(1).null_count(), 1);
+
+        let series = Series {
+            id: String::from("GNPCA"),
+            realtime_start: String::from("2020-01-01"),
+            realtime_end: String::from("2020-01-01"),
+            observation_start: String::from("1929-01-01"),
+            observation_end: String::from("2019-01-01"),
+            popularity: 12,
+            ..Series::default()
+        };
+        let batch = series_batch(std::slice::from_ref(&series)).unwrap();
+        assert_eq!(batch.num_columns(), 16);
+        assert_eq!(batch.column(13).as_any().downcast_ref::<Int64Array>().unwrap().value(0), 12);
+        assert!(batch.column(14).is_null(0));
+
+        let bad = Series { observation_end: String::from("soon"), ..series };
+        match series_batch(&[bad]) {
+            Err(Error::Parse { field, .. }) => assert_eq!(field, "observation_end"),
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
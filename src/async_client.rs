@@ -0,0 +1,927 @@
+//! Functions and definitions related to the asynchronous client
+//! 
+//! The [AsyncFredClient](struct.AsyncFredClient.html) mirrors [FredClient](../client/struct.FredClient.html) endpoint for endpoint, but each method is an `async fn` built on the non-blocking reqwest client.  It accepts the same argument builders and returns the same response objects.
+//! 
+//! ```
+//! use fred_rs::async_client::AsyncFredClient;
+//! use fred_rs::series::observation::{Builder, Units, Frequency, Response};
+//! 
+//! # async fn run() {
+//! // Create the client object
+//! let c = match AsyncFredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! 
+//! // Create the argument builder
+//! let mut builder = Builder::new();
+//! 
+//! // Set the arguments for the builder
+//! builder
+//!     .observation_start("2000-01-01")
+//!     .units(Units::PCH)
+//!     .frequency(Frequency::M);
+//! 
+//! // Make the request and pass in the builder to apply the arguments
+//! let resp: Response = match c.series_observation("GNPCA", Some(builder)).await {
+//!     Ok(resp) => resp,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! # }
+//! ```
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::*;
+use crate::backtest::RealtimeStore;
+use crate::bulk::{BulkObservations, SeriesObservations};
+use crate::chunking::ChunkedObservations;
+use crate::cache::Cache;
+use crate::pagination::{Collect, Paged};
+use crate::pipeline::{self, Attempts, Lookup};
+use crate::client::{check_key_format, FredClientBuilder, KeyStatus};
+use crate::rate_limit::RateLimiter;
+use crate::realtime::RealtimeObservations;
+use crate::revisions::Revisions;
+use crate::retry::RetryPolicy;
+use crate::request::Request;
+use crate::timeseries::TimeSeries;
+use crate::transport::{AsyncTransport, HttpRequest};
+
+#[derive(Clone, Debug)]
+/// Persistent asynchronous client object used to access the FRED API
+/// 
+/// Each method for the client represents a data endpoint provided by the API and will return a data object representing the response contents.
+pub struct AsyncFredClient {
+    pub(crate) transport: Arc<dyn AsyncTransport>,
+    pub(crate) url_base: String,
+    pub(crate) api_key: String,
+    pub(crate) limiter: Option<RateLimiter>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) cache: Option<Cache>,
+}
+
+impl AsyncFredClient {
+
+    /// Creates and initializes a new asynchronous client object
+    /// 
+    /// The client will attempt to load an API key from the environment variable 'FRED_API_KEY'.  If this variable is undefined, the key remains empty.
+    /// 
+    /// No request is sent to the FRED API, so creating a client succeeds offline.  Use `validate_key()` to check the key and the connection.
+    /// 
+    /// ```
+    /// use fred_rs::async_client::AsyncFredClient;
+    /// 
+    /// let mut client = match AsyncFredClient::new() {
+    ///     Ok(c) => c,
+    ///     Err(msg) => {
+    ///         println!("{}", msg);
+    ///         return
+    ///     },
+    /// };
+    /// ```
+    pub fn new() -> Result<AsyncFredClient, Error> {
+        FredClientBuilder::new().build_async()
+    }
+
+    /// Returns a [FredClientBuilder](../client/struct.FredClientBuilder.html) used to configure a new client
+    /// 
+    /// Call `build_async()` on the builder to create the asynchronous client.
+    /// 
+    /// ```
+    /// use fred_rs::async_client::AsyncFredClient;
+    /// 
+    /// let client = match AsyncFredClient::builder()
+    ///     .url_base("http://localhost:8080/fred/")
+    ///     .build_async()
+    /// {
+    ///     Ok(c) => c,
+    ///     Err(msg) => {
+    ///         println!("{}", msg);
+    ///         return
+    ///     },
+    /// };
+    /// ```
+    pub fn builder() -> FredClientBuilder {
+        FredClientBuilder::new()
+    }
+
+    /// Sets the FRED API key for the client
+    /// 
+    /// # Arguments
+    /// * `key` - The [API key](https://research.stlouisfed.org/docs/api/api_key.html) generated to access FRED 
+    pub fn with_key(&mut self, key: &str) {
+        self.api_key = String::from(key);
+    }
+
+    /// Checks whether the client's API key is accepted by FRED
+    /// 
+    /// See [FredClient::validate_key()](../client/struct.FredClient.html#method.validate_key).
+    pub async fn validate_key(&self) -> KeyStatus {
+        if let Err(reason) = check_key_format(&self.api_key) {
+            return KeyStatus::Invalid(reason);
+        }
+
+        // sent past the cache so a revoked key is never reported from a stored response
+        let request = request::category(0);
+        let result = self.fetch(&request).await.and_then(|text| request::parse(request.endpoint(), &text));
+        KeyStatus::from(result)
+    }
+
+    /// Returns the client's response cache, if one is configured
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    async fn get<T: DeserializeOwned>(&self, request: Request) -> Result<T, Error> {
+        let text = self.get_text(&request).await?;
+        request::parse(request.endpoint(), &text)
+    }
+
+    /// Requests every page of a response and combines them, failing if FRED returns fewer results than it reports
+    async fn get_all<R: DeserializeOwned + Paged + Default>(&self, request: Request) -> Result<R, Error> {
+        let mut pages = Collect::new(request);
+        while let Some(page) = pages.next_request() {
+            pages.push(self.get(page).await?);
+        }
+        pages.finish()
+    }
+
+    /// Returns the response body for a request from the cache or the network
+    /// 
+    /// Cache files are read and written on tokio's blocking thread pool.  Boxed because looking up release dates for the cache calls it recursively.
+    fn get_text<'a>(
+        &'a self,
+        request: &'a Request
+    ) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let cache = match &self.cache {
+                Some(c) => c,
+                None => return self.fetch(request).await,
+            };
+
+            let lookup = {
+                let (cache, url_base, request) = (cache.clone(), self.url_base.clone(), request.clone());
+                blocking(move || pipeline::lookup(Some(&cache), &url_base, &request)).await?
+            };
+            let key = match lookup? {
+                Lookup::Cached(text) => return Ok(text),
+                Lookup::Send(key) => key,
+            };
+
+            let text = self.fetch(request).await?;
+            if let Some(key) = key {
+                let expires = pipeline::expiry(cache, request, self.release_expiry(cache, request).await);
+                let (cache, endpoint, body) = (cache.clone(), request.endpoint(), text.clone());
+                blocking(move || cache.store(&key, endpoint, &body, expires)).await?;
+            }
+
+            Ok(text)
+        })
+    }
+
+    /// Finds when cached observations expire based on the series' next release
+    async fn release_expiry(&self, cache: &Cache, request: &Request) -> Option<SystemTime> {
+        let series_release = pipeline::series_release_request(cache, request)?;
+        let text = self.get_text(&series_release).await.ok()?;
+        let (release_id, dates) = pipeline::release_dates_request(&text)?;
+        let text = self.get_text(&dates).await.ok()?;
+        pipeline::release_expiry(release_id, &text)
+    }
+
+    /// Sends a request to FRED, retrying according to the retry policy
+    async fn fetch(&self, request: &Request) -> Result<String, Error> {
+        let http = pipeline::http_request(request, &self.url_base, &self.api_key);
+
+        let mut attempts = Attempts::new(self.retry.as_ref());
+        loop {
+            match self.send(&http).await {
+                (Ok(text), _) => return Ok(text),
+                (Err(e), retry_after) => tokio::time::delay_for(attempts.failed(e, retry_after)?).await,
+            }
+        }
+    }
+
+    /// Sends a single attempt of a request, returning the body and any Retry-After delay
+    async fn send(&self, request: &HttpRequest) -> (Result<String, Error>, Option<Duration>) {
+        if let Some(limiter) = &self.limiter {
+            let wait = limiter.reserve();
+            if wait > Duration::from_secs(0) {
+                tokio::time::delay_for(wait).await;
+            }
+        }
+
+        pipeline::response(self.transport.get(request).await)
+    }
+
+    // ----------------------------------------------------------------------
+    // Series
+
+    /// [See fred_rs::series](../series/index.html)
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series.html#series_id)
+    pub async fn series(
+        &self,
+        series_id: &str,
+        builder: Option<series::Builder>
+    ) -> Result<series::Response, Error> {
+        self.get(request::series(series_id, builder)).await
+    }
+
+    /// [See fred_rs::series::categories](../series/categories/index.html)
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_categories.html#series_id)
+    pub async fn series_categories(
+        &self,
+        series_id: &str,
+        builder: Option<series::categories::Builder>
+    ) -> Result<category::Response, Error> {
+        self.get(request::series_categories(series_id, builder)).await
+    }
+
+    /// [See fred_rs::series::observation](../series/observation/index.html)
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    pub async fn series_observation(
+        &self,
+        series_id: &str,
+        builder: Option<series::observation::Builder>
+    ) -> Result<series::observation::Response, Error> {
+        self.get(request::series_observation(series_id, builder)).await
+    }
+
+    /// [See fred_rs::series::vintage](../series/vintage/index.html)
+    /// 
+    /// Requests output type 2 (VDALL) unless the builder sets one.
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    pub async fn series_observation_vintages(
+        &self,
+        series_id: &str,
+        builder: Option<series::observation::Builder>
+    ) -> Result<series::vintage::Response, Error> {
+        self.get(request::series_observation_vintages(series_id, builder)).await
+    }
+
+    /// Downloads the observations of many series concurrently
+    /// 
+    /// Up to `concurrency` requests are in flight at once.  See [fred_rs::bulk](../bulk/index.html).
+    /// 
+    /// # Arguments
+    /// `bulk` - The series to download and their observation arguments
+    pub async fn bulk_observations(&self, bulk: &BulkObservations) -> Vec<SeriesObservations> {
+        bulk.run_async(|series_id, builder| async move {
+            self.series_observation(&series_id, builder).await
+        }).await
+    }
+
+    /// Downloads the realtime periods of a series' observations
+    /// 
+    /// Any realtime arguments set by the builder are replaced.  Every page is requested, with the builder's limit as the page size, and `Error::Truncated` is returned if FRED sends fewer periods than it reports.  See [fred_rs::realtime](../realtime/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    /// `realtime_start` - The first day of the realtime range
+    /// `realtime_end` - The last day of the realtime range
+    pub async fn series_observation_realtime(
+        &self,
+        series_id: &str,
+        realtime_start: NaiveDate,
+        realtime_end: NaiveDate,
+        builder: Option<series::observation::Builder>
+    ) -> Result<RealtimeObservations, Error> {
+        let request = request::series_observation_realtime(series_id, realtime_start, realtime_end, builder);
+        let resp: series::observation::Response = self.get_all(request).await?;
+        RealtimeObservations::from_response(&resp)
+    }
+
+    /// Returns a series as it was known on a date
+    /// 
+    /// See [fred_rs::realtime](../realtime/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    /// `as_of` - The date the data is viewed from
+    pub async fn series_observation_as_of(
+        &self,
+        series_id: &str,
+        as_of: NaiveDate,
+        builder: Option<series::observation::Builder>
+    ) -> Result<TimeSeries, Error> {
+        let history = self.series_observation_realtime(series_id, as_of, as_of, builder).await?;
+        Ok(history.as_of(as_of))
+    }
+
+    /// Returns a series as it was known on each of several dates
+    /// 
+    /// A single request covers the realtime range from the earliest to the latest date.  See [fred_rs::realtime](../realtime/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    /// `dates` - The dates the data is viewed from
+    pub async fn series_observation_as_of_dates(
+        &self,
+        series_id: &str,
+        dates: &[NaiveDate],
+        builder: Option<series::observation::Builder>
+    ) -> Result<Vec<(NaiveDate, TimeSeries)>, Error> {
+        let (first, last) = realtime::date_bounds(dates)?;
+        let history = self.series_observation_realtime(series_id, first, last, builder).await?;
+        Ok(history.as_of_dates(dates))
+    }
+
+    /// Returns the revision history of every observation of a series
+    /// 
+    /// Fetches every vintage date of the series, then downloads the vintage matrix (output type 2) in chunks of at most `chunking::MAX_VINTAGE_DATES` vintages, paging each chunk until FRED's count is reached.  Any realtime, output type and vintage date arguments set by the builder are replaced.  See [fred_rs::revisions](../revisions/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    pub async fn series_revisions(
+        &self,
+        series_id: &str,
+        builder: Option<series::observation::Builder>
+    ) -> Result<Revisions, Error> {
+        let mut vintage_builder = series::vintagedates::Builder::new();
+        vintage_builder.limit(pagination::VINTAGE_DATE_PAGE_LIMIT);
+        let vintages = self.get_all(request::series_vintagedates(series_id, Some(vintage_builder))).await?;
+
+        let mut chunked = ChunkedObservations::new(series_id);
+        chunked.vintage_dates(&chunking::vintage_list(&vintages)?);
+        if let Some(b) = builder {
+            chunked.builder(b);
+        }
+
+        let mut responses = Vec::new();
+        for chunk in chunked.vintage_chunks() {
+            responses.push(self.get_all(request::series_observation_vintages(series_id, Some(chunk))).await?);
+        }
+        let matrix = chunking::merge_vintages(responses).matrix()?;
+        Ok(Revisions::from_vintages(&matrix))
+    }
+
+    /// Downloads the realtime data of several series into a store for backtests
+    /// 
+    /// Fetches the vintage dates of each series within the realtime window, then its realtime periods split into chunks of at most `chunking::MAX_VINTAGE_DATES` vintages.  Every page is requested and `Error::Truncated` is returned if FRED sends fewer results than it reports.  See [fred_rs::backtest](../backtest/index.html).
+    /// 
+    /// # Arguments
+    /// `store` - The store the series are added to
+    /// `series_ids` - The ids of the series
+    /// `realtime_start` - The first day of the realtime window
+    /// `realtime_end` - The last day of the realtime window
+    pub async fn prime_realtime_store<S: AsRef<str>>(
+        &self,
+        store: &mut RealtimeStore,
+        series_ids: &[S],
+        realtime_start: NaiveDate,
+        realtime_end: NaiveDate
+    ) -> Result<(), Error> {
+        for series_id in series_ids {
+            let series_id = series_id.as_ref();
+            let mut builder = series::vintagedates::Builder::new();
+            builder
+                .realtime_start(&realtime_start.format("%Y-%m-%d").to_string())
+                .realtime_end(&realtime_end.format("%Y-%m-%d").to_string())
+                .limit(pagination::VINTAGE_DATE_PAGE_LIMIT);
+            let vintages = self.get_all(request::series_vintagedates(series_id, Some(builder))).await?;
+
+            let chunked = ChunkedObservations::new(series_id);
+            let builders = chunked.realtime_chunks(realtime_start, realtime_end, &chunking::vintage_list(&vintages)?);
+            let history = RealtimeObservations::from_response(&self.fetch_chunks(&chunked, builders).await?)?;
+            store.insert_download(series_id, realtime_start, realtime_end, &vintages, history)?;
+        }
+        Ok(())
+    }
+
+    /// Downloads real-time observations for any number of vintage dates or any realtime window
+    /// 
+    /// The request is split into chunks FRED accepts, run concurrently and merged into one response of realtime periods.  Every page of each chunk is requested and `Error::Truncated` is returned if a chunk holds fewer periods than FRED reports.  See [fred_rs::chunking](../chunking/index.html).
+    /// 
+    /// # Arguments
+    /// `chunked` - The series, vintage dates or realtime window and chunk settings
+    pub async fn series_observation_chunked(
+        &self,
+        chunked: &ChunkedObservations
+    ) -> Result<series::observation::Response, Error> {
+        let builders = match chunked.realtime_window()? {
+            Some((start, end)) => {
+                let mut builder = series::vintagedates::Builder::new();
+                builder
+                    .realtime_start(&start.format("%Y-%m-%d").to_string())
+                    .realtime_end(&end.format("%Y-%m-%d").to_string())
+                    .limit(pagination::VINTAGE_DATE_PAGE_LIMIT);
+                let vintages = self.get_all(request::series_vintagedates(chunked.series_id(), Some(builder))).await?;
+                chunked.realtime_chunks(start, end, &chunking::vintage_list(&vintages)?)
+            },
+            None => chunked.vintage_chunks(),
+        };
+        self.fetch_chunks(chunked, builders).await
+    }
+
+    /// Requests every page of each chunk and merges the chunks into one response
+    async fn fetch_chunks(
+        &self,
+        chunked: &ChunkedObservations,
+        builders: Vec<series::observation::Builder>
+    ) -> Result<series::observation::Response, Error> {
+        chunking::merge(chunked.bulk(builders).run_async(|series_id, builder| async move {
+            self.get_all(request::series_observation(&series_id, builder)).await
+        }).await)
+    }
+
+    /// [See fred_rs::series::release](../series/release/index.html)
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_release.html#series_id)
+    pub async fn series_release(
+        &self,
+        series_id: &str,
+        builder: Option<series::release::Builder>
+    ) -> Result<release::Response, Error> {
+        self.get(request::series_release(series_id, builder)).await
+    }
+
+    /// [See fred_rs::series::tags](../series/tags/index.html)
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_tags.html#series_id)
+    pub async fn series_tags(
+        &self,
+        series_id: &str,
+        builder: Option<series::tags::Builder>
+    ) -> Result<tags::Response, Error> {
+        self.get(request::series_tags(series_id, builder)).await
+    }
+
+    /// [See fred_rs::series::updates](../series/updates/index.html)
+    pub async fn series_updates(
+        &self,
+        builder: Option<series::updates::Builder>
+    ) -> Result<series::updates::Response, Error> {
+        self.get(request::series_updates(builder)).await
+    }
+
+    /// [See fred_rs::series::vintagedates](../series/vintagedates/index.html)
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_vintagedates.html#series_id)
+    pub async fn series_vintagedates(
+        &self,
+        series_id: &str,
+        builder: Option<series::vintagedates::Builder>
+    ) -> Result<series::vintagedates::Response, Error> {
+        self.get(request::series_vintagedates(series_id, builder)).await
+    }
+
+    // ----------------------------------------------------------------------
+    // Series/Search
+
+    /// [See fred_rs::series::search](../series/search/index.html)
+    /// 
+    /// # Arguments
+    /// `search_text` - The words to match against economic data series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_search.html#search_text)
+    pub async fn series_search(
+        &self,
+        search_text: &str,
+        builder: Option<series::search::Builder>
+    ) -> Result<series::Response, Error> {
+        self.get(request::series_search(search_text, builder)).await
+    }
+
+    /// [See fred_rs::series::search::tags](../series/search/tags/index.html)
+    /// 
+    /// # Arguments
+    /// `series_search_text` - The words to match against economic data series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_search_tags.html#search_text)
+    pub async fn series_search_tags(
+        &self,
+        series_search_text: &str,
+        builder: Option<series::search::tags::Builder>
+    ) -> Result<tags::Response, Error> {
+        self.get(request::series_search_tags(series_search_text, builder)).await
+    }
+
+    /// [See fred_rs::series::search::related_tags](../series/search/related_tags/index.html)
+    /// 
+    /// # Arguments
+    /// `series_search_text` - The words to match against economic data series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_search_related_tags.html#search_text)
+    pub async fn series_search_related_tags(
+        &self,
+        series_search_text: &str,
+        builder: series::search::related_tags::Builder
+    ) -> Result<tags::Response, Error> {
+        match request::series_search_related_tags(series_search_text, builder) {
+            Ok(req) => self.get(req).await,
+            Err(e) => Err(e),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Tags
+
+    /// [See fred_rs::tags](../tags/index.html)
+    pub async fn tags(
+        &self,
+        builder: Option<tags::Builder>
+    ) -> Result<tags::Response, Error> {
+        self.get(request::tags(builder)).await
+    }
+
+    /// [See fred_rs::tags::series](../tags/series/index.html)
+    pub async fn tags_series(
+        &self,
+        builder: tags::series::Builder
+    ) -> Result<series::Response, Error> {
+        match request::tags_series(builder) {
+            Ok(req) => self.get(req).await,
+            Err(e) => Err(e),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Related Tags
+
+    /// [See fred_rs::related_tags](../related_tags/index.html)
+    pub async fn related_tags(
+        &self,
+        builder: related_tags::Builder
+    ) -> Result<tags::Response, Error> {
+        match request::related_tags(builder) {
+            Ok(req) => self.get(req).await,
+            Err(e) => Err(e),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Sources
+
+    /// [See fred_rs::sources](../sources/index.html)
+    pub async fn sources(
+        &self,
+        builder: Option<sources::Builder>
+    ) -> Result<source::Response, Error> {
+        self.get(request::sources(builder)).await
+    }
+
+    // ----------------------------------------------------------------------
+    // Source
+
+    /// [See fred_rs::source](../source/index.html)
+    /// 
+    /// # Arguments
+    /// `source_id` - The id for a source [[Link]](https://research.stlouisfed.org/docs/api/fred/source.html#source_id)
+    pub async fn source(
+        &self,
+        source_id: usize,
+        builder: Option<source::Builder>
+    ) -> Result<source::Response, Error> {
+        self.get(request::source(source_id, builder)).await
+    }
+
+    /// [See fred_rs::source::releases](../source/releases/index.html)
+    /// 
+    /// # Arguments
+    /// `source_id` - The id for a source [[Link]](https://research.stlouisfed.org/docs/api/fred/source_releases.html#source_id)
+    pub async fn source_releases(
+        &self,
+        source_id: usize,
+        builder: Option<source::releases::Builder>
+    ) -> Result<release::Response, Error> {
+        self.get(request::source_releases(source_id, builder)).await
+    }
+
+    // ----------------------------------------------------------------------
+    // Category
+
+    /// [See fred_rs::category](../category/index.html)
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category.html#category_id)
+    pub async fn category(
+        &self,
+        category_id: usize
+    ) -> Result<category::Response, Error> {
+        self.get(request::category(category_id)).await
+    }
+
+    /// [See fred_rs::category::children](../category/children/index.html)
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category_children.html#category_id)
+    pub async fn category_children(
+        &self,
+        category_id: usize,
+        builder: Option<category::children::Builder>
+    ) -> Result<category::Response, Error> {
+        self.get(request::category_children(category_id, builder)).await
+    }
+
+    /// [See fred_rs::category::related](../category/related/index.html)
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category_related.html#category_id)
+    pub async fn category_related(
+        &self,
+        category_id: usize,
+        builder: Option<category::related::Builder>
+    ) -> Result<category::Response, Error> {
+        self.get(request::category_related(category_id, builder)).await
+    }
+
+    /// [See fred_rs::category::series](../category/series/index.html)
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/series.html#category_id)
+    pub async fn category_series(
+        &self,
+        category_id: usize,
+        builder: Option<category::series::Builder>
+    ) -> Result<series::Response, Error> {
+        self.get(request::category_series(category_id, builder)).await
+    }
+
+    /// [See fred_rs::category::tags](../category/tags/index.html)
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category_tags.html#category_id)
+    pub async fn category_tags(
+        &self,
+        category_id: usize,
+        builder: Option<category::tags::Builder>
+    ) -> Result<tags::Response, Error> {
+        self.get(request::category_tags(category_id, builder)).await
+    }
+
+    /// [See fred_rs::category::related_tags](../category/related_tags/index.html)
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category_related_tags.html#category_id)
+    pub async fn category_related_tags(
+        &self,
+        category_id: usize,
+        builder: category::related_tags::Builder
+    ) -> Result<tags::Response, Error> {
+        match request::category_related_tags(category_id, builder) {
+            Ok(req) => self.get(req).await,
+            Err(e) => Err(e),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Releases
+
+    /// [See fred_rs::releases](../releases/index.html)
+    pub async fn releases(
+        &self,
+        builder: Option<releases::Builder>
+    ) -> Result<release::Response, Error> {
+        self.get(request::releases(builder)).await
+    }
+
+    /// [See fred_rs::releases::dates](../releases/dates/index.html)
+    pub async fn releases_dates(
+        &self,
+        builder: Option<releases::dates::Builder>
+    ) -> Result<releases::dates::Response, Error> {
+        self.get(request::releases_dates(builder)).await
+    }
+
+    // ----------------------------------------------------------------------
+    // Release
+
+    /// [See fred_rs::release](../release/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release.html#release_id)
+    pub async fn release(
+        &self,
+        release_id: usize,
+        builder: Option<release::Builder>
+    ) -> Result<release::Response, Error> {
+        self.get(request::release(release_id, builder)).await
+    }
+
+    /// [See fred_rs::release::series](../release/series/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_series.html#release_id)
+    pub async fn release_series(
+        &self,
+        release_id: usize,
+        builder: Option<release::series::Builder>
+    ) -> Result<series::Response, Error> {
+        self.get(request::release_series(release_id, builder)).await
+    }
+
+    /// [See fred_rs::release::dates](../release/dates/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_dates.html#release_id)
+    pub async fn release_dates(
+        &self,
+        release_id: usize,
+        builder: Option<release::dates::Builder>
+    ) -> Result<release::dates::Response, Error> {
+        self.get(request::release_dates(release_id, builder)).await
+    }
+
+    /// [See fred_rs::release::sources](../release/sources/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_sources.html#release_id)
+    pub async fn release_sources(
+        &self,
+        release_id: usize,
+        builder: Option<release::sources::Builder>
+    ) -> Result<source::Response, Error> {
+        self.get(request::release_sources(release_id, builder)).await
+    }
+
+    /// [See fred_rs::release::tags](../release/tags/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_tags.html#release_id)
+    pub async fn release_tags(
+        &self,
+        release_id: usize,
+        builder: Option<release::tags::Builder>
+    ) -> Result<tags::Response, Error> {
+        self.get(request::release_tags(release_id, builder)).await
+    }
+
+    /// [See fred_rs::release::related_tags](../release/related_tags/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_related_tags.html#release_id)
+    pub async fn release_related_tags(
+        &self,
+        release_id: usize,
+        builder: release::related_tags::Builder
+    ) -> Result<tags::Response, Error> {
+        match request::release_related_tags(release_id, builder) {
+            Ok(req) => self.get(req).await,
+            Err(e) => Err(e),
+        }
+    }
+
+    /// [See fred_rs::release::tables](../release/tables/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_tables.html#release_id)
+    pub async fn release_tables(
+        &self,
+        release_id: usize,
+        builder: Option<release::tables::Builder>
+    ) -> Result<release::tables::Response, Error> {
+        self.get(request::release_tables(release_id, builder)).await
+    }
+
+}
+
+/// Runs file system work on tokio's blocking thread pool
+async fn blocking<F, T>(f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(t) => Ok(t),
+        Err(e) => Err(Error::Cache(format!("Cache task failed: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{test_async_client, MemoryTransport};
+
+    #[test]
+    fn async_client_new() {
+        match AsyncFredClient::new() {
+            Ok(_) => assert_eq!(1, 1),
+            Err(msg) => {
+                println!("{}", msg);
+                assert_eq!(2, 1)
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn async_client_series() {
+        let transport = MemoryTransport::new();
+        transport.route("series?series_id=UNRATE", r#"{"realtime_start":"2020-01-01","realtime_end":"2020-01-01","seriess":[
+            {"id":"UNRATE","realtime_start":"2020-01-01","realtime_end":"2020-01-01","title":"Unemployment Rate",
+            "observation_start":"1948-01-01","observation_end":"2019-12-01","frequency":"Monthly","frequency_short":"M",
+            "units":"Percent","units_short":"%","seasonal_adjustment":"Seasonally Adjusted","seasonal_adjustment_short":"SA",
+            "last_updated":"2020-01-10 07:44:02-06","popularity":94}]}"#);
+        let c = test_async_client(&transport);
+
+        let resp: series::Response = match c.series("UNRATE", None).await {
+            Ok(resp) => resp,
+            Err(msg) => {
+                println!("{}", msg);
+                assert_eq!(2, 1);
+                return
+            },
+        };
+
+        assert_eq!(resp.seriess.len(), 1);
+        assert_eq!(resp.seriess[0].id.as_str(), "UNRATE");
+        assert_eq!(resp.seriess[0].title.as_str(), "Unemployment Rate");
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn async_client_cache() {
+        let dir = std::env::temp_dir().join(format!("fred_rs_async_client_cache_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let transport = MemoryTransport::new();
+        transport.route("category?category_id=125", r#"{"categories":[{"id":125,"name":"Trade Balance","parent_id":13}]}"#);
+
+        let c = AsyncFredClient::builder()
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .async_transport(transport.clone())
+            .cache(Cache::new(&dir))
+            .build_async()
+            .unwrap();
+
+        // the second request is answered from the cache
+        for _ in 0..2 {
+            let resp = c.category(125).await.unwrap();
+            assert_eq!(resp.categories[0].name.as_str(), "Trade Balance");
+        }
+        assert_eq!(transport.requests().len(), 1);
+        assert_eq!(c.cache().unwrap().entries().unwrap().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn async_client_validate_key() {
+        let c = AsyncFredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .build_async()
+            .unwrap();
+        match c.validate_key().await {
+            KeyStatus::Unreachable(e) => assert!(e.is_transport()),
+            status => panic!("unexpected key status {:?}", status),
+        }
+    }
+
+    #[tokio::test]
+    async fn async_client_validate_key_skips_cache() {
+        let dir = std::env::temp_dir().join(format!("fred_rs_async_client_validate_key_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = Cache::new(&dir);
+        cache.store(
+            "https://api.stlouisfed.org/fred/category?category_id=0&file_type=json",
+            "category",
+            r#"{"categories":[{"id":0,"name":"Categories","parent_id":0}]}"#,
+            SystemTime::now() + Duration::from_secs(3600)
+        );
+        let transport = MemoryTransport::new();
+        transport.route("category?category_id=0", r#"{"categories":[{"id":0,"name":"Categories","parent_id":0}]}"#);
+
+        let c = AsyncFredClient::builder()
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .async_transport(transport.clone())
+            .cache(cache)
+            .build_async()
+            .unwrap();
+        assert_eq!(c.validate_key().await, KeyStatus::Valid);
+        assert_eq!(transport.requests().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn async_client_rate_limit() {
+        let c = AsyncFredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .rate_limit(120, 1)
+            .build_async()
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            let _ = c.category(0).await;
+        }
+        // the second and third requests each wait half a second for a token
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+}
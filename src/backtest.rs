@@ -0,0 +1,420 @@
+//! Look-ahead-free replay of real-time data for backtests
+//!
+//! A [RealtimeStore](struct.RealtimeStore.html) holds, for each series, its vintage dates and every realtime period of its observations over a realtime window.  It is filled by `FredClient::prime_realtime_store()`, which pages fred/series/vintagedates and downloads the realtime periods in chunks like [ChunkedObservations](../chunking/struct.ChunkedObservations.html), can be saved to a JSON file and loaded again, so a backtest runs without further API calls once primed.
+//!
+//! `RealtimeStore::replay()` steps through a calendar of decision dates.  Each [ReplayStep](struct.ReplayStep.html) gives, for every series, the history as it was known on that date and the values published since the previous step (new observations and revisions).  Nothing published after the decision date is ever returned.
+//!
+//! ```
+//! use fred_rs::backtest::RealtimeStore;
+//! use fred_rs::client::FredClient;
+//! use chrono::NaiveDate;
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let start = NaiveDate::from_ymd_opt(2019, 1, 1).unwrap();
+//! let end = NaiveDate::from_ymd_opt(2019, 12, 31).unwrap();
+//! let mut store = RealtimeStore::new();
+//! if let Err(msg) = c.prime_realtime_store(&mut store, &["UNRATE", "PAYEMS"], start, end) {
+//!     println!("{}", msg);
+//!     return
+//! }
+//!
+//! let calendar: Vec<NaiveDate> = (0..12).map(|m| NaiveDate::from_ymd_opt(2019, m + 1, 15).unwrap()).collect();
+//! for step in store.replay(&calendar) {
+//!     for s in &step.series {
+//!         println!("{} {}: {} values known, {} published since the last step", step.date, s.series_id, s.history.len(), s.releases.len());
+//!     }
+//! }
+//! ```
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::realtime::RealtimeObservations;
+use crate::series::observation::{parse_date, Observation, MISSING_VALUE};
+use crate::series::vintagedates;
+use crate::timeseries::TimeSeries;
+
+#[derive(Clone, Debug, PartialEq)]
+/// The realtime data of one series over a realtime window
+pub struct StoredSeries {
+    /// The series ID
+    pub series_id: String,
+    /// First day of the realtime window
+    pub realtime_start: NaiveDate,
+    /// Last day of the realtime window
+    pub realtime_end: NaiveDate,
+    /// Dates on which new values or revisions were published, in ascending order
+    pub vintage_dates: Vec<NaiveDate>,
+    /// Every realtime period of the observations
+    pub history: RealtimeObservations,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Local store of realtime data used to replay backtests
+pub struct RealtimeStore {
+    series: Vec<StoredSeries>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// The data available on one decision date
+pub struct ReplayStep {
+    /// The decision date
+    pub date: NaiveDate,
+    /// The data of each series, in the order they were stored
+    pub series: Vec<SeriesSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// The data of one series available on a decision date
+pub struct SeriesSnapshot {
+    /// The series ID
+    pub series_id: String,
+    /// The series as it was known on the decision date
+    pub history: TimeSeries,
+    /// Values published after the previous decision date and up to this one
+    ///
+    /// On the first step these are the values in `history`.
+    pub releases: Vec<Observation>,
+    /// Vintage dates after the previous decision date and up to this one
+    pub vintage_dates: Vec<NaiveDate>,
+}
+
+/// Iterator over the steps of a replay
+#[derive(Debug)]
+pub struct Replay<'a> {
+    store: &'a RealtimeStore,
+    dates: std::vec::IntoIter<NaiveDate>,
+    previous: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoreFile {
+    series: Vec<SeriesFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SeriesFile {
+    series_id: String,
+    realtime_start: String,
+    realtime_end: String,
+    vintage_dates: Vec<String>,
+    observations: Vec<PointFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PointFile {
+    realtime_start: String,
+    realtime_end: String,
+    date: String,
+    value: String,
+}
+
+impl RealtimeStore {
+    /// Creates an empty store
+    pub fn new() -> RealtimeStore {
+        RealtimeStore { series: Vec::new() }
+    }
+
+    /// Adds a series, replacing any stored data for the same series ID
+    ///
+    /// # Arguments
+    /// * `series` - the realtime data of the series
+    pub fn insert(&mut self, mut series: StoredSeries) {
+        series.vintage_dates.sort();
+        match self.series.iter_mut().find(|s| s.series_id == series.series_id) {
+            Some(s) => *s = series,
+            None => self.series.push(series),
+        }
+    }
+
+    /// Adds a series from its vintage dates response and realtime periods
+    pub(crate) fn insert_download(
+        &mut self,
+        series_id: &str,
+        realtime_start: NaiveDate,
+        realtime_end: NaiveDate,
+        vintages: &vintagedates::Response,
+        history: RealtimeObservations,
+    ) -> Result<(), Error> {
+        let mut vintage_dates = Vec::with_capacity(vintages.vintage_dates.len());
+        for v in &vintages.vintage_dates {
+            vintage_dates.push(parse_date("vintage_date", v)?);
+        }
+        self.insert(StoredSeries {
+            series_id: String::from(series_id),
+            realtime_start,
+            realtime_end,
+            vintage_dates,
+            history,
+        });
+        Ok(())
+    }
+
+    /// Returns the stored series
+    pub fn series(&self) -> &[StoredSeries] {
+        &self.series
+    }
+
+    /// Returns the stored data of a series
+    ///
+    /// # Arguments
+    /// * `series_id` - The id for a series
+    pub fn get(&self, series_id: &str) -> Option<&StoredSeries> {
+        self.series.iter().find(|s| s.series_id == series_id)
+    }
+
+    /// Steps through decision dates in ascending order
+    ///
+    /// Decision dates should lie within the realtime window of each series; outside it the history of a series is empty.
+    ///
+    /// # Arguments
+    /// * `dates` - the decision dates, in any order; duplicates are removed
+    pub fn replay(&self, dates: &[NaiveDate]) -> Replay<'_> {
+        let mut dates = dates.to_vec();
+        dates.sort();
+        dates.dedup();
+        Replay {
+            store: self,
+            dates: dates.into_iter(),
+            previous: None,
+        }
+    }
+
+    /// Writes the store to a JSON file
+    ///
+    /// # Arguments
+    /// * `path` - the file to create or replace
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let file = StoreFile {
+            series: self.series.iter().map(to_file).collect(),
+        };
+        let text = match serde_json::to_string(&file) {
+            Ok(t) => t,
+            Err(e) => return Err(Error::Io(e.to_string())),
+        };
+        match fs::write(path, text) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(Error::Io(format!("Unable to write {}: {}", path.display(), e))),
+        }
+    }
+
+    /// Reads a store written by `save()`
+    ///
+    /// A file that cannot be read fails with `Error::Io` and one that is not a store fails with `Error::Decode`.
+    ///
+    /// # Arguments
+    /// * `path` - the store file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RealtimeStore, Error> {
+        let path = path.as_ref();
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) => return Err(Error::Io(format!("Unable to read {}: {}", path.display(), e))),
+        };
+        let file: StoreFile = match serde_json::from_str(&text) {
+            Ok(f) => f,
+            Err(e) => return Err(Error::decode(&path.display().to_string(), e, &text)),
+        };
+
+        let mut store = RealtimeStore::new();
+        for s in file.series {
+            store.insert(from_file(s)?);
+        }
+        Ok(store)
+    }
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = ReplayStep;
+
+    fn next(&mut self) -> Option<ReplayStep> {
+        let date = self.dates.next()?;
+        let previous = self.previous.replace(date);
+
+        let series = self.store.series.iter()
+            .map(|s| {
+                let published = |day: NaiveDate| match previous {
+                    Some(p) => p < day && day <= date,
+                    None => day <= date,
+                };
+                let releases = s.history.observations().iter()
+                    .filter(|o| match previous {
+                        Some(_) => published(o.realtime_start),
+                        None => o.realtime_start <= date && date <= o.realtime_end,
+                    })
+                    .cloned()
+                    .collect();
+
+                SeriesSnapshot {
+                    series_id: s.series_id.clone(),
+                    history: s.history.as_of(date),
+                    releases,
+                    vintage_dates: s.vintage_dates.iter().cloned().filter(|v| published(*v)).collect(),
+                }
+            })
+            .collect();
+
+        Some(ReplayStep { date, series })
+    }
+}
+
+fn to_file(s: &StoredSeries) -> SeriesFile {
+    let text = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+    SeriesFile {
+        series_id: s.series_id.clone(),
+        realtime_start: text(s.realtime_start),
+        realtime_end: text(s.realtime_end),
+        vintage_dates: s.vintage_dates.iter().map(|d| text(*d)).collect(),
+        observations: s.history.observations().iter()
+            .map(|o| PointFile {
+                realtime_start: text(o.realtime_start),
+                realtime_end: text(o.realtime_end),
+                date: text(o.date),
+                value: match o.value {
+                    Some(v) => v.to_string(),
+                    None => String::from(MISSING_VALUE),
+                },
+            })
+            .collect(),
+    }
+}
+
+fn from_file(s: SeriesFile) -> Result<StoredSeries, Error> {
+    let mut vintage_dates = Vec::with_capacity(s.vintage_dates.len());
+    for v in &s.vintage_dates {
+        vintage_dates.push(parse_date("vintage_date", v)?);
+    }
+
+    let mut observations = Vec::with_capacity(s.observations.len());
+    for p in s.observations {
+        let point = crate::series::observation::DataPoint {
+            realtime_start: p.realtime_start,
+            realtime_end: p.realtime_end,
+            date: p.date,
+            value: p.value,
+        };
+        observations.push(point.to_observation()?);
+    }
+
+    Ok(StoredSeries {
+        series_id: s.series_id,
+        realtime_start: parse_date("realtime_start", &s.realtime_start)?,
+        realtime_end: parse_date("realtime_end", &s.realtime_end)?,
+        vintage_dates,
+        history: RealtimeObservations::new(observations),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{page_body, test_client, MemoryTransport};
+    use std::env;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    const VINTAGES: &str = r#"{"realtime_start":"2020-01-01","realtime_end":"2020-12-31","order_by":"vintage_date","sort_order":"asc",
+        "count":3,"offset":0,"limit":10000,"vintage_dates":["2020-01-10","2020-02-07","2020-03-06"]}"#;
+    const REALTIME: &str = r#"{"realtime_start":"2020-01-01","realtime_end":"2020-12-31","observation_start":"1776-07-04","observation_end":"9999-12-31",
+        "units":"lin","output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":5,"offset":0,"limit":100000,
+        "observations":[
+            {"realtime_start":"2020-01-01","realtime_end":"2020-12-31","date":"2019-11-01","value":"3.5"},
+            {"realtime_start":"2020-01-01","realtime_end":"2020-02-06","date":"2019-12-01","value":"3.5"},
+            {"realtime_start":"2020-02-07","realtime_end":"2020-12-31","date":"2019-12-01","value":"3.6"},
+            {"realtime_start":"2020-02-07","realtime_end":"2020-12-31","date":"2020-01-01","value":"3.6"},
+            {"realtime_start":"2020-03-06","realtime_end":"2020-12-31","date":"2020-02-01","value":"3.5"}
+        ]}"#;
+
+    fn primed() -> (RealtimeStore, MemoryTransport) {
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=UNRATE&*realtime_start=2020-01-01&realtime_end=2020-12-31", VINTAGES)
+            .route("series/observations?series_id=UNRATE&*realtime_start=2020-01-01&realtime_end=2020-12-31", REALTIME);
+        let c = test_client(&transport);
+
+        let mut store = RealtimeStore::new();
+        c.prime_realtime_store(&mut store, &["UNRATE"], date(2020, 1, 1), date(2020, 12, 31)).unwrap();
+        (store, transport)
+    }
+
+    #[test]
+    fn replay_without_look_ahead() {
+        let (store, transport) = primed();
+        assert_eq!(transport.requests().len(), 2);
+
+        let steps: Vec<ReplayStep> = store.replay(&[date(2020, 2, 15), date(2020, 1, 15), date(2020, 2, 1)]).collect();
+        assert_eq!(steps.len(), 3);
+
+        let first = &steps[0].series[0];
+        assert_eq!(steps[0].date, date(2020, 1, 15));
+        assert_eq!(first.history.points(), &[(date(2019, 11, 1), Some(3.5)), (date(2019, 12, 1), Some(3.5))]);
+        assert_eq!(first.releases.len(), 2);
+        assert_eq!(first.vintage_dates, vec![date(2020, 1, 10)]);
+
+        // nothing was published between the first and second steps
+        assert_eq!(steps[1].series[0].history, first.history);
+        assert!(steps[1].series[0].releases.is_empty());
+
+        let third = &steps[2].series[0];
+        assert_eq!(third.history.get(date(2019, 12, 1)), Some(Some(3.6)));
+        assert_eq!(third.history.get(date(2020, 2, 1)), None);
+        assert_eq!(third.releases.iter().map(|o| o.date).collect::<Vec<_>>(), vec![date(2019, 12, 1), date(2020, 1, 1)]);
+        assert_eq!(third.vintage_dates, vec![date(2020, 2, 7)]);
+    }
+
+    #[test]
+    fn store_save_and_load() {
+        let (store, _) = primed();
+        let path = env::temp_dir().join(format!("fred_rs_backtest_store_{}.json", std::process::id()));
+        store.save(&path).unwrap();
+
+        let loaded = RealtimeStore::load(&path).unwrap();
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.get("UNRATE").unwrap().vintage_dates.len(), 3);
+
+        fs::write(&path, "{\"series\":").unwrap();
+        match RealtimeStore::load(&path) {
+            Err(Error::Decode { .. }) => (),
+            _ => assert_eq!(2, 1),
+        }
+
+        fs::remove_file(&path).unwrap();
+        match RealtimeStore::load(&path) {
+            Err(Error::Io(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn prime_fails_on_missing_periods() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=UNRATE&*realtime_start=2020-01-01&realtime_end=2020-12-31", VINTAGES)
+            .route("series/observations?series_id=UNRATE&*offset=0", &REALTIME.replace("\"count\":5", "\"count\":6"))
+            .route("series/observations?series_id=UNRATE&*offset=5", &page_body::<&str>("observations", ("2020-01-01", "2020-12-31"), 6, 5, &[]));
+        let c = test_client(&transport);
+
+        let mut store = RealtimeStore::new();
+        match c.prime_realtime_store(&mut store, &["UNRATE"], date(2020, 1, 1), date(2020, 12, 31)) {
+            Err(Error::Truncated { count, received, .. }) => {
+                assert_eq!(count, 6);
+                assert_eq!(received, 5);
+            },
+            _ => assert_eq!(2, 1),
+        }
+        assert!(store.get("UNRATE").is_none());
+    }
+}
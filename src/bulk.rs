@@ -0,0 +1,337 @@
+//! Concurrent download of observations for many series
+//!
+//! A [BulkObservations](struct.BulkObservations.html) request lists series IDs with a shared or per-series [observation Builder](../series/observation/struct.Builder.html).  `FredClient::bulk_observations()` fetches them on a pool of threads and `AsyncFredClient::bulk_observations()` runs the requests concurrently on the current task.  Both wait on the client's rate limiter, so a limit set with `FredClientBuilder::rate_limit()` holds across the whole batch.
+//!
+//! Every series gets its own result, so one failing series does not abort the batch.  Results are returned in the order the series were added.
+//!
+//! ```
+//! use fred_rs::bulk::BulkObservations;
+//! use fred_rs::client::FredClient;
+//! use fred_rs::series::observation::Builder;
+//!
+//! let c = match FredClient::builder().rate_limit(120, 4).build() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let mut builder = Builder::new();
+//! builder.observation_start("2000-01-01");
+//!
+//! let mut bulk = BulkObservations::new();
+//! bulk.series_ids(&["GNPCA", "GDPC1", "UNRATE"])
+//!     .builder(builder)
+//!     .concurrency(4)
+//!     .on_progress(|p| println!("{}/{} {}", p.completed, p.total, p.series_id));
+//!
+//! for s in c.bulk_observations(&bulk) {
+//!     match s.result {
+//!         Ok(resp) => println!("{}: {} observations", s.series_id, resp.observations.len()),
+//!         Err(msg) => println!("{}: {}", s.series_id, msg),
+//!     }
+//! }
+//! ```
+
+use futures_util::stream::{self, StreamExt};
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::error::Error;
+use crate::series::observation::{Builder, Response};
+
+/// Number of requests in flight when no concurrency is set
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+type ProgressFn = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+#[derive(Clone)]
+/// A list of series to download with their observation arguments
+pub struct BulkObservations {
+    series: Vec<(String, Option<Builder>)>,
+    shared: Option<Builder>,
+    concurrency: usize,
+    progress: Option<ProgressFn>,
+}
+
+#[derive(Clone, Debug)]
+/// Progress reported after each series completes
+pub struct Progress {
+    /// Number of series finished so far, including this one
+    pub completed: usize,
+    /// Number of series in the batch
+    pub total: usize,
+    /// The series that just finished
+    pub series_id: String,
+    /// True if the series was downloaded successfully
+    pub succeeded: bool,
+}
+
+#[derive(Debug)]
+/// The outcome of downloading a single series
+pub struct SeriesObservations {
+    /// The series ID
+    pub series_id: String,
+    /// The observations or the error returned for the series
+    pub result: Result<Response, Error>,
+}
+
+impl BulkObservations {
+    /// Creates an empty batch
+    pub fn new() -> BulkObservations {
+        BulkObservations {
+            series: Vec::new(),
+            shared: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            progress: None,
+        }
+    }
+
+    /// Adds a series using the shared builder
+    ///
+    /// # Arguments
+    /// * `series_id` - The id for a series
+    pub fn series(&mut self, series_id: &str) -> &mut BulkObservations {
+        self.series.push((String::from(series_id), None));
+        self
+    }
+
+    /// Adds several series using the shared builder
+    ///
+    /// # Arguments
+    /// * `series_ids` - The ids of the series
+    pub fn series_ids<S: AsRef<str>>(&mut self, series_ids: &[S]) -> &mut BulkObservations {
+        for id in series_ids {
+            self.series(id.as_ref());
+        }
+        self
+    }
+
+    /// Adds a series with its own builder
+    ///
+    /// # Arguments
+    /// * `series_id` - The id for a series
+    /// * `builder` - arguments used for this series instead of the shared builder
+    pub fn series_with(&mut self, series_id: &str, builder: Builder) -> &mut BulkObservations {
+        self.series.push((String::from(series_id), Some(builder)));
+        self
+    }
+
+    /// Sets the builder used for series added without their own
+    ///
+    /// # Arguments
+    /// * `builder` - arguments applied to each series
+    pub fn builder(&mut self, builder: Builder) -> &mut BulkObservations {
+        self.shared = Some(builder);
+        self
+    }
+
+    /// Sets the maximum number of requests in flight
+    ///
+    /// # Arguments
+    /// * `requests` - number of concurrent requests (minimum 1)
+    pub fn concurrency(&mut self, requests: usize) -> &mut BulkObservations {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    /// Calls a function after each series completes
+    ///
+    /// With the blocking client the function is called from the worker threads.
+    ///
+    /// # Arguments
+    /// * `f` - progress callback
+    pub fn on_progress<F>(&mut self, f: F) -> &mut BulkObservations
+    where F: Fn(&Progress) + Send + Sync + 'static
+    {
+        self.progress = Some(Arc::new(f));
+        self
+    }
+
+    /// Returns the number of series in the batch
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Returns true if the batch holds no series
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    /// Returns each series with the builder that applies to it
+    fn jobs(&self) -> Vec<(String, Option<Builder>)> {
+        self.series.iter()
+            .map(|(id, builder)| match builder {
+                Some(b) => (id.clone(), Some(b.clone())),
+                None => (id.clone(), self.shared.clone()),
+            })
+            .collect()
+    }
+
+    fn report(&self, completed: &AtomicUsize, series_id: &str, succeeded: bool) {
+        let completed = completed.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(progress) = &self.progress {
+            progress(&Progress {
+                completed,
+                total: self.series.len(),
+                series_id: String::from(series_id),
+                succeeded,
+            });
+        }
+    }
+
+    /// Downloads every series on a pool of threads
+    pub(crate) fn run_blocking<F>(&self, fetch: F) -> Vec<SeriesObservations>
+    where F: Fn(&str, Option<Builder>) -> Result<Response, Error> + Sync
+    {
+        let jobs = self.jobs();
+        let next = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<SeriesObservations>>> = Mutex::new(jobs.iter().map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(jobs.len()) {
+                scope.spawn(|| loop {
+                    let idx = next.fetch_add(1, Ordering::SeqCst);
+                    let (series_id, builder) = match jobs.get(idx) {
+                        Some(job) => job.clone(),
+                        None => break,
+                    };
+
+                    let result = fetch(&series_id, builder);
+                    self.report(&completed, &series_id, result.is_ok());
+
+                    let mut results = match results.lock() {
+                        Ok(r) => r,
+                        Err(poisoned) => poisoned.into_inner(),
+                    };
+                    results[idx] = Some(SeriesObservations { series_id, result });
+                });
+            }
+        });
+
+        let results = match results.into_inner() {
+            Ok(r) => r,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        results.into_iter().flatten().collect()
+    }
+
+    /// Downloads every series with up to `concurrency` requests in flight
+    pub(crate) async fn run_async<F, Fut>(&self, fetch: F) -> Vec<SeriesObservations>
+    where
+        F: Fn(String, Option<Builder>) -> Fut,
+        Fut: Future<Output = Result<Response, Error>>,
+    {
+        let completed = AtomicUsize::new(0);
+        let fetch = &fetch;
+        let completed = &completed;
+
+        let mut results: Vec<(usize, SeriesObservations)> = stream::iter(self.jobs().into_iter().enumerate())
+            .map(|(idx, (series_id, builder))| async move {
+                let result = fetch(series_id.clone(), builder).await;
+                self.report(completed, &series_id, result.is_ok());
+                (idx, SeriesObservations { series_id, result })
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        results.sort_by_key(|(idx, _)| *idx);
+        results.into_iter().map(|(_, s)| s).collect()
+    }
+}
+
+impl Default for BulkObservations {
+    fn default() -> BulkObservations {
+        BulkObservations::new()
+    }
+}
+
+impl Debug for BulkObservations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BulkObservations")
+            .field("series", &self.series)
+            .field("shared", &self.shared)
+            .field("concurrency", &self.concurrency)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{test_async_client, test_client, HttpResponse, MemoryTransport};
+
+    fn observations(value: &str) -> String {
+        format!(
+            r#"{{"realtime_start":"2020-01-01","realtime_end":"2020-01-01","observation_start":"1776-07-04","observation_end":"9999-12-31",
+            "units":"lin","output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":1,"offset":0,"limit":100000,
+            "observations":[{{"realtime_start":"2020-01-01","realtime_end":"2020-01-01","date":"2019-01-01","value":"{}"}}]}}"#,
+            value
+        )
+    }
+
+    fn transport() -> MemoryTransport {
+        let transport = MemoryTransport::new();
+        transport
+            .route("series_id=GNPCA&*observation_start=2000-01-01", &observations("1.0"))
+            .route("series_id=GDPC1&*observation_start=2010-01-01", &observations("2.0"))
+            .route_response(
+                "series_id=NOPE&",
+                HttpResponse::new(400, r#"{"error_code":400,"error_message":"Bad Request.  The series does not exist."}"#)
+            );
+        transport
+    }
+
+    fn batch(progress: Arc<Mutex<Vec<Progress>>>) -> BulkObservations {
+        let mut shared = Builder::new();
+        shared.observation_start("2000-01-01");
+        let mut own = Builder::new();
+        own.observation_start("2010-01-01");
+
+        let mut bulk = BulkObservations::new();
+        bulk.series_ids(&["GNPCA", "NOPE"])
+            .series_with("GDPC1", own)
+            .builder(shared)
+            .concurrency(2)
+            .on_progress(move |p| progress.lock().unwrap().push(p.clone()));
+        bulk
+    }
+
+    fn check(results: &[SeriesObservations], progress: &[Progress]) {
+        let ids: Vec<&str> = results.iter().map(|s| s.series_id.as_str()).collect();
+        assert_eq!(ids, vec!["GNPCA", "NOPE", "GDPC1"]);
+        assert_eq!(results[0].result.as_ref().unwrap().observations[0].value, "1.0");
+        assert!(results[1].result.as_ref().unwrap_err().is_bad_parameter());
+        assert_eq!(results[2].result.as_ref().unwrap().observations[0].value, "2.0");
+
+        assert_eq!(progress.len(), 3);
+        assert_eq!(progress.iter().filter(|p| p.succeeded).count(), 2);
+        assert!(progress.iter().any(|p| p.completed == 3 && p.total == 3));
+    }
+
+    #[test]
+    fn bulk_observations_blocking() {
+        let c = test_client(&transport());
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let results = c.bulk_observations(&batch(progress.clone()));
+        check(&results, &progress.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn bulk_observations_async() {
+        let c = test_async_client(&transport());
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let results = c.bulk_observations(&batch(progress.clone())).await;
+        check(&results, &progress.lock().unwrap());
+    }
+}
@@ -0,0 +1,479 @@
+//! Persistent on-disk cache for FRED responses
+//!
+//! A [Cache](struct.Cache.html) attached to a client stores each successful response body in a directory, keyed by the request URL with the API key removed.  Later requests for the same URL are answered from disk until the entry expires.
+//!
+//! Entries expire after a time-to-live that can be set for each endpoint.  Observation data can instead be kept until the next scheduled date of the series' release, found with the fred/series/release and fred/release/dates endpoints.  In offline mode the cache answers every request it can and returns `Error::NotCached` for everything else without touching the network.
+//!
+//! ```
+//! use fred_rs::cache::Cache;
+//! use fred_rs::client::FredClient;
+//! use std::time::Duration;
+//!
+//! let mut cache = Cache::new("/tmp/fred-rs-cache");
+//! cache
+//!     .ttl("category", Duration::from_secs(30 * 24 * 60 * 60))
+//!     .ttl("series/observations", Duration::from_secs(6 * 60 * 60))
+//!     .release_aware_observations(true);
+//!
+//! let client = match FredClient::builder().cache(cache).build() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! // remove expired entries
+//! if let Some(cache) = client.cache() {
+//!     match cache.prune() {
+//!         Ok(n) => println!("removed {} entries", n),
+//!         Err(msg) => println!("{}", msg),
+//!     }
+//! }
+//! ```
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::Error;
+use crate::releases::dates::ReleaseDate;
+
+const FILE_EXTENSION: &str = "json";
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// How long observations are kept on the day of their release
+pub(crate) const RELEASE_DAY_TTL: Duration = Duration::from_secs(HOUR);
+
+#[derive(Clone, Debug)]
+/// Configuration and handle for an on-disk response cache
+///
+/// Cloning a cache gives another handle to the same directory.
+pub struct Cache {
+    dir: PathBuf,
+    default_ttl: Duration,
+    ttls: HashMap<String, Duration>,
+    offline: bool,
+    release_aware: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    key: String,
+    endpoint: String,
+    stored_at: u64,
+    expires_at: u64,
+    body: String,
+}
+
+#[derive(Clone, Debug)]
+/// Information about a single cached response
+pub struct CacheEntry {
+    /// The request URL with the API key removed
+    pub key: String,
+    /// The endpoint that was requested (e.g. series/observations)
+    pub endpoint: String,
+    /// When the response was stored
+    pub stored_at: SystemTime,
+    /// When the entry expires
+    pub expires_at: SystemTime,
+    /// Size of the response body in bytes
+    pub size: usize,
+}
+
+impl CacheEntry {
+    /// Returns true if the entry has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= SystemTime::now()
+    }
+}
+
+impl Cache {
+    /// Creates a cache stored in the given directory
+    ///
+    /// The directory is created when the first entry is stored.  The default time-to-live is one day, except for slowly changing metadata: category, source and release lookups are kept for seven days.
+    ///
+    /// # Arguments
+    /// * `dir` - directory holding the cache entries
+    pub fn new<P: AsRef<Path>>(dir: P) -> Cache {
+        let mut ttls = HashMap::new();
+        for endpoint in ["category", "source", "sources", "release", "releases", "series/categories", "series/release"].iter() {
+            ttls.insert(String::from(*endpoint), Duration::from_secs(7 * DAY));
+        }
+        ttls.insert(String::from("series/updates"), Duration::from_secs(HOUR));
+        ttls.insert(String::from("releases/dates"), Duration::from_secs(12 * HOUR));
+        ttls.insert(String::from("release/dates"), Duration::from_secs(12 * HOUR));
+
+        Cache {
+            dir: dir.as_ref().to_path_buf(),
+            default_ttl: Duration::from_secs(DAY),
+            ttls,
+            offline: false,
+            release_aware: false,
+        }
+    }
+
+    /// Returns the cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Sets the time-to-live for endpoints without a specific setting
+    ///
+    /// # Arguments
+    /// * `ttl` - how long entries stay valid
+    pub fn default_ttl(&mut self, ttl: Duration) -> &mut Cache {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Sets the time-to-live for an endpoint
+    ///
+    /// A setting for a top level path such as `category` applies to every endpoint below it (e.g. category/children) that has no setting of its own.
+    ///
+    /// # Arguments
+    /// * `endpoint` - endpoint path (e.g. series/observations)
+    /// * `ttl` - how long entries stay valid
+    pub fn ttl(&mut self, endpoint: &str, ttl: Duration) -> &mut Cache {
+        self.ttls.insert(String::from(endpoint.trim_matches('/')), ttl);
+        self
+    }
+
+    /// Answers requests only from the cache
+    ///
+    /// In offline mode the client never sends a request; anything not in the cache returns `Error::NotCached`.  Expired entries are still used.
+    ///
+    /// # Arguments
+    /// * `enable` - true to never touch the network
+    pub fn offline(&mut self, enable: bool) -> &mut Cache {
+        self.offline = enable;
+        self
+    }
+
+    /// Keeps observation data until the series' next scheduled release
+    ///
+    /// When enabled, storing a series/observations response looks up the series' release and the next date it is scheduled (fred/series/release and fred/release/dates).  The entry expires at the start of that date (UTC).  If the release is today the data may not be published yet, so the entry is kept for one hour only.  If no date is found the normal time-to-live is used.
+    ///
+    /// # Arguments
+    /// * `enable` - true to expire observations at the next release
+    pub fn release_aware_observations(&mut self, enable: bool) -> &mut Cache {
+        self.release_aware = enable;
+        self
+    }
+
+    /// Returns true if the cache never touches the network
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns true if observations expire at the next release date
+    pub fn is_release_aware(&self) -> bool {
+        self.release_aware
+    }
+
+    /// Returns the time-to-live used for an endpoint
+    pub fn ttl_for(&self, endpoint: &str) -> Duration {
+        if let Some(ttl) = self.ttls.get(endpoint) {
+            return *ttl;
+        }
+        match endpoint.split('/').next().and_then(|top| self.ttls.get(top)) {
+            Some(ttl) => *ttl,
+            None => self.default_ttl,
+        }
+    }
+
+    /// Lists every entry in the cache
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, Error> {
+        let mut entries = Vec::new();
+        for path in self.entry_paths()? {
+            if let Some(stored) = read_entry(&path) {
+                entries.push(CacheEntry {
+                    size: stored.body.len(),
+                    key: stored.key,
+                    endpoint: stored.endpoint,
+                    stored_at: from_unix(stored.stored_at),
+                    expires_at: from_unix(stored.expires_at),
+                });
+            }
+        }
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(entries)
+    }
+
+    /// Returns the entry for a request URL (without the API key), if cached
+    pub fn entry(&self, key: &str) -> Option<CacheEntry> {
+        let stored = read_entry(&self.path_for(key))?;
+        if stored.key != key {
+            return None;
+        }
+        Some(CacheEntry {
+            size: stored.body.len(),
+            key: stored.key,
+            endpoint: stored.endpoint,
+            stored_at: from_unix(stored.stored_at),
+            expires_at: from_unix(stored.expires_at),
+        })
+    }
+
+    /// Removes every entry and returns the number removed
+    pub fn purge(&self) -> Result<usize, Error> {
+        self.remove_where(|_| true)
+    }
+
+    /// Removes every entry for an endpoint and returns the number removed
+    ///
+    /// # Arguments
+    /// * `endpoint` - endpoint path (e.g. series/observations)
+    pub fn purge_endpoint(&self, endpoint: &str) -> Result<usize, Error> {
+        let endpoint = endpoint.trim_matches('/');
+        self.remove_where(|e| e.endpoint == endpoint)
+    }
+
+    /// Removes every entry whose key contains the given text and returns the number removed
+    ///
+    /// # Arguments
+    /// * `pattern` - text to look for in the request URL (e.g. series_id=GNPCA)
+    pub fn purge_matching(&self, pattern: &str) -> Result<usize, Error> {
+        self.remove_where(|e| e.key.contains(pattern))
+    }
+
+    /// Removes every expired entry and returns the number removed
+    pub fn prune(&self) -> Result<usize, Error> {
+        let now = unix_now();
+        self.remove_where(|e| e.expires_at <= now)
+    }
+
+    /// Returns a cached response body that has not expired (or any cached body in offline mode)
+    pub(crate) fn lookup(&self, key: &str) -> Option<String> {
+        let stored = read_entry(&self.path_for(key))?;
+        if stored.key != key {
+            return None; // hash collision
+        }
+        if !self.offline && stored.expires_at <= unix_now() {
+            return None;
+        }
+        Some(stored.body)
+    }
+
+    /// Stores a response body
+    ///
+    /// Failing to write the entry does not fail the request, so errors are ignored.
+    pub(crate) fn store(&self, key: &str, endpoint: &str, body: &str, expires_at: SystemTime) {
+        let stored = StoredEntry {
+            key: String::from(key),
+            endpoint: String::from(endpoint),
+            stored_at: unix_now(),
+            expires_at: to_unix(expires_at),
+            body: String::from(body),
+        };
+
+        let text = match serde_json::to_string(&stored) {
+            Ok(t) => t,
+            Err(_) => return,
+        };
+        if fs::create_dir_all(&self.dir).is_ok() {
+            let _ = fs::write(self.path_for(key), text);
+        }
+    }
+
+    /// Returns the expiry time for a response stored now
+    pub(crate) fn expiry(&self, endpoint: &str) -> SystemTime {
+        SystemTime::now() + self.ttl_for(endpoint)
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.{}", fnv1a(key), FILE_EXTENSION))
+    }
+
+    fn entry_paths(&self) -> Result<Vec<PathBuf>, Error> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(d) => d,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::Cache(format!("Unable to read {}: {}", self.dir.display(), e))),
+        };
+
+        Ok(dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == FILE_EXTENSION))
+            .collect())
+    }
+
+    fn remove_where<F: Fn(&StoredEntry) -> bool>(&self, predicate: F) -> Result<usize, Error> {
+        let mut removed = 0;
+        for path in self.entry_paths()? {
+            let matches = match read_entry(&path) {
+                Some(stored) => predicate(&stored),
+                None => false,
+            };
+            if matches {
+                match fs::remove_file(&path) {
+                    Ok(_) => removed += 1,
+                    Err(e) => return Err(Error::Cache(format!("Unable to remove {}: {}", path.display(), e))),
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Returns when an observation entry should expire given the upcoming release dates
+///
+/// The entry expires at the start of the next release date of `release_id`.  On the release date itself the data may not be published yet, so the entry is kept for `RELEASE_DAY_TTL` only.  Returns None if no upcoming release is listed.
+pub(crate) fn release_expiry(
+    release_id: usize,
+    dates: &[ReleaseDate],
+    now: DateTime<Utc>
+) -> Option<SystemTime> {
+    let today = now.date_naive();
+    let next = dates.iter()
+        .filter(|d| d.release_id == release_id)
+        .filter_map(|d| NaiveDate::parse_from_str(&d.date, "%Y-%m-%d").ok())
+        .filter(|d| *d >= today)
+        .min()?;
+
+    if next == today {
+        return Some(from_unix(now.timestamp().max(0) as u64) + RELEASE_DAY_TTL);
+    }
+    let midnight = Utc.from_utc_datetime(&next.and_hms_opt(0, 0, 0)?);
+
+    Some(from_unix(midnight.timestamp().max(0) as u64))
+}
+
+fn read_entry(path: &Path) -> Option<StoredEntry> {
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// 64 bit FNV-1a hash, used because its output is stable across Rust releases
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn unix_now() -> u64 {
+    to_unix(SystemTime::now())
+}
+
+fn to_unix(time: SystemTime) -> u64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+fn from_unix(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_cache(name: &str) -> Cache {
+        let dir = env::temp_dir().join(format!("fred_rs_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(dir)
+    }
+
+    #[test]
+    fn cache_store_lookup_and_expiry() {
+        let cache = temp_cache("store_lookup");
+        let key = "https://api.stlouisfed.org/fred/category?category_id=0&file_type=json";
+
+        assert_eq!(cache.lookup(key), None);
+        cache.store(key, "category", "{}", SystemTime::now() + Duration::from_secs(60));
+        assert_eq!(cache.lookup(key), Some(String::from("{}")));
+
+        cache.store(key, "category", "{}", SystemTime::now() - Duration::from_secs(60));
+        assert_eq!(cache.lookup(key), None);
+
+        let mut offline = cache.clone();
+        offline.offline(true);
+        assert_eq!(offline.lookup(key), Some(String::from("{}")));
+
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn cache_management() {
+        let cache = temp_cache("management");
+        let later = SystemTime::now() + Duration::from_secs(60);
+        let earlier = SystemTime::now() - Duration::from_secs(60);
+        cache.store("series?series_id=GNPCA", "series", "{}", later);
+        cache.store("series/observations?series_id=GNPCA", "series/observations", "{}", earlier);
+        cache.store("series/observations?series_id=UNRATE", "series/observations", "{}", later);
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries.iter().filter(|e| e.is_expired()).count(), 1);
+        assert_eq!(cache.entry("series?series_id=GNPCA").unwrap().endpoint.as_str(), "series");
+
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert_eq!(cache.purge_matching("UNRATE").unwrap(), 1);
+        assert_eq!(cache.purge_endpoint("series").unwrap(), 1);
+        assert!(cache.entries().unwrap().is_empty());
+        assert_eq!(cache.purge().unwrap(), 0);
+
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn cache_ttl_lookup() {
+        let mut cache = temp_cache("ttl");
+        cache
+            .default_ttl(Duration::from_secs(10))
+            .ttl("category/children", Duration::from_secs(20));
+
+        assert_eq!(cache.ttl_for("series/observations"), Duration::from_secs(10));
+        assert_eq!(cache.ttl_for("category/children"), Duration::from_secs(20));
+        assert_eq!(cache.ttl_for("category/series"), Duration::from_secs(7 * DAY));
+    }
+
+    #[test]
+    fn cache_release_expiry() {
+        let text = r#"{
+            "realtime_start": "2020-03-01", "realtime_end": "2020-04-30",
+            "order_by": "release_date", "sort_order": "asc",
+            "count": 3, "offset": 0, "limit": 1000,
+            "release_dates": [
+                {"release_id": 50, "release_name": "Employment Situation", "date": "2020-03-06"},
+                {"release_id": 53, "release_name": "Gross Domestic Product", "date": "2020-03-26"},
+                {"release_id": 50, "release_name": "Employment Situation", "date": "2020-04-03"}
+            ]
+        }"#;
+        let dates: crate::releases::dates::Response = serde_json::from_str(text).unwrap();
+        // 2020-03-10T12:00:00Z
+        let now = Utc.timestamp_opt(1_583_841_600, 0).unwrap();
+
+        // 2020-04-03T00:00:00Z
+        assert_eq!(release_expiry(50, &dates.release_dates, now), Some(from_unix(1_585_872_000)));
+        assert_eq!(release_expiry(9, &dates.release_dates, now), None);
+    }
+
+    #[test]
+    fn cache_release_day_expiry() {
+        let dates: crate::releases::dates::Response = serde_json::from_str(r#"{
+            "realtime_start": "2020-03-06", "realtime_end": "2020-03-06",
+            "order_by": "release_date", "sort_order": "asc",
+            "count": 1, "offset": 0, "limit": 1,
+            "release_dates": [{"release_id": 50, "date": "2020-03-06"}]
+        }"#).unwrap();
+        // 2020-03-06T12:00:00Z, before an 08:30 ET release
+        let now = Utc.timestamp_opt(1_583_496_000, 0).unwrap();
+
+        // kept for an hour rather than until the end of the release day
+        assert_eq!(release_expiry(50, &dates.release_dates, now), Some(from_unix(1_583_496_000) + RELEASE_DAY_TTL));
+    }
+}
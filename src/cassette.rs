@@ -0,0 +1,346 @@
+//! Record and replay FRED responses for deterministic tests
+//!
+//! A [Cassette](struct.Cassette.html) attached to a client with `FredClientBuilder::cassette()` either records every request and response to a JSON file or serves them back from it.
+//!
+//! * In record mode requests are sent as usual and each interaction is appended to the file.  The API key is never written; interactions are keyed by the request URL without it.
+//! * In replay mode nothing is sent.  Each request is answered from the file and a request without a recording fails with `Error::Cassette`, so tests notice when the requests they make change.
+//!
+//! A test suite can record once with a real API key and then replay in CI without network access or secrets.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::cassette::Cassette;
+//!
+//! let path = std::env::temp_dir().join("fred_rs_cassette_doc.json");
+//! std::fs::write(&path, r#"{"interactions":[{
+//!     "url": "https://api.stlouisfed.org/fred/category?category_id=125&file_type=json",
+//!     "status": 200,
+//!     "body": "{\"categories\":[{\"id\":125,\"name\":\"Trade Balance\",\"parent_id\":13}]}"
+//! }]}"#).unwrap();
+//!
+//! let cassette = match Cassette::replay(&path) {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! let client = FredClient::builder().cassette(cassette).build().unwrap();
+//!
+//! let resp = client.category(125).unwrap();
+//! assert_eq!(resp.categories[0].name, "Trade Balance");
+//! assert!(client.category(13).is_err());
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::Error;
+use crate::transport::{AsyncTransport, HttpRequest, HttpResponse, Transport, TransportFuture};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Whether a cassette records new interactions or replays stored ones
+pub enum CassetteMode {
+    /// Send requests and append each interaction to the file
+    Record,
+    /// Answer requests from the file without sending them
+    Replay,
+}
+
+#[derive(Clone, Debug)]
+/// A file of recorded request/response interactions
+///
+/// Clones share the same recording.
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    tape: Arc<Mutex<Tape>>,
+}
+
+#[derive(Debug, Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+    /// Number of times each interaction has been replayed
+    plays: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    /// Request URL without the API key
+    url: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Cassette {
+    /// Creates a cassette that records to a file
+    ///
+    /// The file is replaced by the first recorded interaction.
+    ///
+    /// # Arguments
+    /// * `path` - file the interactions are written to
+    pub fn record<P: AsRef<Path>>(path: P) -> Cassette {
+        Cassette {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Record,
+            tape: Arc::new(Mutex::new(Tape::default())),
+        }
+    }
+
+    /// Loads a cassette that replays the interactions stored in a file
+    ///
+    /// # Arguments
+    /// * `path` - file written by a recording cassette
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Cassette, Error> {
+        let path = path.as_ref().to_path_buf();
+        let text = match fs::read_to_string(&path) {
+            Ok(t) => t,
+            Err(e) => return Err(Error::Cassette(format!("Unable to read {}: {}", path.display(), e))),
+        };
+        let file: CassetteFile = match serde_json::from_str(&text) {
+            Ok(f) => f,
+            Err(e) => return Err(Error::Cassette(format!("Unable to parse {}: {}", path.display(), e))),
+        };
+
+        let plays = vec![0; file.interactions.len()];
+        Ok(Cassette {
+            path,
+            mode: CassetteMode::Replay,
+            tape: Arc::new(Mutex::new(Tape {
+                interactions: file.interactions,
+                plays,
+            })),
+        })
+    }
+
+    /// Returns the path of the cassette file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether the cassette records or replays
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Returns the number of interactions on the cassette
+    pub fn len(&self) -> usize {
+        self.lock().interactions.len()
+    }
+
+    /// Returns true if the cassette holds no interactions
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the URLs of replayable interactions that have not been requested
+    ///
+    /// Tests can assert this is empty to check that every recorded request was made.
+    pub fn unplayed(&self) -> Vec<String> {
+        let tape = self.lock();
+        tape.interactions.iter()
+            .zip(tape.plays.iter())
+            .filter(|(_, plays)| **plays == 0)
+            .map(|(i, _)| i.url.clone())
+            .collect()
+    }
+
+    /// Answers a request from the recording
+    ///
+    /// Interactions for the same URL are replayed in the order they were recorded; once all have been played the last one is repeated.
+    fn play(&self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        let mut tape = self.lock();
+
+        let matching: Vec<usize> = tape.interactions.iter()
+            .enumerate()
+            .filter(|(_, i)| i.url == request.redacted_url)
+            .map(|(idx, _)| idx)
+            .collect();
+        let idx = match matching.iter().find(|idx| tape.plays[**idx] == 0) {
+            Some(idx) => *idx,
+            None => match matching.last() {
+                Some(idx) => *idx,
+                None => return Err(Error::Cassette(format!(
+                    "{} has no recording for {}",
+                    self.path.display(),
+                    request.redacted_url
+                ))),
+            },
+        };
+
+        tape.plays[idx] += 1;
+        let interaction = &tape.interactions[idx];
+        Ok(HttpResponse {
+            status: interaction.status,
+            headers: interaction.headers.clone(),
+            body: interaction.body.clone(),
+        })
+    }
+
+    /// Appends an interaction and rewrites the cassette file
+    fn append(&self, request: &HttpRequest, response: &HttpResponse) -> Result<(), Error> {
+        let mut tape = self.lock();
+        tape.interactions.push(Interaction {
+            url: request.redacted_url.clone(),
+            status: response.status,
+            headers: response.headers.iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("Retry-After"))
+                .cloned()
+                .collect(),
+            body: response.body.clone(),
+        });
+        tape.plays.push(0);
+
+        let file = CassetteFile { interactions: tape.interactions.clone() };
+        let text = match serde_json::to_string_pretty(&file) {
+            Ok(t) => t,
+            Err(e) => return Err(Error::Cassette(e.to_string())),
+        };
+        if let Some(dir) = self.path.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                return Err(Error::Cassette(format!("Unable to create {}: {}", dir.display(), e)));
+            }
+        }
+        match fs::write(&self.path, text) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(Error::Cassette(format!("Unable to write {}: {}", self.path.display(), e))),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tape> {
+        match self.tape.lock() {
+            Ok(t) => t,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Blocking transport that records to or replays from a cassette
+pub(crate) struct CassetteTransport {
+    pub(crate) cassette: Cassette,
+    pub(crate) inner: Arc<dyn Transport>,
+}
+
+impl Transport for CassetteTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        match self.cassette.mode {
+            CassetteMode::Replay => self.cassette.play(request),
+            CassetteMode::Record => {
+                let response = self.inner.get(request)?;
+                self.cassette.append(request, &response)?;
+                Ok(response)
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Asynchronous transport that records to or replays from a cassette
+pub(crate) struct AsyncCassetteTransport {
+    pub(crate) cassette: Cassette,
+    pub(crate) inner: Arc<dyn AsyncTransport>,
+}
+
+impl AsyncTransport for AsyncCassetteTransport {
+    fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            match self.cassette.mode {
+                CassetteMode::Replay => self.cassette.play(request),
+                CassetteMode::Record => {
+                    let response = self.inner.get(request).await?;
+                    self.cassette.append(request, &response)?;
+                    Ok(response)
+                },
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::FredClient;
+    use crate::transport::MemoryTransport;
+    use std::env;
+
+    const CATEGORY: &str = r#"{"categories":[{"id":125,"name":"Trade Balance","parent_id":13}]}"#;
+
+    #[test]
+    fn cassette_record_then_replay() {
+        let path = env::temp_dir().join(format!("fred_rs_cassette_record_then_replay_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let transport = MemoryTransport::new();
+        transport.route("category?category_id=125&", CATEGORY);
+        let c = FredClient::builder()
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .transport(transport)
+            .cassette(Cassette::record(&path))
+            .build()
+            .unwrap();
+        c.category(125).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("category?category_id=125&file_type=json"));
+        assert!(!text.contains("abcdefghijklmnopqrstuvwxyz123456"));
+
+        let cassette = Cassette::replay(&path).unwrap();
+        let c = FredClient::builder()
+            .cassette(cassette.clone())
+            .build()
+            .unwrap();
+        assert_eq!(cassette.unplayed().len(), 1);
+
+        let resp = c.category(125).unwrap();
+        assert_eq!(resp.categories[0].id, 125);
+        assert!(cassette.unplayed().is_empty());
+
+        match c.category(13) {
+            Err(Error::Cassette(msg)) => assert!(msg.contains("category?category_id=13&")),
+            _ => assert_eq!(2, 1),
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cassette_replays_in_order() {
+        let path = env::temp_dir().join(format!("fred_rs_cassette_replays_in_order_{}.json", std::process::id()));
+        fs::write(&path, r#"{"interactions":[
+            {"url": "http://127.0.0.1:9/fred/category?category_id=125&file_type=json", "status": 429, "headers": [["Retry-After", "0"]], "body": "{\"error_code\":429,\"error_message\":\"Too Many Requests.\"}"},
+            {"url": "http://127.0.0.1:9/fred/category?category_id=125&file_type=json", "status": 200, "body": "{\"categories\":[]}"}
+        ]}"#).unwrap();
+
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .cassette(Cassette::replay(&path).unwrap())
+            .build()
+            .unwrap();
+
+        match c.category(125) {
+            Err(e) => assert!(e.is_rate_limited()),
+            Ok(_) => assert_eq!(2, 1),
+        }
+        // the last interaction repeats once every recording has been played
+        assert!(c.category(125).unwrap().categories.is_empty());
+        assert!(c.category(125).unwrap().categories.is_empty());
+
+        fs::remove_file(&path).unwrap();
+        match Cassette::replay(&path) {
+            Err(Error::Cassette(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
@@ -303,6 +303,7 @@ mod tests {
     use super::*;
     use crate::tags::Response;
     use crate::client::FredClient;
+    use crate::Error;
 
     #[test]
     fn category_related_tags_with_options_passing() {
@@ -361,7 +362,7 @@ mod tests {
         let _resp: Response = match c.category_related_tags(125, builder) {
             Ok(resp) => resp,
             Err(msg) => {
-                assert_eq!(msg.as_str(), TAG_NAME_REQUIRED_ERROR_TEXT);
+                assert_eq!(msg, Error::Builder(String::from(TAG_NAME_REQUIRED_ERROR_TEXT)));
                 return
             },
         };
@@ -0,0 +1,452 @@
+//! Observation requests split into chunks FRED accepts
+//!
+//! FRED rejects real-time requests for too many vintage dates or for a realtime range spanning too many vintages.  A [ChunkedObservations](struct.ChunkedObservations.html) request takes any number of vintage dates or any realtime window and `FredClient::series_observation_chunked()` sends it as several compliant requests:
+//! * a list of vintage dates is split into groups of at most `max_vintage_dates()`
+//! * a realtime window is split at vintage dates (fetched with one fred/series/vintagedates request) so each sub-window holds at most `max_vintage_dates()` vintages
+//!
+//! The requests run on the client's bulk download machinery, up to `concurrency()` at once.  Each chunk is paged until it holds every realtime period FRED reports for it, so a chunk over the row limit is still complete.  Their realtime periods are merged into a single response: repeated periods are removed and adjacent periods with the same value are joined.
+//!
+//! ```
+//! use fred_rs::chunking::ChunkedObservations;
+//! use fred_rs::client::FredClient;
+//! use chrono::NaiveDate;
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let mut chunked = ChunkedObservations::new("PAYEMS");
+//! chunked
+//!     .realtime(NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(), NaiveDate::from_ymd_opt(2020, 1, 1).unwrap())
+//!     .max_vintage_dates(500)
+//!     .concurrency(4);
+//!
+//! match c.series_observation_chunked(&chunked) {
+//!     Ok(resp) => println!("{} realtime periods", resp.observations.len()),
+//!     Err(msg) => println!("{}", msg),
+//! }
+//! ```
+
+use chrono::{Duration, NaiveDate};
+
+use std::collections::BTreeMap;
+
+use crate::bulk::{BulkObservations, SeriesObservations};
+use crate::error::Error;
+use crate::series::observation::{parse_date, Builder, DataPoint, Response};
+use crate::series::vintage::{self, VintagePoint};
+use crate::series::vintagedates;
+
+/// Most vintage dates sent in one request when no maximum is set
+pub const MAX_VINTAGE_DATES: usize = 2000;
+
+#[derive(Clone, Debug)]
+/// A real-time observation request for many vintage dates or a long realtime window
+pub struct ChunkedObservations {
+    series_id: String,
+    builder: Option<Builder>,
+    vintage_dates: Vec<NaiveDate>,
+    realtime: Option<(NaiveDate, NaiveDate)>,
+    max_vintage_dates: usize,
+    concurrency: usize,
+}
+
+impl ChunkedObservations {
+    /// Creates a request for a series
+    ///
+    /// # Arguments
+    /// * `series_id` - The id for a series
+    pub fn new(series_id: &str) -> ChunkedObservations {
+        ChunkedObservations {
+            series_id: String::from(series_id),
+            builder: None,
+            vintage_dates: Vec::new(),
+            realtime: None,
+            max_vintage_dates: MAX_VINTAGE_DATES,
+            concurrency: 1,
+        }
+    }
+
+    /// Sets the other observation arguments
+    ///
+    /// The realtime, output type and vintage date arguments of the builder are ignored.
+    ///
+    /// # Arguments
+    /// * `builder` - arguments applied to every chunk
+    pub fn builder(&mut self, builder: Builder) -> &mut ChunkedObservations {
+        self.builder = Some(builder);
+        self
+    }
+
+    /// Requests the given vintage dates
+    ///
+    /// # Arguments
+    /// * `dates` - the vintage dates, in any order
+    pub fn vintage_dates(&mut self, dates: &[NaiveDate]) -> &mut ChunkedObservations {
+        self.vintage_dates = dates.to_vec();
+        self
+    }
+
+    /// Requests every realtime period within a window
+    ///
+    /// # Arguments
+    /// * `start` - the first day of the realtime window
+    /// * `end` - the last day of the realtime window
+    pub fn realtime(&mut self, start: NaiveDate, end: NaiveDate) -> &mut ChunkedObservations {
+        self.realtime = Some((start, end));
+        self
+    }
+
+    /// Sets the most vintage dates covered by one request
+    ///
+    /// # Arguments
+    /// * `max` - vintages per request (default 2000, minimum 1)
+    pub fn max_vintage_dates(&mut self, max: usize) -> &mut ChunkedObservations {
+        self.max_vintage_dates = max.max(1);
+        self
+    }
+
+    /// Sets the maximum number of requests in flight
+    ///
+    /// # Arguments
+    /// * `requests` - number of concurrent requests (default 1)
+    pub fn concurrency(&mut self, requests: usize) -> &mut ChunkedObservations {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    /// Returns the series ID
+    pub fn series_id(&self) -> &str {
+        &self.series_id
+    }
+
+    /// Returns the realtime window if one was set
+    pub(crate) fn realtime_window(&self) -> Result<Option<(NaiveDate, NaiveDate)>, Error> {
+        match (self.realtime, self.vintage_dates.is_empty()) {
+            (Some(_), false) => Err(Error::Builder(String::from("Set either vintage dates or a realtime window, not both."))),
+            (None, true) => Err(Error::Builder(String::from("Vintage dates or a realtime window must be set."))),
+            (realtime, _) => Ok(realtime),
+        }
+    }
+
+    /// Returns one builder per group of vintage dates
+    pub(crate) fn vintage_chunks(&self) -> Vec<Builder> {
+        let mut dates = self.vintage_dates.clone();
+        dates.sort();
+        dates.dedup();
+
+        dates.chunks(self.max_vintage_dates)
+            .map(|group| {
+                let mut builder = self.base();
+                for date in group {
+                    builder.vintage_date(&date.format("%Y-%m-%d").to_string());
+                }
+                builder
+            })
+            .collect()
+    }
+
+    /// Returns one builder per realtime sub-window, split so each holds at most `max_vintage_dates` of the given vintages
+    pub(crate) fn realtime_chunks(&self, start: NaiveDate, end: NaiveDate, vintages: &[NaiveDate]) -> Vec<Builder> {
+        let mut splits: Vec<NaiveDate> = vintages.iter()
+            .cloned()
+            .filter(|v| start < *v && *v <= end)
+            .collect();
+        splits.sort();
+        splits.dedup();
+
+        let mut bounds = vec![start];
+        bounds.extend(splits.iter().skip(self.max_vintage_dates - 1).step_by(self.max_vintage_dates).cloned());
+
+        bounds.iter()
+            .enumerate()
+            .map(|(idx, first)| {
+                let last = match bounds.get(idx + 1) {
+                    Some(next) => *next - Duration::days(1),
+                    None => end,
+                };
+                let mut builder = self.base();
+                builder
+                    .realtime_start(&first.format("%Y-%m-%d").to_string())
+                    .realtime_end(&last.format("%Y-%m-%d").to_string());
+                builder
+            })
+            .collect()
+    }
+
+    /// Returns the chunk requests as a bulk download
+    pub(crate) fn bulk(&self, chunks: Vec<Builder>) -> BulkObservations {
+        let mut bulk = BulkObservations::new();
+        bulk.concurrency(self.concurrency);
+        for builder in chunks {
+            bulk.series_with(&self.series_id, builder);
+        }
+        bulk
+    }
+
+    fn base(&self) -> Builder {
+        match &self.builder {
+            Some(b) => b.without_realtime(),
+            None => Builder::new(),
+        }
+    }
+}
+
+/// Parses the vintage dates used to split a realtime window
+pub(crate) fn vintage_list(resp: &vintagedates::Response) -> Result<Vec<NaiveDate>, Error> {
+    let mut dates = Vec::with_capacity(resp.vintage_dates.len());
+    for v in &resp.vintage_dates {
+        dates.push(parse_date("vintage_date", v)?);
+    }
+    Ok(dates)
+}
+
+/// Merges the responses of every chunk into one response
+///
+/// The first chunk that failed is returned as an error.
+pub(crate) fn merge(results: Vec<SeriesObservations>) -> Result<Response, Error> {
+    let mut responses = Vec::with_capacity(results.len());
+    for r in results {
+        responses.push(r.result?);
+    }
+
+    let mut points = Vec::new();
+    for resp in &responses {
+        for p in &resp.observations {
+            points.push((
+                parse_date("date", &p.date)?,
+                parse_date("realtime_start", &p.realtime_start)?,
+                parse_date("realtime_end", &p.realtime_end)?,
+                p.value.clone(),
+            ));
+        }
+    }
+    points.sort_by_key(|p| (p.0, p.1));
+
+    let mut merged: Vec<(NaiveDate, NaiveDate, NaiveDate, String)> = Vec::with_capacity(points.len());
+    for point in points {
+        match merged.last_mut() {
+            Some(last) if last.0 == point.0 && last.3 == point.3 && point.1 <= last.2 + Duration::days(1) => {
+                last.2 = last.2.max(point.2);
+            },
+            _ => merged.push(point),
+        }
+    }
+
+    let text = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+    let mut resp = responses.first().cloned().unwrap_or_default();
+    if let Some(start) = responses.iter().map(|r| r.realtime_start.clone()).min() {
+        resp.realtime_start = start;
+    }
+    if let Some(end) = responses.iter().map(|r| r.realtime_end.clone()).max() {
+        resp.realtime_end = end;
+    }
+    resp.observations = merged.into_iter()
+        .map(|(date, start, end, value)| DataPoint {
+            realtime_start: text(start),
+            realtime_end: text(end),
+            date: text(date),
+            value,
+        })
+        .collect();
+    resp.count = resp.observations.len();
+    resp.offset = 0;
+    Ok(resp)
+}
+
+/// Merges the vintage matrices of every chunk of vintage dates into one response
+///
+/// Each chunk holds the columns of its own vintages, so the rows of a date are joined.
+pub(crate) fn merge_vintages(responses: Vec<vintage::Response>) -> vintage::Response {
+    let mut resp = responses.first().cloned().unwrap_or_default();
+    if let Some(start) = responses.iter().map(|r| r.realtime_start.clone()).min() {
+        resp.realtime_start = start;
+    }
+    if let Some(end) = responses.iter().map(|r| r.realtime_end.clone()).max() {
+        resp.realtime_end = end;
+    }
+
+    let mut rows: BTreeMap<String, BTreeMap<String, Option<String>>> = BTreeMap::new();
+    for r in responses {
+        for p in r.observations {
+            rows.entry(p.date).or_default().extend(p.values);
+        }
+    }
+    resp.observations = rows.into_iter()
+        .map(|(date, values)| VintagePoint { date, values })
+        .collect();
+    resp.count = resp.observations.len();
+    resp.offset = 0;
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{page_body, realtime_point, test_client, MemoryTransport};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn points(points: &[(&str, &str, &str, &str)]) -> Vec<String> {
+        points.iter().map(|(start, end, date, value)| realtime_point(start, end, date, value)).collect()
+    }
+
+    fn response(realtime_start: &str, realtime_end: &str, p: &[(&str, &str, &str, &str)]) -> String {
+        page_body("observations", (realtime_start, realtime_end), p.len(), 0, &points(p))
+    }
+
+    #[test]
+    fn chunked_realtime_window() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=GDPC1&*realtime_start=2020-01-01&realtime_end=2020-12-31", r#"{"realtime_start":"2020-01-01",
+                "realtime_end":"2020-12-31","order_by":"vintage_date","sort_order":"asc","count":3,"offset":0,"limit":10000,
+                "vintage_dates":["2020-01-30","2020-02-27","2020-03-26"]}"#)
+            .route("series/observations?*realtime_start=2020-01-01&realtime_end=2020-02-26", &response("2020-01-01", "2020-02-26", &[
+                ("2020-01-01", "2020-01-29", "2019-07-01", "100.0"),
+                ("2020-01-30", "2020-02-26", "2019-07-01", "101.0"),
+                ("2020-01-30", "2020-02-26", "2019-10-01", "102.0"),
+            ]))
+            .route("series/observations?*realtime_start=2020-02-27&realtime_end=2020-12-31", &response("2020-02-27", "2020-12-31", &[
+                ("2020-02-27", "2020-12-31", "2019-07-01", "101.0"),
+                ("2020-02-27", "2020-03-25", "2019-10-01", "102.5"),
+                ("2020-03-26", "2020-12-31", "2019-10-01", "102.5"),
+            ]));
+
+        let mut builder = Builder::new();
+        builder.realtime_start("1999-01-01").observation_start("2019-01-01");
+        let mut chunked = ChunkedObservations::new("GDPC1");
+        chunked.builder(builder)
+            .realtime(date(2020, 1, 1), date(2020, 12, 31))
+            .max_vintage_dates(2)
+            .concurrency(2);
+
+        let resp = test_client(&transport).series_observation_chunked(&chunked).unwrap();
+        assert_eq!(transport.requests().len(), 3);
+        assert!(transport.requests().iter().all(|r| !r.contains("1999-01-01")));
+        assert!(transport.requests()[1..].iter().all(|r| r.contains("observation_start=2019-01-01")));
+
+        assert_eq!((resp.realtime_start.as_str(), resp.realtime_end.as_str()), ("2020-01-01", "2020-12-31"));
+        let periods: Vec<(&str, &str, &str, &str)> = resp.observations.iter()
+            .map(|p| (p.realtime_start.as_str(), p.realtime_end.as_str(), p.date.as_str(), p.value.as_str()))
+            .collect();
+        assert_eq!(periods, vec![
+            ("2020-01-01", "2020-01-29", "2019-07-01", "100.0"),
+            ("2020-01-30", "2020-12-31", "2019-07-01", "101.0"),
+            ("2020-01-30", "2020-02-26", "2019-10-01", "102.0"),
+            ("2020-02-27", "2020-12-31", "2019-10-01", "102.5"),
+        ]);
+        assert_eq!(resp.count, 4);
+    }
+
+    #[test]
+    fn chunked_vintage_dates() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("vintage_dates=2020-01-30,2020-02-27", &response("2020-01-30", "2020-02-27", &[
+                ("2020-01-30", "2020-01-30", "2019-07-01", "101.0"),
+                ("2020-02-27", "2020-02-27", "2019-07-01", "101.0"),
+            ]))
+            .route("vintage_dates=2020-03-26", &response("2020-03-26", "2020-03-26", &[
+                ("2020-03-26", "2020-03-26", "2019-07-01", "101.0"),
+            ]));
+
+        let mut chunked = ChunkedObservations::new("GDPC1");
+        chunked
+            .vintage_dates(&[date(2020, 3, 26), date(2020, 1, 30), date(2020, 2, 27), date(2020, 1, 30)])
+            .max_vintage_dates(2);
+
+        let c = test_client(&transport);
+        let resp = c.series_observation_chunked(&chunked).unwrap();
+        assert_eq!(transport.requests().len(), 2);
+        assert_eq!(resp.observations.len(), 3);
+
+        chunked.realtime(date(2020, 1, 1), date(2020, 12, 31));
+        match c.series_observation_chunked(&chunked) {
+            Err(Error::Builder(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+        match c.series_observation_chunked(&ChunkedObservations::new("GDPC1")) {
+            Err(Error::Builder(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn chunked_pages_large_chunk() {
+        let vintagedates = r#"{"realtime_start":"2020-01-01","realtime_end":"2020-12-31","order_by":"vintage_date","sort_order":"asc","count":1,"offset":0,
+            "limit":10000,"vintage_dates":["2020-02-27"]}"#;
+        let first = &[
+            ("2020-01-01", "2020-01-29", "2019-07-01", "100.0"),
+            ("2020-01-30", "2020-02-26", "2019-07-01", "101.0"),
+        ];
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=GDPC1&*offset=0", vintagedates)
+            .route("series/observations?*realtime_start=2020-01-01&realtime_end=2020-02-26&limit=2&offset=0", &page_body("observations", ("2020-01-01", "2020-02-26"), 3, 0, &points(first)))
+            .route("series/observations?*realtime_start=2020-01-01&realtime_end=2020-02-26&limit=2&offset=2", &page_body("observations", ("2020-01-01", "2020-02-26"), 3, 2, &points(&[
+                ("2020-01-01", "2020-02-26", "2019-10-01", "102.0"),
+            ])))
+            .route("series/observations?*realtime_start=2020-02-27&realtime_end=2020-12-31&limit=2&offset=0", &page_body("observations", ("2020-02-27", "2020-12-31"), 1, 0, &points(&[
+                ("2020-02-27", "2020-12-31", "2019-07-01", "101.0"),
+            ])));
+
+        let mut builder = Builder::new();
+        builder.limit(2);
+        let mut chunked = ChunkedObservations::new("GDPC1");
+        chunked.builder(builder)
+            .realtime(date(2020, 1, 1), date(2020, 12, 31))
+            .max_vintage_dates(1);
+
+        let resp = test_client(&transport).series_observation_chunked(&chunked).unwrap();
+        assert_eq!(transport.requests().len(), 4);
+        assert_eq!(resp.count, 3);
+        assert_eq!(resp.observations[1].realtime_end, "2020-12-31");
+        assert_eq!(resp.observations[2].date, "2019-10-01");
+
+        // a chunk missing its second page is not merged
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=GDPC1&*offset=0", vintagedates)
+            .route("series/observations?*realtime_start=2020-01-01&realtime_end=2020-02-26&limit=2&offset=0", &page_body("observations", ("2020-01-01", "2020-02-26"), 3, 0, &points(first)))
+            .route("series/observations?*realtime_start=2020-01-01&realtime_end=2020-02-26&limit=2&offset=2", &page_body("observations", ("2020-01-01", "2020-02-26"), 3, 2, &points(&[])))
+            .route("series/observations?*realtime_start=2020-02-27&realtime_end=2020-12-31&limit=2&offset=0", &page_body("observations", ("2020-02-27", "2020-12-31"), 1, 0, &points(&[
+                ("2020-02-27", "2020-12-31", "2019-07-01", "101.0"),
+            ])));
+        match test_client(&transport).series_observation_chunked(&chunked) {
+            Err(Error::Truncated { count, received, .. }) => {
+                assert_eq!(count, 3);
+                assert_eq!(received, 2);
+            },
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn chunked_vintages_merge() {
+        let chunk = |start: &str, rows: &str| -> vintage::Response {
+            serde_json::from_str(&format!(
+                r#"{{"realtime_start":"{}","realtime_end":"{}","observation_start":"1776-07-04","observation_end":"9999-12-31","units":"lin",
+                "output_type":2,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":2,"offset":0,"limit":100000,"observations":[{}]}}"#,
+                start, start, rows
+            )).unwrap()
+        };
+        let resp = merge_vintages(vec![
+            chunk("2020-04-29", r#"{"date":"2020-01-01","GDPC1_20200429":"100.0"},{"date":"2020-04-01","GDPC1_20200429":"."}"#),
+            chunk("2020-07-30", r#"{"date":"2020-01-01","GDPC1_20200730":"101.0"},{"date":"2020-04-01","GDPC1_20200730":"90.0"}"#),
+        ]);
+
+        assert_eq!((resp.realtime_start.as_str(), resp.realtime_end.as_str()), ("2020-04-29", "2020-07-30"));
+        assert_eq!(resp.count, 2);
+        let matrix = resp.matrix().unwrap();
+        assert_eq!(matrix.vintage_dates(), vec![date(2020, 4, 29), date(2020, 7, 30)]);
+        assert_eq!(matrix.row(0), Some(&[Some(100.0), Some(101.0)][..]));
+        assert_eq!(matrix.row(1), Some(&[None, Some(90.0)][..]));
+    }
+}
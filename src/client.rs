@@ -32,24 +32,46 @@
 //! };
 //! ```
 
-use reqwest::blocking::{Client, Response};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
 
-use std::time::Duration;
-use std::env;
+use chrono::NaiveDate;
 
-use crate::*;
+use std::time::{Duration, SystemTime};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{env, fs, thread};
 
-const FRED_BASE_URL: &str = "https://api.stlouisfed.org/fred/";
-const FRED_API_KEY: &str = "FRED_API_KEY";
+use crate::*;
+use crate::async_client::AsyncFredClient;
+use crate::cache::Cache;
+use crate::backtest::RealtimeStore;
+use crate::bulk::{BulkObservations, SeriesObservations};
+use crate::chunking::ChunkedObservations;
+use crate::pagination::{Collect, Page, Paged, Pages};
+use crate::pipeline::{self, Attempts, Lookup};
+use crate::cassette::{AsyncCassetteTransport, Cassette, CassetteTransport};
+use crate::rate_limit::RateLimiter;
+use crate::realtime::RealtimeObservations;
+use crate::revisions::Revisions;
+use crate::retry::RetryPolicy;
+use crate::request::{Request, FRED_BASE_URL, FRED_API_KEY};
+use crate::timeseries::TimeSeries;
+use crate::transport::{AsyncReqwestTransport, AsyncTransport, HttpRequest, ReqwestTransport, Transport};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Clone, Debug)]
 /// Persistent client object used to access the FRED API
 /// 
 /// Each method for the client represents a data endpoint provided by the API and will return a data object representing the response contents.
 pub struct FredClient {
-    client: Client,
-    url_base: &'static str,
+    transport: Arc<dyn Transport>,
+    url_base: String,
     api_key: String,
+    limiter: Option<RateLimiter>,
+    retry: Option<RetryPolicy>,
+    cache: Option<Cache>,
 }
 
 impl FredClient {
@@ -58,7 +80,7 @@ impl FredClient {
     /// 
     /// The client will attempt to load an API key from the environment variable 'FRED_API_KEY'.  If this variable is undefined, the key remains empty.
     /// 
-    /// If a connection cannot be made to the FRED API, it returns Err containing an error message.
+    /// No request is sent to the FRED API, so creating a client succeeds offline.  Use `validate_key()` to check the key and the connection.
     /// 
     /// ```
     /// use fred_rs::client::FredClient;
@@ -71,32 +93,32 @@ impl FredClient {
     ///     },
     /// };
     /// ```
-    pub fn new() -> Result<FredClient, String> {
-
-        let client = match Client::builder().timeout(Duration::from_secs(30)).build() {
-            Ok(c) => c,
-            Err(msg) => return Err(msg.to_string()),
-        };
-
-        let api_key = match env::var(FRED_API_KEY) {
-            Ok(val) => val,
-            Err(_) => String::from(""),
-        };
-
-        let fred = FredClient {
-            client,
-            url_base: FRED_BASE_URL,
-            api_key,
-        };
-
-        let url = format!("{}category?category_id=125&api_key={}&file_type=json", fred.url_base, fred.api_key);
-        match fred.client.get(url.as_str()).send() {
-            Ok(_) => (),
-            Err(msg) => return Err(msg.to_string()),
-        }
-
-        return Ok(fred)
+    pub fn new() -> Result<FredClient, Error> {
+        FredClient::builder().build()
+    }
 
+    /// Returns a [FredClientBuilder](struct.FredClientBuilder.html) used to configure a new client
+    /// 
+    /// ```
+    /// use fred_rs::client::FredClient;
+    /// use std::time::Duration;
+    /// 
+    /// let client = match FredClient::builder()
+    ///     .url_base("http://localhost:8080/fred/")
+    ///     .timeout(Duration::from_secs(5))
+    ///     .user_agent("my-app/1.0")
+    ///     .api_key("abcdefghijklmnopqrstuvwxyz123456")
+    ///     .build()
+    /// {
+    ///     Ok(c) => c,
+    ///     Err(msg) => {
+    ///         println!("{}", msg);
+    ///         return
+    ///     },
+    /// };
+    /// ```
+    pub fn builder() -> FredClientBuilder {
+        FredClientBuilder::new()
     }
 
     /// Sets the FRED API key for the client
@@ -121,11 +143,102 @@ impl FredClient {
         self.api_key = String::from(key);
     }
 
-    fn get_request(&self, url: &str) -> Result<Response, String> {
-        match self.client.get(url).send() {
-            Ok(r) => Ok(r),
-            Err(msg) => Err(msg.to_string()),
+    /// Checks whether the client's API key is accepted by FRED
+    /// 
+    /// The key format is checked locally first (FRED keys are 32 lowercase alphanumeric characters).  A well formed key is then checked with a single request for the root category, which is always sent to FRED and never read from or stored in the response cache.
+    /// 
+    /// ```
+    /// use fred_rs::client::{FredClient, KeyStatus};
+    /// 
+    /// let client = match FredClient::new() {
+    ///     Ok(c) => c,
+    ///     Err(msg) => {
+    ///         println!("{}", msg);
+    ///         return
+    ///     },
+    /// };
+    /// 
+    /// match client.validate_key() {
+    ///     KeyStatus::Valid => println!("ready"),
+    ///     KeyStatus::Invalid(reason) => println!("bad key: {}", reason),
+    ///     KeyStatus::Unreachable(e) => println!("unable to reach FRED: {}", e),
+    /// }
+    /// ```
+    pub fn validate_key(&self) -> KeyStatus {
+        if let Err(reason) = check_key_format(&self.api_key) {
+            return KeyStatus::Invalid(reason);
+        }
+
+        // sent past the cache so a revoked key is never reported from a stored response
+        let request = request::category(0);
+        let result = self.fetch(&request).and_then(|text| request::parse(request.endpoint(), &text));
+        KeyStatus::from(result)
+    }
+
+    /// Returns the client's response cache, if one is configured
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    fn get<T: DeserializeOwned>(&self, request: Request) -> Result<T, Error> {
+        let text = self.get_text(&request)?;
+        request::parse(request.endpoint(), &text)
+    }
+
+    /// Requests every page of a response and combines them, failing if FRED returns fewer results than it reports
+    fn get_all<R: DeserializeOwned + Paged + Default>(&self, request: Request) -> Result<R, Error> {
+        let mut pages = Collect::new(request);
+        while let Some(page) = pages.next_request() {
+            pages.push(self.get(page)?);
+        }
+        pages.finish()
+    }
+
+    /// Returns the response body for a request from the cache or the network
+    fn get_text(&self, request: &Request) -> Result<String, Error> {
+        let key = match pipeline::lookup(self.cache.as_ref(), &self.url_base, request)? {
+            Lookup::Cached(text) => return Ok(text),
+            Lookup::Send(key) => key,
+        };
+
+        let text = self.fetch(request)?;
+        if let (Some(cache), Some(key)) = (&self.cache, key) {
+            let expires = pipeline::expiry(cache, request, self.release_expiry(cache, request));
+            cache.store(&key, request.endpoint(), &text, expires);
+        }
+
+        Ok(text)
+    }
+
+    /// Finds when cached observations expire based on the series' next release
+    fn release_expiry(&self, cache: &Cache, request: &Request) -> Option<SystemTime> {
+        let series_release = pipeline::series_release_request(cache, request)?;
+        let text = self.get_text(&series_release).ok()?;
+        let (release_id, dates) = pipeline::release_dates_request(&text)?;
+        let text = self.get_text(&dates).ok()?;
+        pipeline::release_expiry(release_id, &text)
+    }
+
+    /// Sends a request to FRED, retrying according to the retry policy
+    fn fetch(&self, request: &Request) -> Result<String, Error> {
+        let http = pipeline::http_request(request, &self.url_base, &self.api_key);
+
+        let mut attempts = Attempts::new(self.retry.as_ref());
+        loop {
+            match self.send(&http) {
+                (Ok(text), _) => return Ok(text),
+                (Err(e), retry_after) => thread::sleep(attempts.failed(e, retry_after)?),
+            }
+        }
+    }
+
+    /// Sends a single attempt of a request, returning the body and any Retry-After delay
+    fn send(&self, request: &HttpRequest) -> (Result<String, Error>, Option<Duration>) {
+        if let Some(limiter) = &self.limiter {
+            thread::sleep(limiter.reserve());
         }
+
+        pipeline::response(self.transport.get(request))
     }
 
     // ----------------------------------------------------------------------
@@ -139,42 +252,8 @@ impl FredClient {
         &self,
         series_id: &str,
         builder: Option<series::Builder>
-    ) -> Result<series::Response, String> {
-        let mut url: String = format!(
-            "{}series?series_id={}&api_key={}&file_type=json",
-            self.url_base,
-            series_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<series::Response, Error> {
+        self.get(request::series(series_id, builder))
     }
 
     /// [See fred_rs::series::categories](../series/categories/index.html)
@@ -185,42 +264,8 @@ impl FredClient {
         &self,
         series_id: &str,
         builder: Option<series::categories::Builder>
-    ) -> Result<category::Response, String> {
-        let mut url: String = format!(
-            "{}series/categories?series_id={}&api_key={}&file_type=json",
-            self.url_base,
-            series_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<category::Response, Error> {
+        self.get(request::series_categories(series_id, builder))
     }
 
     /// [See fred_rs::series::observation](../series/observation/index.html)
@@ -231,42 +276,22 @@ impl FredClient {
         &self,
         series_id: &str,
         builder: Option<series::observation::Builder>
-    ) -> Result<series::observation::Response, String> {
-        let mut url: String = format!(
-            "{}series/observations?series_id={}&api_key={}&file_type=json",
-            self.url_base,
-            series_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
+    ) -> Result<series::observation::Response, Error> {
+        self.get(request::series_observation(series_id, builder))
+    }
 
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    /// [See fred_rs::series::vintage](../series/vintage/index.html)
+    /// 
+    /// Requests output type 2 (VDALL) unless the builder sets one.
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    pub fn series_observation_vintages(
+        &self,
+        series_id: &str,
+        builder: Option<series::observation::Builder>
+    ) -> Result<series::vintage::Response, Error> {
+        self.get(request::series_observation_vintages(series_id, builder))
     }
 
     /// [See fred_rs::series::release](../series/release/index.html)
@@ -277,44 +302,10 @@ impl FredClient {
         &self,
         series_id: &str,
         builder: Option<series::release::Builder>
-    ) -> Result<release::Response, String> {
-        let mut url: String = format!(
-            "{}series/release?series_id={}&api_key={}&file_type=json",
-            self.url_base,
-            series_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<release::Response, Error> {
+        self.get(request::series_release(series_id, builder))
     }
-    
+
     /// [See fred_rs::series::tags](../series/tags/index.html)
     /// 
     /// # Arguments
@@ -323,85 +314,16 @@ impl FredClient {
         &self,
         series_id: &str,
         builder: Option<series::tags::Builder>
-    ) -> Result<tags::Response, String> {
-
-        let mut url: String = format!(
-            "{}series/tags?series_id={}&api_key={}&file_type=json",
-            self.url_base,
-            series_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-        
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<tags::Response, Error> {
+        self.get(request::series_tags(series_id, builder))
     }
 
     /// [See fred_rs::series::updates](../series/updates/index.html)
     pub fn series_updates(
         &self,
         builder: Option<series::updates::Builder>
-    ) -> Result<series::updates::Response, String> {
-
-        let mut url: String = format!(
-            "{}series/updates?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-        
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<series::updates::Response, Error> {
+        self.get(request::series_updates(builder))
     }
 
     /// [See fred_rs::series::vintagedates](../series/vintagedates/index.html)
@@ -412,43 +334,8 @@ impl FredClient {
         &self,
         series_id: &str,
         builder: Option<series::vintagedates::Builder>
-    ) -> Result<series::vintagedates::Response, String> {
-
-        let mut url: String = format!(
-            "{}series/vintagedates?series_id={}&api_key={}&file_type=json",
-            self.url_base,
-            series_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-        
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<series::vintagedates::Response, Error> {
+        self.get(request::series_vintagedates(series_id, builder))
     }
 
     // ----------------------------------------------------------------------
@@ -462,44 +349,8 @@ impl FredClient {
         &self,
         search_text: &str,
         builder: Option<series::search::Builder>
-    ) -> Result<series::Response, String> {
-        let search_text = search_text.replace(" ", "%20"); // encode strings in url
-
-        let mut url: String = format!(
-            "{}series/search?search_text={}&api_key={}&file_type=json",
-            self.url_base,
-            search_text,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<series::Response, Error> {
+        self.get(request::series_search(search_text, builder))
     }
 
     /// [See fred_rs::series::search::tags](../series/search/tags/index.html)
@@ -510,44 +361,8 @@ impl FredClient {
         &self,
         series_search_text: &str,
         builder: Option<series::search::tags::Builder>
-    ) -> Result<tags::Response, String> {
-        let search_text = series_search_text.replace(" ", "%20"); // encode spaces in url
-
-        let mut url: String = format!(
-            "{}series/search/tags?series_search_text={}&api_key={}&file_type=json",
-            self.url_base,
-            search_text,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-        
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<tags::Response, Error> {
+        self.get(request::series_search_tags(series_search_text, builder))
     }
 
     /// [See fred_rs::series::search::related_tags](../series/search/related_tags/index.html)
@@ -558,44 +373,10 @@ impl FredClient {
         &self,
         series_search_text: &str,
         builder: series::search::related_tags::Builder
-    ) -> Result<tags::Response, String> {
-
-        let search_text = series_search_text.replace(" ", "%20"); // encode spaces in url
-
-        let mut url: String = format!(
-            "{}series/search/related_tags?series_search_text={}&api_key={}&file_type=json",
-            self.url_base,
-            search_text,
-            self.api_key
-        );
-
-        match builder.build() {
-            Ok(s) => url.push_str(s.as_str()),
-            Err(msg) => return Err(msg),
-        }
-                
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
+    ) -> Result<tags::Response, Error> {
+        match request::series_search_related_tags(series_search_text, builder) {
+            Ok(req) => self.get(req),
+            Err(e) => Err(e),
         }
     }
 
@@ -606,81 +387,18 @@ impl FredClient {
     pub fn tags(
         &self,
         builder: Option<tags::Builder>
-    ) -> Result<tags::Response, String> {
-        let mut url: String = format!(
-            "{}tags?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<tags::Response, Error> {
+        self.get(request::tags(builder))
     }
 
     /// [See fred_rs::tags::series](../tags/series/index.html)
     pub fn tags_series(
         &self,
         builder: tags::series::Builder
-    ) -> Result<series::Response, String> {
-        let mut url: String = format!(
-            "{}tags/series?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder.build() {
-            Ok(opt) => url.push_str(opt.as_str()),
-            Err(msg) => return Err(msg),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
+    ) -> Result<series::Response, Error> {
+        match request::tags_series(builder) {
+            Ok(req) => self.get(req),
+            Err(e) => Err(e),
         }
     }
 
@@ -691,40 +409,10 @@ impl FredClient {
     pub fn related_tags(
         &self,
         builder: related_tags::Builder
-    ) -> Result<tags::Response, String> {
-        let mut url: String = format!(
-            "{}related_tags?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder.build() {
-            Ok(opt) => url.push_str(opt.as_str()),
-            Err(msg) => return Err(msg),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
+    ) -> Result<tags::Response, Error> {
+        match request::related_tags(builder) {
+            Ok(req) => self.get(req),
+            Err(e) => Err(e),
         }
     }
 
@@ -735,41 +423,8 @@ impl FredClient {
     pub fn sources(
         &self,
         builder: Option<sources::Builder>
-    ) -> Result<source::Response, String> {
-        let mut url: String = format!(
-            "{}sources?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<source::Response, Error> {
+        self.get(request::sources(builder))
     }
 
     // ----------------------------------------------------------------------
@@ -783,42 +438,8 @@ impl FredClient {
         &self,
         source_id: usize,
         builder: Option<source::Builder>
-    ) -> Result<source::Response, String> {
-        let mut url: String = format!(
-            "{}source?source_id={}&api_key={}&file_type=json",
-            self.url_base,
-            source_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<source::Response, Error> {
+        self.get(request::source(source_id, builder))
     }
 
     /// [See fred_rs::source::releases](../source/releases/index.html)
@@ -829,42 +450,8 @@ impl FredClient {
         &self,
         source_id: usize,
         builder: Option<source::releases::Builder>
-    ) -> Result<release::Response, String> {
-        let mut url: String = format!(
-            "{}source/releases?source_id={}&api_key={}&file_type=json",
-            self.url_base,
-            source_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<release::Response, Error> {
+        self.get(request::source_releases(source_id, builder))
     }
 
     // ----------------------------------------------------------------------
@@ -877,37 +464,8 @@ impl FredClient {
     pub fn category(
         &self,
         category_id: usize
-    ) -> Result<category::Response, String> {
-        let url: String = format!(
-            "{}category?category_id={}&api_key={}&file_type=json",
-            self.url_base,
-            category_id,
-            self.api_key
-        );
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<category::Response, Error> {
+        self.get(request::category(category_id))
     }
 
     /// [See fred_rs::category::children](../category/children/index.html)
@@ -917,43 +475,9 @@ impl FredClient {
     pub fn category_children(
         &self,
         category_id: usize,
-        builder: Option<category::children::Builder>,
-    ) -> Result<category::Response, String> {
-        let mut url: String = format!(
-            "{}category/children?category_id={}&api_key={}&file_type=json",
-            self.url_base,
-            category_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+        builder: Option<category::children::Builder>
+    ) -> Result<category::Response, Error> {
+        self.get(request::category_children(category_id, builder))
     }
 
     /// [See fred_rs::category::related](../category/related/index.html)
@@ -963,43 +487,9 @@ impl FredClient {
     pub fn category_related(
         &self,
         category_id: usize,
-        builder: Option<category::related::Builder>,
-    ) -> Result<category::Response, String> {
-        let mut url: String = format!(
-            "{}category/related?category_id={}&api_key={}&file_type=json",
-            self.url_base,
-            category_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+        builder: Option<category::related::Builder>
+    ) -> Result<category::Response, Error> {
+        self.get(request::category_related(category_id, builder))
     }
 
     /// [See fred_rs::category::series](../category/series/index.html)
@@ -1010,42 +500,8 @@ impl FredClient {
         &self,
         category_id: usize,
         builder: Option<category::series::Builder>
-    ) -> Result<series::Response, String> {
-        let mut url: String = format!(
-            "{}category/series?category_id={}&api_key={}&file_type=json",
-            self.url_base,
-            category_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<series::Response, Error> {
+        self.get(request::category_series(category_id, builder))
     }
 
     /// [See fred_rs::category::tags](../category/tags/index.html)
@@ -1056,42 +512,8 @@ impl FredClient {
         &self,
         category_id: usize,
         builder: Option<category::tags::Builder>
-    ) -> Result<tags::Response, String> {
-        let mut url: String = format!(
-            "{}category/tags?category_id={}&api_key={}&file_type=json",
-            self.url_base,
-            category_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<tags::Response, Error> {
+        self.get(request::category_tags(category_id, builder))
     }
 
     /// [See fred_rs::category::related_tags](../category/related_tags/index.html)
@@ -1102,41 +524,10 @@ impl FredClient {
         &self,
         category_id: usize,
         builder: category::related_tags::Builder
-    ) -> Result<tags::Response, String> {
-        let mut url: String = format!(
-            "{}category/related_tags?category_id={}&api_key={}&file_type=json",
-            self.url_base,
-            category_id,
-            self.api_key
-        );
-
-        match builder.build() {
-            Ok(o) => url.push_str(o.as_str()),
-            Err(msg) => return Err(msg),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
+    ) -> Result<tags::Response, Error> {
+        match request::category_related_tags(category_id, builder) {
+            Ok(req) => self.get(req),
+            Err(e) => Err(e),
         }
     }
 
@@ -1147,82 +538,16 @@ impl FredClient {
     pub fn releases(
         &self,
         builder: Option<releases::Builder>
-    ) -> Result<release::Response, String> {
-        let mut url: String = format!(
-            "{}releases?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<release::Response, Error> {
+        self.get(request::releases(builder))
     }
 
     /// [See fred_rs::releases::dates](../releases/dates/index.html)
     pub fn releases_dates(
         &self,
         builder: Option<releases::dates::Builder>
-    ) -> Result<releases::dates::Response, String> {
-        let mut url: String = format!(
-            "{}releases/dates?api_key={}&file_type=json",
-            self.url_base,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<releases::dates::Response, Error> {
+        self.get(request::releases_dates(builder))
     }
 
     // ----------------------------------------------------------------------
@@ -1236,42 +561,8 @@ impl FredClient {
         &self,
         release_id: usize,
         builder: Option<release::Builder>
-    ) -> Result<release::Response, String> {
-        let mut url: String = format!(
-            "{}release?release_id={}&api_key={}&file_type=json",
-            self.url_base,
-            release_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<release::Response, Error> {
+        self.get(request::release(release_id, builder))
     }
 
     /// [See fred_rs::release::series](../release/series/index.html)
@@ -1282,42 +573,20 @@ impl FredClient {
         &self,
         release_id: usize,
         builder: Option<release::series::Builder>
-    ) -> Result<series::Response, String> {
-        let mut url: String = format!(
-            "{}release/series?release_id={}&api_key={}&file_type=json",
-            self.url_base,
-            release_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
+    ) -> Result<series::Response, Error> {
+        self.get(request::release_series(release_id, builder))
+    }
 
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    /// [See fred_rs::release::dates](../release/dates/index.html)
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_dates.html#release_id)
+    pub fn release_dates(
+        &self,
+        release_id: usize,
+        builder: Option<release::dates::Builder>
+    ) -> Result<release::dates::Response, Error> {
+        self.get(request::release_dates(release_id, builder))
     }
 
     /// [See fred_rs::release::sources](../release/sources/index.html)
@@ -1328,42 +597,8 @@ impl FredClient {
         &self,
         release_id: usize,
         builder: Option<release::sources::Builder>
-    ) -> Result<source::Response, String> {
-        let mut url: String = format!(
-            "{}release/sources?release_id={}&api_key={}&file_type=json",
-            self.url_base,
-            release_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<source::Response, Error> {
+        self.get(request::release_sources(release_id, builder))
     }
 
     /// [See fred_rs::release::tags](../release/tags/index.html)
@@ -1374,42 +609,8 @@ impl FredClient {
         &self,
         release_id: usize,
         builder: Option<release::tags::Builder>
-    ) -> Result<tags::Response, String> {
-        let mut url: String = format!(
-            "{}release/tags?release_id={}&api_key={}&file_type=json",
-            self.url_base,
-            release_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<tags::Response, Error> {
+        self.get(request::release_tags(release_id, builder))
     }
 
     /// [See fred_rs::release::related_tags](../release/related_tags/index.html)
@@ -1420,41 +621,10 @@ impl FredClient {
         &self,
         release_id: usize,
         builder: release::related_tags::Builder
-    ) -> Result<tags::Response, String> {
-        let mut url: String = format!(
-            "{}release/related_tags?release_id={}&api_key={}&file_type=json",
-            self.url_base,
-            release_id,
-            self.api_key
-        );
-
-        match builder.build() {
-            Ok(o) => url.push_str(o.as_str()),
-            Err(msg) => return Err(msg),
-        }
-
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
+    ) -> Result<tags::Response, Error> {
+        match request::release_related_tags(release_id, builder) {
+            Ok(req) => self.get(req),
+            Err(e) => Err(e),
         }
     }
 
@@ -1466,51 +636,881 @@ impl FredClient {
         &self,
         release_id: usize,
         builder: Option<release::tables::Builder>
-    ) -> Result<release::tables::Response, String> {
-        let mut url: String = format!(
-            "{}release/tables?release_id={}&api_key={}&file_type=json",
-            self.url_base,
-            release_id,
-            self.api_key
-        );
-
-        match builder {
-            Some(b) => url.push_str(b.build().as_str()),
-            None => (),
-        }
-        
-        match self.get_request(url.as_str()) {
-            Ok(resp) => {
-                let text = resp.text().unwrap();
-                match serde_json::from_str(&text) {
-                    Ok(val) => Ok(val),
-                    Err(_e) => {
-                        match serde_json::from_str(&text) {
-                            Ok(e) => {
-                                let err: error::FredError = e;
-                                let err_msg = format!(
-                                    "ERROR {}: {}",
-                                    err.error_code,
-                                    err.error_message
-                                );
-                                return Err(err_msg);
-                            },
-                            Err(msg) => return Err(String::from(msg.to_string())),
-                        }
-                    },
-                }
-            },
-            Err(e) => return Err(e.to_string()),
-        }
+    ) -> Result<release::tables::Response, Error> {
+        self.get(request::release_tables(release_id, builder))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // ----------------------------------------------------------------------
+    // Bulk
 
-    #[test]
-    fn client_new() {
+    /// Downloads the observations of many series concurrently
+    /// 
+    /// The series are fetched on `concurrency` threads.  See [fred_rs::bulk](../bulk/index.html).
+    /// 
+    /// # Arguments
+    /// `bulk` - The series to download and their observation arguments
+    pub fn bulk_observations(&self, bulk: &BulkObservations) -> Vec<SeriesObservations> {
+        bulk.run_blocking(|series_id, builder| self.series_observation(series_id, builder))
+    }
+
+    // ----------------------------------------------------------------------
+    // Realtime
+
+    /// Downloads the realtime periods of a series' observations
+    /// 
+    /// Any realtime arguments set by the builder are replaced.  Every page is requested, with the builder's limit as the page size, and `Error::Truncated` is returned if FRED sends fewer periods than it reports.  See [fred_rs::realtime](../realtime/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    /// `realtime_start` - The first day of the realtime range
+    /// `realtime_end` - The last day of the realtime range
+    pub fn series_observation_realtime(
+        &self,
+        series_id: &str,
+        realtime_start: NaiveDate,
+        realtime_end: NaiveDate,
+        builder: Option<series::observation::Builder>
+    ) -> Result<RealtimeObservations, Error> {
+        let request = request::series_observation_realtime(series_id, realtime_start, realtime_end, builder);
+        let resp: series::observation::Response = self.get_all(request)?;
+        RealtimeObservations::from_response(&resp)
+    }
+
+    /// Returns a series as it was known on a date
+    /// 
+    /// See [fred_rs::realtime](../realtime/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    /// `as_of` - The date the data is viewed from
+    pub fn series_observation_as_of(
+        &self,
+        series_id: &str,
+        as_of: NaiveDate,
+        builder: Option<series::observation::Builder>
+    ) -> Result<TimeSeries, Error> {
+        let history = self.series_observation_realtime(series_id, as_of, as_of, builder)?;
+        Ok(history.as_of(as_of))
+    }
+
+    /// Returns a series as it was known on each of several dates
+    /// 
+    /// A single request covers the realtime range from the earliest to the latest date.  See [fred_rs::realtime](../realtime/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    /// `dates` - The dates the data is viewed from
+    pub fn series_observation_as_of_dates(
+        &self,
+        series_id: &str,
+        dates: &[NaiveDate],
+        builder: Option<series::observation::Builder>
+    ) -> Result<Vec<(NaiveDate, TimeSeries)>, Error> {
+        let (first, last) = realtime::date_bounds(dates)?;
+        let history = self.series_observation_realtime(series_id, first, last, builder)?;
+        Ok(history.as_of_dates(dates))
+    }
+
+    /// Returns the revision history of every observation of a series
+    /// 
+    /// Fetches every vintage date of the series, then downloads the vintage matrix (output type 2) in chunks of at most `chunking::MAX_VINTAGE_DATES` vintages, paging each chunk until FRED's count is reached.  Any realtime, output type and vintage date arguments set by the builder are replaced.  See [fred_rs::revisions](../revisions/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    pub fn series_revisions(
+        &self,
+        series_id: &str,
+        builder: Option<series::observation::Builder>
+    ) -> Result<Revisions, Error> {
+        let mut vintage_builder = series::vintagedates::Builder::new();
+        vintage_builder.limit(pagination::VINTAGE_DATE_PAGE_LIMIT);
+        let vintages = self.get_all(request::series_vintagedates(series_id, Some(vintage_builder)))?;
+
+        let mut chunked = ChunkedObservations::new(series_id);
+        chunked.vintage_dates(&chunking::vintage_list(&vintages)?);
+        if let Some(b) = builder {
+            chunked.builder(b);
+        }
+
+        let mut responses = Vec::new();
+        for chunk in chunked.vintage_chunks() {
+            responses.push(self.get_all(request::series_observation_vintages(series_id, Some(chunk)))?);
+        }
+        let matrix = chunking::merge_vintages(responses).matrix()?;
+        Ok(Revisions::from_vintages(&matrix))
+    }
+
+    /// Downloads the realtime data of several series into a store for backtests
+    /// 
+    /// Fetches the vintage dates of each series within the realtime window, then its realtime periods split into chunks of at most `chunking::MAX_VINTAGE_DATES` vintages.  Every page is requested and `Error::Truncated` is returned if FRED sends fewer results than it reports.  See [fred_rs::backtest](../backtest/index.html).
+    /// 
+    /// # Arguments
+    /// `store` - The store the series are added to
+    /// `series_ids` - The ids of the series
+    /// `realtime_start` - The first day of the realtime window
+    /// `realtime_end` - The last day of the realtime window
+    pub fn prime_realtime_store<S: AsRef<str>>(
+        &self,
+        store: &mut RealtimeStore,
+        series_ids: &[S],
+        realtime_start: NaiveDate,
+        realtime_end: NaiveDate
+    ) -> Result<(), Error> {
+        for series_id in series_ids {
+            let series_id = series_id.as_ref();
+            let mut builder = series::vintagedates::Builder::new();
+            builder
+                .realtime_start(&realtime_start.format("%Y-%m-%d").to_string())
+                .realtime_end(&realtime_end.format("%Y-%m-%d").to_string())
+                .limit(pagination::VINTAGE_DATE_PAGE_LIMIT);
+            let vintages = self.get_all(request::series_vintagedates(series_id, Some(builder)))?;
+
+            let chunked = ChunkedObservations::new(series_id);
+            let builders = chunked.realtime_chunks(realtime_start, realtime_end, &chunking::vintage_list(&vintages)?);
+            let history = RealtimeObservations::from_response(&self.fetch_chunks(&chunked, builders)?)?;
+            store.insert_download(series_id, realtime_start, realtime_end, &vintages, history)?;
+        }
+        Ok(())
+    }
+
+    /// Downloads real-time observations for any number of vintage dates or any realtime window
+    /// 
+    /// The request is split into chunks FRED accepts, run concurrently and merged into one response of realtime periods.  Every page of each chunk is requested and `Error::Truncated` is returned if a chunk holds fewer periods than FRED reports.  See [fred_rs::chunking](../chunking/index.html).
+    /// 
+    /// # Arguments
+    /// `chunked` - The series, vintage dates or realtime window and chunk settings
+    pub fn series_observation_chunked(
+        &self,
+        chunked: &ChunkedObservations
+    ) -> Result<series::observation::Response, Error> {
+        let builders = match chunked.realtime_window()? {
+            Some((start, end)) => {
+                let mut builder = series::vintagedates::Builder::new();
+                builder
+                    .realtime_start(&start.format("%Y-%m-%d").to_string())
+                    .realtime_end(&end.format("%Y-%m-%d").to_string())
+                    .limit(pagination::VINTAGE_DATE_PAGE_LIMIT);
+                let vintages = self.get_all(request::series_vintagedates(chunked.series_id(), Some(builder)))?;
+                chunked.realtime_chunks(start, end, &chunking::vintage_list(&vintages)?)
+            },
+            None => chunked.vintage_chunks(),
+        };
+        self.fetch_chunks(chunked, builders)
+    }
+
+    /// Requests every page of each chunk and merges the chunks into one response
+    fn fetch_chunks(
+        &self,
+        chunked: &ChunkedObservations,
+        builders: Vec<series::observation::Builder>
+    ) -> Result<series::observation::Response, Error> {
+        chunking::merge(chunked.bulk(builders).run_blocking(|series_id, builder| {
+            self.get_all(request::series_observation(series_id, builder))
+        }))
+    }
+
+    // ----------------------------------------------------------------------
+    // Pagination
+
+    fn pages<R: DeserializeOwned + 'static, T: 'static>(
+        &self,
+        request: Result<Request, Error>,
+        page: fn(R) -> Page<T>
+    ) -> Pages<'_, T> {
+        Pages::new(request, move |req| self.get(req).map(page))
+    }
+
+    /// Iterates over every result of [fred_rs::series::observation](../series/observation/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_observation.html#series_id)
+    pub fn series_observation_iter(
+        &self,
+        series_id: &str,
+        builder: Option<series::observation::Builder>
+    ) -> Pages<'_, series::observation::DataPoint> {
+        self.pages(
+            Ok(request::series_observation(series_id, builder)),
+            |r: series::observation::Response| Page::new(r.observations, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::series::updates](../series/updates/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn series_updates_iter(
+        &self,
+        builder: Option<series::updates::Builder>
+    ) -> Pages<'_, series::Series> {
+        self.pages(
+            Ok(request::series_updates(builder)),
+            |r: series::updates::Response| Page::new(r.seriess, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::series::vintagedates](../series/vintagedates/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `series_id` - The id for a series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_vintagedates.html#series_id)
+    pub fn series_vintagedates_iter(
+        &self,
+        series_id: &str,
+        builder: Option<series::vintagedates::Builder>
+    ) -> Pages<'_, String> {
+        self.pages(
+            Ok(request::series_vintagedates(series_id, builder)),
+            |r: series::vintagedates::Response| Page::new(r.vintage_dates, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::series::search](../series/search/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `search_text` - The words to match against economic data series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_search.html#search_text)
+    pub fn series_search_iter(
+        &self,
+        search_text: &str,
+        builder: Option<series::search::Builder>
+    ) -> Pages<'_, series::Series> {
+        self.pages(
+            Ok(request::series_search(search_text, builder)),
+            |r: series::Response| Page::new(r.seriess, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::series::search::tags](../series/search/tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `series_search_text` - The words to match against economic data series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_search_tags.html#search_text)
+    pub fn series_search_tags_iter(
+        &self,
+        series_search_text: &str,
+        builder: Option<series::search::tags::Builder>
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            Ok(request::series_search_tags(series_search_text, builder)),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::series::search::related_tags](../series/search/related_tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `series_search_text` - The words to match against economic data series [[Link]](https://research.stlouisfed.org/docs/api/fred/series_search_related_tags.html#search_text)
+    pub fn series_search_related_tags_iter(
+        &self,
+        series_search_text: &str,
+        builder: series::search::related_tags::Builder
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            request::series_search_related_tags(series_search_text, builder),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::tags](../tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn tags_iter(
+        &self,
+        builder: Option<tags::Builder>
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            Ok(request::tags(builder)),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::tags::series](../tags/series/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn tags_series_iter(
+        &self,
+        builder: tags::series::Builder
+    ) -> Pages<'_, series::Series> {
+        self.pages(
+            request::tags_series(builder),
+            |r: series::Response| Page::new(r.seriess, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::related_tags](../related_tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn related_tags_iter(
+        &self,
+        builder: related_tags::Builder
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            request::related_tags(builder),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::sources](../sources/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn sources_iter(
+        &self,
+        builder: Option<sources::Builder>
+    ) -> Pages<'_, source::Source> {
+        self.pages(
+            Ok(request::sources(builder)),
+            |r: source::Response| Page::new(r.sources, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::source::releases](../source/releases/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `source_id` - The id for a source [[Link]](https://research.stlouisfed.org/docs/api/fred/source_releases.html#source_id)
+    pub fn source_releases_iter(
+        &self,
+        source_id: usize,
+        builder: Option<source::releases::Builder>
+    ) -> Pages<'_, release::Release> {
+        self.pages(
+            Ok(request::source_releases(source_id, builder)),
+            |r: release::Response| Page::new(r.releases, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::category::series](../category/series/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/series.html#category_id)
+    pub fn category_series_iter(
+        &self,
+        category_id: usize,
+        builder: Option<category::series::Builder>
+    ) -> Pages<'_, series::Series> {
+        self.pages(
+            Ok(request::category_series(category_id, builder)),
+            |r: series::Response| Page::new(r.seriess, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::category::tags](../category/tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category_tags.html#category_id)
+    pub fn category_tags_iter(
+        &self,
+        category_id: usize,
+        builder: Option<category::tags::Builder>
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            Ok(request::category_tags(category_id, builder)),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::category::related_tags](../category/related_tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `category_id` - The id for a category [[Link]](https://research.stlouisfed.org/docs/api/fred/category_related_tags.html#category_id)
+    pub fn category_related_tags_iter(
+        &self,
+        category_id: usize,
+        builder: category::related_tags::Builder
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            request::category_related_tags(category_id, builder),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::releases](../releases/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn releases_iter(
+        &self,
+        builder: Option<releases::Builder>
+    ) -> Pages<'_, release::Release> {
+        self.pages(
+            Ok(request::releases(builder)),
+            |r: release::Response| Page::new(r.releases, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::releases::dates](../releases/dates/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    pub fn releases_dates_iter(
+        &self,
+        builder: Option<releases::dates::Builder>
+    ) -> Pages<'_, releases::dates::ReleaseDate> {
+        self.pages(
+            Ok(request::releases_dates(builder)),
+            |r: releases::dates::Response| Page::new(r.release_dates, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::release::series](../release/series/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_series.html#release_id)
+    pub fn release_series_iter(
+        &self,
+        release_id: usize,
+        builder: Option<release::series::Builder>
+    ) -> Pages<'_, series::Series> {
+        self.pages(
+            Ok(request::release_series(release_id, builder)),
+            |r: series::Response| Page::new(r.seriess, r.count)
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::release::tags](../release/tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_tags.html#release_id)
+    pub fn release_tags_iter(
+        &self,
+        release_id: usize,
+        builder: Option<release::tags::Builder>
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            Ok(request::release_tags(release_id, builder)),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+
+    /// Iterates over every result of [fred_rs::release::related_tags](../release/related_tags/index.html) across pages
+    /// 
+    /// See [fred_rs::pagination](../pagination/index.html).
+    /// 
+    /// # Arguments
+    /// `release_id` - The id for a release [[Link]](https://research.stlouisfed.org/docs/api/fred/release_related_tags.html#release_id)
+    pub fn release_related_tags_iter(
+        &self,
+        release_id: usize,
+        builder: release::related_tags::Builder
+    ) -> Pages<'_, tags::Tag> {
+        self.pages(
+            request::release_related_tags(release_id, builder),
+            |r: tags::Response| Page::new(r.tags, Some(r.count))
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Result of checking an API key with `validate_key()`
+pub enum KeyStatus {
+    /// FRED accepted the key
+    Valid,
+    /// The key is malformed or FRED rejected it; contains the reason
+    Invalid(String),
+    /// FRED could not be reached or did not answer normally, so the key could not be checked
+    Unreachable(Error),
+}
+
+impl From<Result<category::Response, Error>> for KeyStatus {
+    fn from(result: Result<category::Response, Error>) -> KeyStatus {
+        match result {
+            Ok(_) => KeyStatus::Valid,
+            Err(Error::Api { code, message }) if code == 400 || code == 403 => KeyStatus::Invalid(message),
+            Err(e) => KeyStatus::Unreachable(e),
+        }
+    }
+}
+
+/// Checks that a key looks like a FRED API key (32 lowercase alphanumeric characters)
+pub(crate) fn check_key_format(key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Err(format!("No API key set; set the {} environment variable or use with_key()", FRED_API_KEY));
+    }
+    if key.len() != 32 {
+        return Err(format!("API key must be 32 characters long, found {}", key.len()));
+    }
+    if !key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+        return Err(String::from("API key must contain only lowercase letters and digits"));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+enum ApiKey {
+    /// Read from FRED_API_KEY, leaving the key empty if it is undefined
+    Default,
+    Value(String),
+    Env(String),
+    File(PathBuf),
+}
+
+#[derive(Clone, Debug)]
+/// Configuration builder for [FredClient](struct.FredClient.html) and [AsyncFredClient](../async_client/struct.AsyncFredClient.html)
+/// 
+/// Building a client does not contact the FRED API.
+/// 
+/// ```
+/// use fred_rs::client::FredClientBuilder;
+/// use std::time::Duration;
+/// 
+/// let mut builder = FredClientBuilder::new();
+/// builder
+///     .connect_timeout(Duration::from_secs(2))
+///     .gzip(true)
+///     .api_key_env("MY_FRED_KEY");
+/// ```
+pub struct FredClientBuilder {
+    url_base: String,
+    api_key: ApiKey,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+    gzip: bool,
+    limiter: Option<RateLimiter>,
+    retry: Option<RetryPolicy>,
+    cache: Option<Cache>,
+    transport: Option<Arc<dyn Transport>>,
+    async_transport: Option<Arc<dyn AsyncTransport>>,
+    cassette: Option<Cassette>,
+}
+
+impl FredClientBuilder {
+    /// Initializes a builder with the default settings used by `FredClient::new()`
+    /// 
+    /// The default base URL is https://api.stlouisfed.org/fred/, the request timeout is 30 seconds and the API key is read from the `FRED_API_KEY` environment variable.
+    pub fn new() -> FredClientBuilder {
+        FredClientBuilder {
+            url_base: String::from(FRED_BASE_URL),
+            api_key: ApiKey::Default,
+            timeout: Some(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            user_agent: None,
+            proxy: None,
+            gzip: false,
+            limiter: None,
+            retry: None,
+            cache: None,
+            transport: None,
+            async_transport: None,
+            cassette: None,
+        }
+    }
+
+    /// Sets the base URL that endpoint paths are appended to
+    /// 
+    /// A trailing `/` is added if it is missing.
+    /// 
+    /// # Arguments
+    /// * `url` - base URL (e.g. http://localhost:8080/fred/)
+    pub fn url_base(&mut self, url: &str) -> &mut FredClientBuilder {
+        self.url_base = String::from(url);
+        if !self.url_base.ends_with('/') {
+            self.url_base.push('/');
+        }
+        self
+    }
+
+    /// Sets the total timeout for each request
+    /// 
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for a complete response
+    pub fn timeout(&mut self, timeout: Duration) -> &mut FredClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Removes the request timeout
+    pub fn no_timeout(&mut self) -> &mut FredClientBuilder {
+        self.timeout = None;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection
+    /// 
+    /// # Arguments
+    /// * `timeout` - maximum time to wait for the connection
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut FredClientBuilder {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the User-Agent header sent with each request
+    /// 
+    /// # Arguments
+    /// * `agent` - User-Agent value
+    pub fn user_agent(&mut self, agent: &str) -> &mut FredClientBuilder {
+        self.user_agent = Some(String::from(agent));
+        self
+    }
+
+    /// Sends all requests through a proxy
+    /// 
+    /// # Arguments
+    /// * `url` - proxy URL (e.g. http://proxy.example.com:3128)
+    pub fn proxy(&mut self, url: &str) -> &mut FredClientBuilder {
+        self.proxy = Some(String::from(url));
+        self
+    }
+
+    /// Enables or disables gzip compressed responses
+    /// 
+    /// # Arguments
+    /// * `enable` - true to request gzip responses
+    pub fn gzip(&mut self, enable: bool) -> &mut FredClientBuilder {
+        self.gzip = enable;
+        self
+    }
+
+    /// Limits the client to a number of requests per minute
+    /// 
+    /// Requests that would exceed the limit wait until a slot frees up.  Clones of the built client share the limit.  FRED allows roughly 120 requests per minute for each API key.
+    /// 
+    /// # Arguments
+    /// * `per_minute` - sustained number of requests allowed per minute
+    /// * `burst` - number of requests that may be sent back to back
+    pub fn rate_limit(&mut self, per_minute: u32, burst: u32) -> &mut FredClientBuilder {
+        self.limiter = Some(RateLimiter::new(per_minute, burst));
+        self
+    }
+
+    /// Uses an existing [RateLimiter](../rate_limit/struct.RateLimiter.html)
+    /// 
+    /// Passing clones of one limiter to several builders makes the clients share a single quota.
+    /// 
+    /// # Arguments
+    /// * `limiter` - the rate limiter to share
+    pub fn rate_limiter(&mut self, limiter: RateLimiter) -> &mut FredClientBuilder {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Retries failed requests according to a [RetryPolicy](../retry/struct.RetryPolicy.html)
+    /// 
+    /// By default requests are not retried.
+    /// 
+    /// # Arguments
+    /// * `policy` - when and how often to resend a failed request
+    pub fn retry(&mut self, policy: RetryPolicy) -> &mut FredClientBuilder {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Stores responses in an on-disk [Cache](../cache/struct.Cache.html)
+    /// 
+    /// By default responses are not cached.
+    /// 
+    /// # Arguments
+    /// * `cache` - cache configuration
+    pub fn cache(&mut self, cache: Cache) -> &mut FredClientBuilder {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sends the requests of a blocking client through a custom [Transport](../transport/trait.Transport.html)
+    /// 
+    /// By default requests are sent with reqwest.  The timeout, user agent, proxy and gzip settings only apply to the default transport.
+    /// 
+    /// # Arguments
+    /// * `transport` - the transport to use (e.g. a [MemoryTransport](../transport/struct.MemoryTransport.html))
+    pub fn transport<T: Transport + 'static>(&mut self, transport: T) -> &mut FredClientBuilder {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sends the requests of an async client through a custom [AsyncTransport](../transport/trait.AsyncTransport.html)
+    /// 
+    /// By default requests are sent with reqwest.  The timeout, user agent, proxy and gzip settings only apply to the default transport.
+    /// 
+    /// # Arguments
+    /// * `transport` - the transport to use (e.g. a [MemoryTransport](../transport/struct.MemoryTransport.html))
+    pub fn async_transport<T: AsyncTransport + 'static>(&mut self, transport: T) -> &mut FredClientBuilder {
+        self.async_transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Records requests to or replays them from a [Cassette](../cassette/struct.Cassette.html)
+    /// 
+    /// A recording cassette wraps the client's transport.  A replaying cassette answers every request itself.
+    /// 
+    /// # Arguments
+    /// * `cassette` - the cassette to record to or replay from
+    pub fn cassette(&mut self, cassette: Cassette) -> &mut FredClientBuilder {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Sets the FRED API key from a string
+    /// 
+    /// # Arguments
+    /// * `key` - The [API key](https://research.stlouisfed.org/docs/api/api_key.html) generated to access FRED
+    pub fn api_key(&mut self, key: &str) -> &mut FredClientBuilder {
+        self.api_key = ApiKey::Value(String::from(key));
+        self
+    }
+
+    /// Reads the FRED API key from an environment variable when the client is built
+    /// 
+    /// Building fails if the variable is undefined.
+    /// 
+    /// # Arguments
+    /// * `var` - name of the environment variable
+    pub fn api_key_env(&mut self, var: &str) -> &mut FredClientBuilder {
+        self.api_key = ApiKey::Env(String::from(var));
+        self
+    }
+
+    /// Reads the FRED API key from a file when the client is built
+    /// 
+    /// Leading and trailing whitespace in the file is ignored.  Building fails if the file cannot be read.
+    /// 
+    /// # Arguments
+    /// * `path` - path to a file containing only the key
+    pub fn api_key_file<P: AsRef<Path>>(&mut self, path: P) -> &mut FredClientBuilder {
+        self.api_key = ApiKey::File(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Creates a blocking [FredClient](struct.FredClient.html) from the current settings
+    pub fn build(&self) -> Result<FredClient, Error> {
+        let api_key = self.resolve_api_key()?;
+        let mut transport: Arc<dyn Transport> = match &self.transport {
+            Some(t) => t.clone(),
+            None => Arc::new(ReqwestTransport::new(self.reqwest_client()?)),
+        };
+        if let Some(cassette) = &self.cassette {
+            transport = Arc::new(CassetteTransport {
+                cassette: cassette.clone(),
+                inner: transport,
+            });
+        }
+
+        Ok(FredClient {
+            transport,
+            url_base: self.url_base.clone(),
+            api_key,
+            limiter: self.limiter.clone(),
+            retry: self.retry.clone(),
+            cache: self.cache.clone(),
+        })
+    }
+
+    /// Creates an [AsyncFredClient](../async_client/struct.AsyncFredClient.html) from the current settings
+    pub fn build_async(&self) -> Result<AsyncFredClient, Error> {
+        let api_key = self.resolve_api_key()?;
+        let mut transport: Arc<dyn AsyncTransport> = match &self.async_transport {
+            Some(t) => t.clone(),
+            None => Arc::new(AsyncReqwestTransport::new(self.async_reqwest_client()?)),
+        };
+        if let Some(cassette) = &self.cassette {
+            transport = Arc::new(AsyncCassetteTransport {
+                cassette: cassette.clone(),
+                inner: transport,
+            });
+        }
+
+        Ok(AsyncFredClient {
+            transport,
+            url_base: self.url_base.clone(),
+            api_key,
+            limiter: self.limiter.clone(),
+            retry: self.retry.clone(),
+            cache: self.cache.clone(),
+        })
+    }
+
+    fn reqwest_client(&self) -> Result<Client, Error> {
+        let mut builder = Client::builder()
+            .timeout(self.timeout)
+            .gzip(self.gzip);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        if let Some(proxy) = &self.proxy {
+            match reqwest::Proxy::all(proxy.as_str()) {
+                Ok(p) => builder = builder.proxy(p),
+                Err(e) => return Err(Error::Builder(format!("Invalid proxy {}: {}", proxy, e))),
+            }
+        }
+
+        match builder.build() {
+            Ok(client) => Ok(client),
+            Err(e) => Err(Error::transport(e)),
+        }
+    }
+
+    fn async_reqwest_client(&self) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder().gzip(self.gzip);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        if let Some(proxy) = &self.proxy {
+            match reqwest::Proxy::all(proxy.as_str()) {
+                Ok(p) => builder = builder.proxy(p),
+                Err(e) => return Err(Error::Builder(format!("Invalid proxy {}: {}", proxy, e))),
+            }
+        }
+
+        match builder.build() {
+            Ok(client) => Ok(client),
+            Err(e) => Err(Error::transport(e)),
+        }
+    }
+
+    fn resolve_api_key(&self) -> Result<String, Error> {
+        match &self.api_key {
+            ApiKey::Default => Ok(env::var(FRED_API_KEY).unwrap_or_default()),
+            ApiKey::Value(key) => Ok(key.clone()),
+            ApiKey::Env(var) => match env::var(var) {
+                Ok(key) => Ok(key),
+                Err(e) => Err(Error::Builder(format!("Unable to read API key from ${}: {}", var, e))),
+            },
+            ApiKey::File(path) => match fs::read_to_string(path) {
+                Ok(key) => Ok(String::from(key.trim())),
+                Err(e) => Err(Error::Builder(format!("Unable to read API key from {}: {}", path.display(), e))),
+            },
+        }
+    }
+}
+
+impl Default for FredClientBuilder {
+    fn default() -> FredClientBuilder {
+        FredClientBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpResponse, MemoryTransport};
+
+    #[test]
+    fn client_new() {
         match FredClient::new() {
             Ok(_) => assert_eq!(1, 1),
             Err(msg) => {
@@ -1519,4 +1519,203 @@ mod tests {
             },
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn client_builder_api_key_file() {
+        let path = env::temp_dir().join(format!("fred_rs_client_builder_api_key_file_{}", std::process::id()));
+        fs::write(&path, "abcdefghijklmnopqrstuvwxyz123456\n").unwrap();
+
+        let c = FredClient::builder()
+            .api_key_file(&path)
+            .build()
+            .unwrap();
+        assert_eq!(c.api_key.as_str(), "abcdefghijklmnopqrstuvwxyz123456");
+
+        fs::remove_file(&path).unwrap();
+        match FredClient::builder().api_key_file(&path).build() {
+            Err(Error::Builder(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn client_builder_url_base() {
+        // nothing listens on port 9 (discard) so the request fails without leaving the machine
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred")
+            .connect_timeout(Duration::from_secs(1))
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .build()
+            .unwrap();
+        assert_eq!(c.url_base.as_str(), "http://127.0.0.1:9/fred/");
+
+        match c.series("GNPCA", None) {
+            Err(e) => assert!(e.is_transport()),
+            Ok(_) => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn client_builder_invalid_proxy() {
+        match FredClient::builder().proxy("not a url").build() {
+            Err(Error::Builder(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn client_key_format() {
+        assert_eq!(check_key_format("abcdefghijklmnopqrstuvwxyz123456"), Ok(()));
+        assert!(check_key_format("").is_err());
+        assert!(check_key_format("abc").is_err());
+        assert!(check_key_format("ABCDEFGHIJKLMNOPQRSTUVWXYZ123456").is_err());
+    }
+
+    #[test]
+    fn client_validate_key() {
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .api_key("not-a-key")
+            .build()
+            .unwrap();
+        match c.validate_key() {
+            KeyStatus::Invalid(_) => (),
+            status => panic!("unexpected key status {:?}", status),
+        }
+
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .connect_timeout(Duration::from_secs(1))
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .build()
+            .unwrap();
+        match c.validate_key() {
+            KeyStatus::Unreachable(e) => assert!(e.is_transport()),
+            status => panic!("unexpected key status {:?}", status),
+        }
+    }
+
+    #[test]
+    fn client_validate_key_skips_cache() {
+        let dir = env::temp_dir().join(format!("fred_rs_client_validate_key_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = Cache::new(&dir);
+        let transport = MemoryTransport::new();
+        transport.route_response(
+            "category?category_id=0",
+            HttpResponse::new(400, r#"{"error_code":400,"error_message":"Bad Request.  The value for variable api_key is not registered."}"#)
+        );
+
+        let c = FredClient::builder()
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .transport(transport.clone())
+            .cache(cache.clone())
+            .build()
+            .unwrap();
+        cache.store(
+            "https://api.stlouisfed.org/fred/category?category_id=0&file_type=json",
+            "category",
+            r#"{"categories":[{"id":0,"name":"Categories","parent_id":0}]}"#,
+            SystemTime::now() + Duration::from_secs(3600)
+        );
+        assert!(c.category(0).is_ok());
+        assert!(transport.requests().is_empty());
+
+        // a warm cache does not hide the revoked key, and the failure is not stored
+        match c.validate_key() {
+            KeyStatus::Invalid(msg) => assert!(msg.contains("api_key")),
+            status => panic!("unexpected key status {:?}", status),
+        }
+        assert_eq!(transport.requests().len(), 1);
+        assert!(c.category(0).is_ok());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn key_status_from_response() {
+        let invalid = Err(Error::Api {
+            code: 400,
+            message: String::from("Bad Request.  The value for variable api_key is not registered."),
+        });
+        match KeyStatus::from(invalid) {
+            KeyStatus::Invalid(msg) => assert!(msg.contains("api_key")),
+            status => panic!("unexpected key status {:?}", status),
+        }
+
+        let valid = Ok(category::Response::default());
+        assert_eq!(KeyStatus::from(valid), KeyStatus::Valid);
+    }
+
+    #[test]
+    fn client_rate_limit_shared_by_clones() {
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .rate_limit(120, 1)
+            .build()
+            .unwrap();
+        let clone = c.clone();
+
+        let start = std::time::Instant::now();
+        let _ = c.category(0);
+        let _ = clone.category(0);
+        let _ = c.category(0);
+        // the second and third requests each wait half a second for a token
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[test]
+    fn client_retry_reports_attempts() {
+        let mut policy = RetryPolicy::new();
+        policy
+            .max_attempts(3)
+            .base_delay(Duration::from_millis(10));
+
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .retry(policy)
+            .build()
+            .unwrap();
+
+        match c.category(0) {
+            Err(e) => {
+                assert!(e.is_transport());
+                assert_eq!(e.attempts(), 3);
+            },
+            Ok(_) => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn client_offline_cache() {
+        let dir = env::temp_dir().join(format!("fred_rs_client_offline_cache_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut cache = Cache::new(&dir);
+        cache.offline(true);
+
+        let c = FredClient::builder()
+            .url_base("http://127.0.0.1:9/fred/")
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .cache(cache.clone())
+            .build()
+            .unwrap();
+
+        match c.category(125) {
+            Err(Error::NotCached(url)) => assert_eq!(url.as_str(), "http://127.0.0.1:9/fred/category?category_id=125&file_type=json"),
+            _ => assert_eq!(2, 1),
+        }
+
+        let body = r#"{"categories":[{"id":125,"name":"Trade Balance","parent_id":13}]}"#;
+        cache.store(
+            "http://127.0.0.1:9/fred/category?category_id=125&file_type=json",
+            "category",
+            body,
+            SystemTime::now()
+        );
+        let resp = c.category(125).unwrap();
+        assert_eq!(resp.categories[0].name.as_str(), "Trade Balance");
+        assert_eq!(c.cache().unwrap().entries().unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
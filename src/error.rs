@@ -1,10 +1,317 @@
+//! Error type returned by the FRED clients
+//!
+//! Every client method returns a [fred_rs::Error](enum.Error.html) when a request fails.  The variants distinguish problems sending the request, unsuccessful HTTP status codes, error messages returned by FRED, responses that could not be decoded and invalid builder arguments.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::Error;
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(e) => {
+//!         println!("{}", e);
+//!         return
+//!     },
+//! };
+//!
+//! match c.series("GNPCA", None) {
+//!     Ok(resp) => println!("{}", resp),
+//!     Err(e) if e.is_rate_limited() => println!("slow down: {}", e),
+//!     Err(Error::Api { code, message }) => println!("FRED error {}: {}", code, message),
+//!     Err(e) => println!("{}", e),
+//! }
+//! ```
 
 use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
 
 pub(crate) const TAG_NAME_REQUIRED_ERROR_TEXT: &str = "At least one tag must be specified using the tag_name() function of the related_tags::Builder.";
 
+/// Number of characters of a response body kept in an error
+const BODY_SNIPPET_LEN: usize = 200;
+
 #[derive(Deserialize)]
 pub(crate) struct FredError {
     pub(crate) error_code: usize,
     pub(crate) error_message: String,
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Errors returned by the FRED clients
+pub enum Error {
+    /// The request could not be sent or the response could not be read
+    Transport {
+        /// The general cause of the failure
+        kind: TransportErrorKind,
+        /// A description of the failure
+        message: String,
+    },
+    /// The server responded with an unsuccessful HTTP status and a body that is not a FRED error message
+    Status {
+        /// The HTTP status code (e.g. 502)
+        status: u16,
+        /// The beginning of the response body
+        message: String,
+    },
+    /// FRED rejected the request and returned an error message
+    ///
+    /// The error code mirrors the HTTP status of the response (e.g. 400 for a bad parameter or 429 when rate limited).
+    Api {
+        /// The error_code field of the FRED error message
+        code: usize,
+        /// The error_message field of the FRED error message
+        message: String,
+    },
+    /// The response body could not be decoded into the expected data object
+    Decode {
+        /// The endpoint that was requested (e.g. series/observations)
+        endpoint: String,
+        /// The decoding error
+        message: String,
+        /// The beginning of the response body
+        snippet: String,
+    },
+    /// The arguments passed to a builder are invalid and the request was not sent
+    Builder(String),
+    /// The client's cache is offline and holds no response for the request; contains the request URL without the API key
+    NotCached(String),
+    /// A cache management operation failed
+    Cache(String),
+    /// A [Cassette](../cassette/struct.Cassette.html) could not be read or written, or has no recording for a replayed request
+    Cassette(String),
+    /// Exported data could not be written
+    Io(String),
+    /// Data could not be converted to another format (e.g. an Arrow record batch)
+    Convert(String),
+    /// A value in a response could not be converted to its typed form (e.g. an observation date or value)
+    Parse {
+        /// The name of the field (e.g. date)
+        field: String,
+        /// The text that could not be parsed
+        value: String,
+    },
+    /// FRED returned fewer results than the count it reported, so combining the pages would silently drop data
+    Truncated {
+        /// The endpoint that was requested (e.g. series/observations)
+        endpoint: String,
+        /// The number of results FRED reported
+        count: usize,
+        /// The number of results received
+        received: usize,
+    },
+    /// The request failed after being retried by the client's [RetryPolicy](../retry/struct.RetryPolicy.html)
+    Retried {
+        /// The number of attempts made, including the first request
+        attempts: usize,
+        /// The error returned by the last attempt
+        last: Box<Error>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The general cause of an [Error::Transport](enum.Error.html#variant.Transport)
+pub enum TransportErrorKind {
+    /// The request timed out
+    Timeout,
+    /// A connection to the server could not be established
+    Connect,
+    /// Any other failure sending the request or reading the response
+    Other,
+}
+
+impl Error {
+    /// Returns the HTTP status associated with the error, if any
+    ///
+    /// For FRED error messages this is the FRED error code, which FRED sets to the HTTP status of the response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Status { status, .. } => Some(*status),
+            Error::Api { code, .. } => Some(*code as u16),
+            Error::Retried { last, .. } => last.status(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if FRED rejected a request parameter (status 400)
+    pub fn is_bad_parameter(&self) -> bool {
+        self.status() == Some(400)
+    }
+
+    /// Returns true if the request was rejected for exceeding the rate limit (status 429)
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(429)
+    }
+
+    /// Returns true if the server reported an internal error (status 5xx)
+    pub fn is_server_error(&self) -> bool {
+        match self.status() {
+            Some(status) => (500..600).contains(&status),
+            None => false,
+        }
+    }
+
+    /// Returns true if the request could not be sent or the response could not be read
+    pub fn is_transport(&self) -> bool {
+        match self {
+            Error::Transport { .. } => true,
+            Error::Retried { last, .. } => last.is_transport(),
+            _ => false,
+        }
+    }
+
+    /// Returns the number of times the request was sent before failing
+    pub fn attempts(&self) -> usize {
+        match self {
+            Error::Retried { attempts, .. } => *attempts,
+            Error::Builder(_) | Error::NotCached(_) | Error::Cache(_) | Error::Cassette(_) | Error::Io(_) | Error::Convert(_) | Error::Parse { .. } => 0,
+            _ => 1,
+        }
+    }
+
+    /// Wraps the error of the final attempt if the request was sent more than once
+    pub(crate) fn after_attempts(self, attempts: usize) -> Error {
+        if attempts > 1 {
+            Error::Retried {
+                attempts,
+                last: Box::new(self),
+            }
+        } else {
+            self
+        }
+    }
+
+    pub(crate) fn transport(err: reqwest::Error) -> Error {
+        let kind = if err.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if err.is_connect() {
+            TransportErrorKind::Connect
+        } else {
+            TransportErrorKind::Other
+        };
+
+        Error::Transport {
+            kind,
+            message: err.to_string(),
+        }
+    }
+
+    pub(crate) fn parse_field(field: &str, value: &str) -> Error {
+        Error::Parse {
+            field: String::from(field),
+            value: String::from(value),
+        }
+    }
+
+    pub(crate) fn status_code(status: u16, body: &str) -> Error {
+        Error::Status {
+            status,
+            message: snippet(body),
+        }
+    }
+
+    pub(crate) fn decode(endpoint: &str, err: serde_json::Error, body: &str) -> Error {
+        Error::Decode {
+            endpoint: String::from(endpoint),
+            message: err.to_string(),
+            snippet: snippet(body),
+        }
+    }
+}
+
+impl From<FredError> for Error {
+    fn from(err: FredError) -> Error {
+        Error::Api {
+            code: err.error_code,
+            message: err.error_message,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport { message, .. } => write!(f, "Transport error: {}", message),
+            Error::Status { status, message } => write!(f, "HTTP {}: {}", status, message),
+            Error::Api { code, message } => write!(f, "ERROR {}: {}", code, message),
+            Error::Decode { endpoint, message, snippet } => write!(
+                f,
+                "Unable to decode {} response: {} (body: {})",
+                endpoint,
+                message,
+                snippet
+            ),
+            Error::Builder(message) => write!(f, "{}", message),
+            Error::NotCached(url) => write!(f, "Offline cache has no response for {}", url),
+            Error::Cache(message) => write!(f, "Cache error: {}", message),
+            Error::Cassette(message) => write!(f, "Cassette error: {}", message),
+            Error::Io(message) => write!(f, "Write error: {}", message),
+            Error::Convert(message) => write!(f, "Conversion error: {}", message),
+            Error::Parse { field, value } => write!(f, "Unable to parse {} '{}'", field, value),
+            Error::Truncated { endpoint, count, received } => write!(
+                f,
+                "Incomplete {} response: received {} of {} results",
+                endpoint,
+                received,
+                count
+            ),
+            Error::Retried { attempts, last } => write!(f, "{} (after {} attempts)", last, attempts),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Retried { last, .. } => Some(last.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the beginning of a response body for use in error messages
+fn snippet(body: &str) -> String {
+    match body.char_indices().nth(BODY_SNIPPET_LEN) {
+        Some((idx, _)) => format!("{}...", &body[..idx]),
+        None => String::from(body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_status_helpers() {
+        let e = Error::Api { code: 429, message: String::from("Too Many Requests.") };
+        assert!(e.is_rate_limited());
+        assert!(!e.is_bad_parameter());
+
+        let e = Error::status_code(503, "<html>Service Unavailable</html>");
+        assert!(e.is_server_error());
+        assert_eq!(e.status(), Some(503));
+
+        let e = Error::Builder(String::from(TAG_NAME_REQUIRED_ERROR_TEXT));
+        assert_eq!(e.status(), None);
+        assert_eq!(e.to_string(), TAG_NAME_REQUIRED_ERROR_TEXT);
+    }
+
+    #[test]
+    fn error_after_attempts() {
+        let e = Error::Api { code: 429, message: String::from("Too Many Requests.") };
+        assert_eq!(e.clone().after_attempts(1), e);
+
+        let e = e.after_attempts(3);
+        assert_eq!(e.attempts(), 3);
+        assert!(e.is_rate_limited());
+        assert_eq!(e.to_string(), "ERROR 429: Too Many Requests. (after 3 attempts)");
+    }
+
+    #[test]
+    fn error_body_snippet() {
+        let body = "x".repeat(500);
+        match Error::status_code(502, &body) {
+            Error::Status { message, .. } => assert_eq!(message.len(), BODY_SNIPPET_LEN + 3),
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! CSV and TSV export of observations, panels and metadata lists
+//!
+//! A [CsvWriter](struct.CsvWriter.html) writes delimited text that spreadsheets and R read directly.  It can write:
+//! * the observations of a `series::observation::Response` (realtime_start, realtime_end, date, value)
+//! * a [Panel](../panel/struct.Panel.html) of aligned series (date followed by one column per series)
+//! * lists of series, tags or categories
+//! * the per-date statistics of a [Revisions](../revisions/struct.Revisions.html) history
+//!
+//! The missing-value token (default empty), the date format (default `%Y-%m-%d`) and the delimiter are configurable.  With `metadata_header(true)` the data is preceded by rows starting with `#` that give the series id, title, units and retrieval time.
+//!
+//! ```
+//! use fred_rs::export::CsvWriter;
+//! use fred_rs::panel::Builder;
+//! use fred_rs::timeseries::TimeSeries;
+//! use chrono::NaiveDate;
+//!
+//! let date = |m| NaiveDate::from_ymd_opt(2020, m, 1).unwrap();
+//! let mut builder = Builder::new();
+//! builder.series("UNRATE", TimeSeries::new(vec![(date(1), Some(3.6)), (date(2), None)]));
+//! let panel = builder.build();
+//!
+//! let mut writer = CsvWriter::tsv();
+//! writer.missing_value("NA").date_format("%m/%d/%Y");
+//!
+//! let mut out = Vec::new();
+//! writer.write_panel(&mut out, &panel).unwrap();
+//! assert_eq!(String::from_utf8(out).unwrap(), "date\tUNRATE\n01/01/2020\t3.6\n02/01/2020\tNA\n");
+//! ```
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::category::Category;
+use crate::error::Error;
+use crate::panel::{Column, Panel};
+use crate::revisions::Revisions;
+use crate::series::Series;
+use crate::series::observation::Response;
+use crate::tags::Tag;
+
+/// Date format used when none is set
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Clone, Debug)]
+/// Writes delimited text files
+pub struct CsvWriter {
+    delimiter: char,
+    missing: String,
+    date_format: String,
+    metadata: bool,
+    retrieved: Option<DateTime<Utc>>,
+}
+
+impl CsvWriter {
+    /// Creates a comma separated writer
+    pub fn new() -> CsvWriter {
+        CsvWriter {
+            delimiter: ',',
+            missing: String::new(),
+            date_format: String::from(DEFAULT_DATE_FORMAT),
+            metadata: false,
+            retrieved: None,
+        }
+    }
+
+    /// Creates a tab separated writer
+    pub fn tsv() -> CsvWriter {
+        let mut writer = CsvWriter::new();
+        writer.delimiter('\t');
+        writer
+    }
+
+    /// Sets the field delimiter
+    ///
+    /// # Arguments
+    /// * `delimiter` - character placed between fields (default ',')
+    pub fn delimiter(&mut self, delimiter: char) -> &mut CsvWriter {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the text written for missing values
+    ///
+    /// # Arguments
+    /// * `token` - e.g. "NA", "." or "" (default "")
+    pub fn missing_value(&mut self, token: &str) -> &mut CsvWriter {
+        self.missing = String::from(token);
+        self
+    }
+
+    /// Sets the format of dates
+    ///
+    /// # Arguments
+    /// * `format` - a chrono strftime format (default "%Y-%m-%d")
+    pub fn date_format(&mut self, format: &str) -> &mut CsvWriter {
+        self.date_format = String::from(format);
+        self
+    }
+
+    /// Writes metadata rows before the data
+    ///
+    /// Each metadata row starts with a `#` field naming the property: `# series_id`, `# title`, `# units` and `# retrieved`.
+    ///
+    /// # Arguments
+    /// * `enabled` - true to write the metadata rows (default false)
+    pub fn metadata_header(&mut self, enabled: bool) -> &mut CsvWriter {
+        self.metadata = enabled;
+        self
+    }
+
+    /// Sets the retrieval time written in the metadata header
+    ///
+    /// The time of writing is used if none is set.
+    ///
+    /// # Arguments
+    /// * `time` - when the data was downloaded
+    pub fn retrieved(&mut self, time: DateTime<Utc>) -> &mut CsvWriter {
+        self.retrieved = Some(time);
+        self
+    }
+
+    /// Writes the observations of a single series
+    ///
+    /// # Arguments
+    /// * `out` - destination of the text
+    /// * `column` - the series id and metadata written in the metadata header
+    /// * `resp` - observations returned by fred/series/observations
+    pub fn write_observations<W: Write>(&self, out: &mut W, column: &Column, resp: &Response) -> Result<(), Error> {
+        let mut text = String::new();
+        self.metadata_rows(&mut text, std::slice::from_ref(column));
+        self.row(&mut text, &["realtime_start", "realtime_end", "date", "value"]);
+
+        for point in &resp.observations {
+            let o = point.to_observation()?;
+            let cells = [
+                self.date(o.realtime_start)?,
+                self.date(o.realtime_end)?,
+                self.date(o.date)?,
+                self.value(o.value),
+            ];
+            self.row(&mut text, &cells);
+        }
+
+        write_all(out, &text)
+    }
+
+    /// Writes aligned series with one row per date
+    ///
+    /// # Arguments
+    /// * `out` - destination of the text
+    /// * `panel` - the aligned series
+    pub fn write_panel<W: Write>(&self, out: &mut W, panel: &Panel) -> Result<(), Error> {
+        let mut text = String::new();
+        self.metadata_rows(&mut text, panel.columns());
+
+        let mut header = vec![String::from("date")];
+        header.extend(panel.columns().iter().map(|c| c.name.clone()));
+        self.row(&mut text, &header);
+
+        for (idx, date) in panel.dates().iter().enumerate() {
+            let mut cells = vec![self.date(*date)?];
+            if let Some(row) = panel.row(idx) {
+                cells.extend(row.into_iter().map(|v| self.value(v)));
+            }
+            self.row(&mut text, &cells);
+        }
+
+        write_all(out, &text)
+    }
+
+    /// Writes a list of series such as `series::Response::seriess`
+    ///
+    /// # Arguments
+    /// * `out` - destination of the text
+    /// * `seriess` - the series to write
+    pub fn write_series<W: Write>(&self, out: &mut W, seriess: &[Series]) -> Result<(), Error> {
+        let mut text = String::new();
+        self.row(&mut text, &[
+            "id", "title", "observation_start", "observation_end", "frequency", "units",
+            "seasonal_adjustment", "last_updated", "popularity", "realtime_start", "realtime_end", "notes",
+        ]);
+        for s in seriess {
+            self.row(&mut text, &[
+                s.id.clone(),
+                s.title.clone(),
+                s.observation_start.clone(),
+                s.observation_end.clone(),
+                s.frequency.clone(),
+                s.units.clone(),
+                s.seasonal_adjustment.clone(),
+                s.last_updated.clone(),
+                s.popularity.to_string(),
+                s.realtime_start.clone(),
+                s.realtime_end.clone(),
+                s.notes.clone().unwrap_or_default(),
+            ]);
+        }
+        write_all(out, &text)
+    }
+
+    /// Writes a list of tags such as `tags::Response::tags`
+    ///
+    /// # Arguments
+    /// * `out` - destination of the text
+    /// * `tags` - the tags to write
+    pub fn write_tags<W: Write>(&self, out: &mut W, tags: &[Tag]) -> Result<(), Error> {
+        let mut text = String::new();
+        self.row(&mut text, &["name", "group_id", "created", "popularity", "series_count", "notes"]);
+        for t in tags {
+            self.row(&mut text, &[
+                t.name.clone(),
+                t.group_id.clone(),
+                t.created.clone(),
+                t.popularity.to_string(),
+                t.series_count.to_string(),
+                t.notes.clone().unwrap_or_default(),
+            ]);
+        }
+        write_all(out, &text)
+    }
+
+    /// Writes a list of categories such as `category::Response::categories`
+    ///
+    /// # Arguments
+    /// * `out` - destination of the text
+    /// * `categories` - the categories to write
+    pub fn write_categories<W: Write>(&self, out: &mut W, categories: &[Category]) -> Result<(), Error> {
+        let mut text = String::new();
+        self.row(&mut text, &["id", "name", "parent_id", "notes"]);
+        for c in categories {
+            self.row(&mut text, &[
+                c.id.to_string(),
+                c.name.clone(),
+                c.parent_id.to_string(),
+                c.notes.clone().unwrap_or_default(),
+            ]);
+        }
+        write_all(out, &text)
+    }
+
+    /// Writes the revision statistics of each observation date
+    ///
+    /// # Arguments
+    /// * `out` - destination of the text
+    /// * `revisions` - the revision history of a series
+    pub fn write_revisions<W: Write>(&self, out: &mut W, revisions: &Revisions) -> Result<(), Error> {
+        let mut text = String::new();
+        self.row(&mut text, &[
+            "date", "initial_release", "initial_value", "latest_release", "latest_value", "revision_count",
+            "total_revision", "mean_revision", "mean_absolute_revision", "days_to_final",
+        ]);
+        for d in revisions.dates() {
+            let (initial, latest) = match (d.initial(), d.latest()) {
+                (Some(initial), Some(latest)) => (initial, latest),
+                _ => continue,
+            };
+            self.row(&mut text, &[
+                self.date(d.date)?,
+                self.date(initial.vintage_date)?,
+                self.value(initial.value),
+                self.date(latest.vintage_date)?,
+                self.value(latest.value),
+                d.revision_count().to_string(),
+                self.value(d.total_revision()),
+                self.value(d.mean_revision()),
+                self.value(d.mean_absolute_revision()),
+                d.days_to_final().map_or_else(|| self.missing.clone(), |days| days.to_string()),
+            ]);
+        }
+        write_all(out, &text)
+    }
+
+    /// Appends the metadata rows for a list of columns if enabled
+    fn metadata_rows(&self, text: &mut String, columns: &[Column]) {
+        if !self.metadata {
+            return;
+        }
+
+        let property = |name: &str, f: &dyn Fn(&Column) -> String| {
+            let mut cells = vec![format!("# {}", name)];
+            cells.extend(columns.iter().map(f));
+            cells
+        };
+        let retrieved = self.retrieved
+            .unwrap_or_else(Utc::now)
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        self.row(text, &property("series_id", &|c| c.name.clone()));
+        self.row(text, &property("title", &|c| c.title.clone().unwrap_or_default()));
+        self.row(text, &property("units", &|c| c.units.clone().unwrap_or_default()));
+        self.row(text, &[String::from("# retrieved"), retrieved]);
+    }
+
+    /// Appends one delimited line, quoting fields that need it
+    fn row<S: AsRef<str>>(&self, text: &mut String, cells: &[S]) {
+        for (idx, cell) in cells.iter().enumerate() {
+            if idx > 0 {
+                text.push(self.delimiter);
+            }
+            let cell = cell.as_ref();
+            if cell.contains([self.delimiter, '"', '\n', '\r']) {
+                text.push('"');
+                text.push_str(&cell.replace('"', "\"\""));
+                text.push('"');
+            } else {
+                text.push_str(cell);
+            }
+        }
+        text.push('\n');
+    }
+
+    fn date(&self, date: NaiveDate) -> Result<String, Error> {
+        let mut text = String::new();
+        match write!(text, "{}", date.format(&self.date_format)) {
+            Ok(()) => Ok(text),
+            Err(_) => Err(Error::Builder(format!("Invalid date format '{}'", self.date_format))),
+        }
+    }
+
+    fn value(&self, value: Option<f64>) -> String {
+        match value {
+            Some(v) => v.to_string(),
+            None => self.missing.clone(),
+        }
+    }
+}
+
+impl Default for CsvWriter {
+    fn default() -> CsvWriter {
+        CsvWriter::new()
+    }
+}
+
+fn write_all<W: Write>(out: &mut W, text: &str) -> Result<(), Error> {
+    match out.write_all(text.as_bytes()) {
+        Ok(()) => Ok(()),
+        Err(e) => Err(Error::Io(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panel::Builder;
+    use crate::timeseries::TimeSeries;
+    use crate::realtime::RealtimeObservations;
+    use crate::series::observation::Observation;
+    use chrono::TimeZone;
+
+    fn observations() -> Response {
+        serde_json::from_str(r#"{"realtime_start":"2020-01-01","realtime_end":"2020-01-01","observation_start":"1776-07-04","observation_end":"9999-12-31",
+            "units":"lin","output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":2,"offset":0,"limit":100000,
+            "observations":[
+                {"realtime_start":"2020-01-01","realtime_end":"2020-01-01","date":"2019-01-01","value":"1.5"},
+                {"realtime_start":"2020-01-01","realtime_end":"2020-01-01","date":"2019-04-01","value":"."}
+            ]}"#).unwrap()
+    }
+
+    fn written<F: Fn(&mut Vec<u8>) -> Result<(), Error>>(f: F) -> String {
+        let mut out = Vec::new();
+        f(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn export_observations() {
+        let mut writer = CsvWriter::new();
+        writer.missing_value(".")
+            .metadata_header(true)
+            .retrieved(Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap());
+        let column = Column {
+            title: Some(String::from("Real GNP, \"chained\"")),
+            units: Some(String::from("Billions")),
+            ..Column::new("GNPCA")
+        };
+
+        let text = written(|out| writer.write_observations(out, &column, &observations()));
+        assert_eq!(text, "# series_id,GNPCA\n\
+            # title,\"Real GNP, \"\"chained\"\"\"\n\
+            # units,Billions\n\
+            # retrieved,2020-01-02T03:04:05Z\n\
+            realtime_start,realtime_end,date,value\n\
+            2020-01-01,2020-01-01,2019-01-01,1.5\n\
+            2020-01-01,2020-01-01,2019-04-01,.\n");
+
+        let mut bad = CsvWriter::new();
+        bad.date_format("%Q");
+        match bad.write_observations(&mut Vec::new(), &column, &observations()) {
+            Err(Error::Builder(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn export_panel_and_metadata() {
+        let date = |m| NaiveDate::from_ymd_opt(2020, m, 1).unwrap();
+        let mut builder = Builder::new();
+        builder.series("A", TimeSeries::new(vec![(date(1), Some(1.0)), (date(2), None)]))
+            .series("B", TimeSeries::new(vec![(date(2), Some(2.5))]));
+        let mut writer = CsvWriter::new();
+        writer.metadata_header(true).retrieved(Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap());
+
+        let text = written(|out| writer.write_panel(out, &builder.build()));
+        assert_eq!(text, "# series_id,A,B\n# title,,\n# units,,\n# retrieved,2020-01-02T00:00:00Z\ndate,A,B\n2020-01-01,1,\n2020-02-01,,2.5\n");
+
+        let categories = vec![Category { id: 125, name: String::from("Trade Balance"), parent_id: 13, notes: None }];
+        let text = written(|out| CsvWriter::tsv().write_categories(out, &categories));
+        assert_eq!(text, "id\tname\tparent_id\tnotes\n125\tTrade Balance\t13\t\n");
+    }
+
+    #[test]
+    fn export_revisions() {
+        let date = |m| NaiveDate::from_ymd_opt(2020, m, 1).unwrap();
+        let history = RealtimeObservations::new(vec![
+            Observation { realtime_start: date(1), realtime_end: date(2), date: date(1), value: Some(1.0) },
+            Observation { realtime_start: date(3), realtime_end: date(3), date: date(1), value: Some(1.5) },
+        ]);
+
+        let text = written(|out| CsvWriter::new().write_revisions(out, &Revisions::from_realtime(&history)));
+        assert_eq!(text, "date,initial_release,initial_value,latest_release,latest_value,revision_count,total_revision,mean_revision,mean_absolute_revision,days_to_final\n\
+            2020-01-01,2020-01-01,1,2020-03-01,1.5,1,0.5,0.5,0.5,60\n");
+    }
+}
@@ -88,6 +88,32 @@
 //! client.with_key("abcdefghijklmnopqrstuvwxyz123456");
 //! ```
 //! 
+//! # Client Configuration
+//! `FredClient::builder()` returns a `FredClientBuilder` that configures the 
+//! base URL, timeouts, user agent, proxy and gzip support.  The API key can be 
+//! given as a string or read from any environment variable or a file.  Pointing 
+//! the base URL at a local server makes it possible to test without reaching FRED.
+//! Requests can also be routed through a custom `transport::Transport`, such as 
+//! the in-memory `transport::MemoryTransport` that serves fixture bodies, or 
+//! recorded to and replayed from a `cassette::Cassette` file.
+//! ```rust
+//! use fred_rs::client::FredClient;
+//! use std::time::Duration;
+//! 
+//! let client = match FredClient::builder()
+//!     .url_base("http://localhost:8080/fred/")
+//!     .timeout(Duration::from_secs(10))
+//!     .api_key_file("/etc/fred/api_key")
+//!     .build()
+//! {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! ```
+//! 
 //! # Issues/Bugs/Improvments/Help/Questions
 //! If you discover any issues or bugs, want to suggest any improvements, or 
 //! have questions about the crate, feel free to open a GitHub issue or email 
@@ -105,6 +131,7 @@
 //! conditions.
 
 pub mod client;
+pub mod async_client;
 pub mod category;
 pub mod releases;
 pub mod release;
@@ -113,5 +140,29 @@ pub mod tags;
 pub mod related_tags;
 pub mod sources;
 pub mod source;
+pub mod error;
+pub mod rate_limit;
+pub mod retry;
+pub mod cache;
+pub mod transport;
+pub mod cassette;
+pub mod pagination;
+pub mod bulk;
+pub mod chunking;
+pub mod timeseries;
+pub mod realtime;
+pub mod revisions;
+pub mod backtest;
+pub mod panel;
+pub mod export;
+#[cfg(feature = "mock-server")]
+pub mod mock_server;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "polars")]
+pub mod polars;
+
+pub use error::Error;
 
-mod error;
\ No newline at end of file
+mod request;
+mod pipeline;
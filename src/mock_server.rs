@@ -0,0 +1,1143 @@
+//! A local FRED server for integration tests
+//!
+//! _Requires the `mock-server` feature._
+//!
+//! [MockServer](struct.MockServer.html) listens on a local port and answers the `/fred/*` routes used by the clients from [Fixtures](struct.Fixtures.html).  Responses use the same JSON layout as FRED, including error messages for missing or invalid parameters, so a client pointed at the server with `FredClientBuilder::url_base()` can run its whole workflow without network access.
+//!
+//! The server supports the `limit`, `offset`, `sort_order` and `order_by` arguments of list endpoints, realtime periods of observations and the `observation_start`/`observation_end` range.  It can also simulate rate limiting by answering with status 429.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::mock_server::{Fixtures, MockServer};
+//!
+//! let server = MockServer::start(Fixtures::sample()).unwrap();
+//! let client = FredClient::builder()
+//!     .url_base(&server.url_base())
+//!     .api_key("abcdefghijklmnopqrstuvwxyz123456")
+//!     .build()
+//!     .unwrap();
+//!
+//! let resp = client.series_observation("GNPCA", None).unwrap();
+//! assert_eq!(resp.observations[0].date, "2016-01-01");
+//!
+//! // the next request is rejected as if the rate limit were exceeded
+//! server.throttle_next(1);
+//! assert!(client.series("GNPCA", None).unwrap_err().is_rate_limited());
+//! ```
+//!
+//! # Fixtures
+//! Fixtures are a JSON document holding FRED objects.  Series carry their observations, categories, release and tags; releases carry their release dates and sources.
+//! ```json
+//! {
+//!     "series": [{
+//!         "id": "GNPCA", "title": "Real Gross National Product", ...,
+//!         "category_ids": [106], "release_id": 53, "tags": ["gnp", "usa"],
+//!         "observations": [
+//!             {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2018-01-01", "value": "18650.9"}
+//!         ]
+//!     }],
+//!     "categories": [{"id": 106, "name": "National Income & Product Accounts", "parent_id": 18}],
+//!     "releases": [{"id": 53, "name": "Gross Domestic Product", ..., "dates": ["2019-07-26"], "source_ids": [18]}],
+//!     "tags": [{"name": "gnp", "group_id": "gen", "notes": "", "created": "2012-02-27 10:18:19-06", "popularity": 79}],
+//!     "sources": [{"id": 18, "realtime_start": "2019-01-01", "realtime_end": "9999-12-31", "name": "U.S. Bureau of Economic Analysis"}]
+//! }
+//! ```
+
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::client::check_key_format;
+use crate::error::{Error, TransportErrorKind};
+
+/// Maximum limit accepted by list endpoints
+const MAX_LIMIT: usize = 1000;
+/// Maximum limit accepted by fred/series/observations
+const MAX_OBSERVATION_LIMIT: usize = 100_000;
+const EARLIEST_REALTIME: &str = "1776-07-04";
+const LATEST_REALTIME: &str = "9999-12-31";
+
+const SAMPLE_FIXTURES: &str = r#"{
+    "series": [
+        {
+            "id": "GNPCA", "realtime_start": "2019-07-26", "realtime_end": "9999-12-31",
+            "title": "Real Gross National Product", "observation_start": "2016-01-01", "observation_end": "2018-01-01",
+            "frequency": "Annual", "frequency_short": "A", "units": "Billions of Chained 2012 Dollars", "units_short": "Bil. of Chn. 2012 $",
+            "seasonal_adjustment": "Not Seasonally Adjusted", "seasonal_adjustment_short": "NSA",
+            "last_updated": "2019-07-26 07:51:31-05", "popularity": 13, "notes": "BEA Account Code: A001RX",
+            "category_ids": [106], "release_id": 53, "tags": ["annual", "gnp", "nation", "usa"],
+            "observations": [
+                {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2016-01-01", "value": "17659.187"},
+                {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2017-01-01", "value": "18050.683"},
+                {"realtime_start": "2019-03-28", "realtime_end": "2019-07-25", "date": "2018-01-01", "value": "18566.7"},
+                {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2018-01-01", "value": "18650.9"}
+            ]
+        },
+        {
+            "id": "GDPC1", "realtime_start": "2019-07-26", "realtime_end": "9999-12-31",
+            "title": "Real Gross Domestic Product", "observation_start": "2018-10-01", "observation_end": "2019-04-01",
+            "frequency": "Quarterly", "frequency_short": "Q", "units": "Billions of Chained 2012 Dollars", "units_short": "Bil. of Chn. 2012 $",
+            "seasonal_adjustment": "Seasonally Adjusted Annual Rate", "seasonal_adjustment_short": "SAAR",
+            "last_updated": "2019-08-29 07:52:03-05", "popularity": 84, "notes": "BEA Account Code: A191RX",
+            "category_ids": [106], "release_id": 53, "tags": ["gdp", "nation", "quarterly", "usa"],
+            "observations": [
+                {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2018-10-01", "value": "18783.548"},
+                {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2019-01-01", "value": "18927.281"},
+                {"realtime_start": "2019-07-26", "realtime_end": "9999-12-31", "date": "2019-04-01", "value": "."}
+            ]
+        }
+    ],
+    "categories": [
+        {"id": 0, "name": "Categories", "parent_id": 0},
+        {"id": 18, "name": "National Accounts", "parent_id": 0},
+        {"id": 106, "name": "National Income & Product Accounts", "parent_id": 18}
+    ],
+    "releases": [
+        {
+            "id": 53, "realtime_start": "2019-01-01", "realtime_end": "9999-12-31",
+            "name": "Gross Domestic Product", "press_release": true, "link": "http://www.bea.gov/national/index.htm",
+            "dates": ["2019-03-28", "2019-07-26", "2019-08-29"], "source_ids": [18]
+        }
+    ],
+    "tags": [
+        {"name": "annual", "group_id": "freq", "notes": "", "created": "2012-02-27 10:18:19-06", "popularity": 86},
+        {"name": "gdp", "group_id": "gen", "notes": "Gross Domestic Product", "created": "2012-02-27 10:18:19-06", "popularity": 81},
+        {"name": "gnp", "group_id": "gen", "notes": "Gross National Product", "created": "2012-02-27 10:18:19-06", "popularity": 39},
+        {"name": "nation", "group_id": "geot", "notes": "Country Level", "created": "2012-02-27 10:18:19-06", "popularity": 100},
+        {"name": "quarterly", "group_id": "freq", "notes": "", "created": "2012-02-27 10:18:19-06", "popularity": 88},
+        {"name": "usa", "group_id": "geo", "notes": "United States of America", "created": "2012-02-27 10:18:19-06", "popularity": 100}
+    ],
+    "sources": [
+        {"id": 18, "realtime_start": "2019-01-01", "realtime_end": "9999-12-31", "name": "U.S. Bureau of Economic Analysis", "link": "http://www.bea.gov/"}
+    ]
+}"#;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+/// Data served by a [MockServer](struct.MockServer.html)
+pub struct Fixtures {
+    #[serde(default)]
+    series: Vec<SeriesFixture>,
+    #[serde(default)]
+    categories: Vec<Value>,
+    #[serde(default)]
+    releases: Vec<ReleaseFixture>,
+    #[serde(default)]
+    tags: Vec<Value>,
+    #[serde(default)]
+    sources: Vec<Value>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SeriesFixture {
+    #[serde(default)]
+    observations: Vec<Value>,
+    #[serde(default)]
+    category_ids: Vec<usize>,
+    #[serde(default)]
+    release_id: Option<usize>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// The FRED series object
+    #[serde(flatten)]
+    series: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ReleaseFixture {
+    #[serde(default)]
+    dates: Vec<String>,
+    #[serde(default)]
+    source_ids: Vec<usize>,
+    /// The FRED release object
+    #[serde(flatten)]
+    release: Map<String, Value>,
+}
+
+impl Fixtures {
+    /// Creates an empty set of fixtures
+    pub fn new() -> Fixtures {
+        Fixtures::default()
+    }
+
+    /// Returns a small built-in data set
+    ///
+    /// The sample holds the annual series GNPCA (with a revised 2018 value) and the quarterly series GDPC1, their category, the Gross Domestic Product release, its source and their tags.
+    pub fn sample() -> Fixtures {
+        match Fixtures::from_json(SAMPLE_FIXTURES) {
+            Ok(f) => f,
+            Err(e) => panic!("invalid sample fixtures: {}", e),
+        }
+    }
+
+    /// Parses fixtures from a JSON document
+    ///
+    /// # Arguments
+    /// * `text` - JSON document (see the [module documentation](index.html#fixtures))
+    pub fn from_json(text: &str) -> Result<Fixtures, Error> {
+        match serde_json::from_str(text) {
+            Ok(f) => Ok(f),
+            Err(e) => Err(Error::Builder(format!("Invalid fixtures: {}", e))),
+        }
+    }
+
+    /// Reads fixtures from a JSON file
+    ///
+    /// # Arguments
+    /// * `path` - path to a JSON document
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Fixtures, Error> {
+        match fs::read_to_string(path.as_ref()) {
+            Ok(text) => Fixtures::from_json(&text),
+            Err(e) => Err(Error::Builder(format!("Unable to read {}: {}", path.as_ref().display(), e))),
+        }
+    }
+
+    fn series_by_id(&self, id: &str) -> Option<&SeriesFixture> {
+        self.series.iter().find(|s| s.series.get("id").and_then(Value::as_str) == Some(id))
+    }
+
+    fn release_by_id(&self, id: usize) -> Option<&ReleaseFixture> {
+        self.releases.iter().find(|r| id_of(&r.release) == Some(id))
+    }
+
+    fn category_by_id(&self, id: usize) -> Option<&Value> {
+        self.categories.iter().find(|c| c["id"].as_u64() == Some(id as u64))
+    }
+
+    fn source_by_id(&self, id: usize) -> Option<&Value> {
+        self.sources.iter().find(|s| s["id"].as_u64() == Some(id as u64))
+    }
+
+    /// Returns the tag object for a name with the number of series carrying it
+    fn tag(&self, name: &str) -> Value {
+        let mut tag = match self.tags.iter().find(|t| t["name"].as_str() == Some(name)) {
+            Some(t) => t.clone(),
+            None => json!({
+                "name": name,
+                "group_id": "gen",
+                "notes": "",
+                "created": "2012-02-27 10:18:19-06",
+                "popularity": 0
+            }),
+        };
+        let count = self.series.iter().filter(|s| s.tags.iter().any(|t| t == name)).count();
+        tag["series_count"] = json!(count);
+        tag
+    }
+}
+
+/// Client activity and simulated failures, shared with the connection threads
+#[derive(Debug, Default)]
+struct Control {
+    throttle_next: usize,
+    per_minute: Option<u32>,
+    window: Vec<Instant>,
+    requests: Vec<String>,
+}
+
+#[derive(Debug)]
+struct State {
+    fixtures: Fixtures,
+    control: Mutex<Control>,
+}
+
+impl State {
+    fn control(&self) -> MutexGuard<'_, Control> {
+        match self.control.lock() {
+            Ok(c) => c,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A local HTTP server answering FRED requests from fixtures
+///
+/// The server stops when it is dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free local port
+    ///
+    /// # Arguments
+    /// * `fixtures` - the data to serve
+    pub fn start(fixtures: Fixtures) -> Result<MockServer, Error> {
+        let listener = match TcpListener::bind("127.0.0.1:0") {
+            Ok(l) => l,
+            Err(e) => return Err(Error::Transport { kind: TransportErrorKind::Other, message: e.to_string() }),
+        };
+        let addr = match listener.local_addr() {
+            Ok(a) => a,
+            Err(e) => return Err(Error::Transport { kind: TransportErrorKind::Other, message: e.to_string() }),
+        };
+
+        let state = Arc::new(State {
+            fixtures,
+            control: Mutex::new(Control::default()),
+        });
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_state = state.clone();
+        let thread_shutdown = shutdown.clone();
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = thread_state.clone();
+                    thread::spawn(move || handle_connection(&state, stream));
+                }
+            }
+        });
+
+        Ok(MockServer {
+            addr,
+            state,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the base URL to pass to `FredClientBuilder::url_base()`
+    pub fn url_base(&self) -> String {
+        format!("http://{}/fred/", self.addr)
+    }
+
+    /// Returns the address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Answers the next requests with status 429
+    ///
+    /// # Arguments
+    /// * `count` - number of requests to reject
+    pub fn throttle_next(&self, count: usize) {
+        self.state.control().throttle_next = count;
+    }
+
+    /// Answers requests above a number per minute with status 429
+    ///
+    /// # Arguments
+    /// * `limit` - requests allowed in any 60 second window
+    pub fn requests_per_minute(&self, limit: u32) {
+        self.state.control().per_minute = Some(limit);
+    }
+
+    /// Returns the path and query of every request received, without the API key
+    pub fn requests(&self) -> Vec<String> {
+        self.state.control().requests.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(state: &State, stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => (),
+            Err(_) => return,
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => respond(state, target),
+        (Some(_), Some(_)) => fred_error(405, "Method Not Allowed."),
+        _ => return,
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    let mut stream = &stream;
+    let _ = stream.write_all(response.as_bytes());
+    let _ = stream.flush();
+}
+
+/// Produces the status and body for a request target (path and query)
+fn respond(state: &State, target: &str) -> (u16, String) {
+    let (path, query) = match target.find('?') {
+        Some(idx) => (&target[..idx], &target[idx + 1..]),
+        None => (target, ""),
+    };
+    let params = parse_query(query);
+
+    {
+        let mut control = state.control();
+        let redacted: Vec<&str> = query.split('&').filter(|p| !p.starts_with("api_key=")).collect();
+        control.requests.push(format!("{}?{}", path, redacted.join("&")));
+
+        if control.throttle_next > 0 {
+            control.throttle_next -= 1;
+            return fred_error(429, "Too Many Requests.  Exceeded Rate Limit");
+        }
+        if let Some(limit) = control.per_minute {
+            let now = Instant::now();
+            control.window.retain(|t| now.duration_since(*t) < Duration::from_secs(60));
+            if control.window.len() >= limit as usize {
+                return fred_error(429, "Too Many Requests.  Exceeded Rate Limit");
+            }
+            control.window.push(now);
+        }
+    }
+
+    let endpoint = match path.strip_prefix("/fred/") {
+        Some(e) => e,
+        None => return fred_error(404, "Not Found."),
+    };
+
+    let result = Query::parse(endpoint, params)
+        .and_then(|q| route(&state.fixtures, endpoint, &q));
+    match result {
+        Ok(body) => (200, body.to_string()),
+        Err((status, message)) => fred_error(status, &message),
+    }
+}
+
+type RouteResult = Result<Value, (u16, String)>;
+
+fn bad_request(message: &str) -> (u16, String) {
+    (400, format!("Bad Request.  {}", message))
+}
+
+fn fred_error(status: u16, message: &str) -> (u16, String) {
+    let body = json!({ "error_code": status, "error_message": message });
+    (status, body.to_string())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        _ => "Error",
+    }
+}
+
+/// Validated arguments common to all endpoints
+struct Query {
+    params: HashMap<String, String>,
+    realtime_start: String,
+    realtime_end: String,
+    limit: usize,
+    offset: usize,
+    order_by: Option<String>,
+    descending: Option<bool>,
+}
+
+impl Query {
+    fn parse(endpoint: &str, params: HashMap<String, String>) -> Result<Query, (u16, String)> {
+        match params.get("api_key") {
+            None => return Err(bad_request("Variable api_key is not set.  Read https://research.stlouisfed.org/docs/api/api_key.html for more information.")),
+            Some(key) if check_key_format(key).is_err() => return Err(bad_request(
+                "The value for variable api_key is not a 32 character alpha-numeric lower-case string.  Read https://research.stlouisfed.org/docs/api/api_key.html for more information."
+            )),
+            Some(_) => (),
+        }
+        match params.get("file_type").map(String::as_str) {
+            None | Some("json") => (),
+            Some(_) => return Err(bad_request("Variable file_type is not one of the following values: 'json'.")),
+        }
+
+        let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        let realtime_start = date_param(&params, "realtime_start")?.unwrap_or_else(|| today.clone());
+        let realtime_end = date_param(&params, "realtime_end")?.unwrap_or(today);
+        if realtime_start > realtime_end {
+            return Err(bad_request("The value for variable realtime_start is later than the value for variable realtime_end."));
+        }
+
+        let max_limit = if endpoint == "series/observations" { MAX_OBSERVATION_LIMIT } else { MAX_LIMIT };
+        let limit = match params.get("limit") {
+            None => max_limit,
+            Some(v) => match v.parse::<usize>() {
+                Ok(l) if l >= 1 && l <= max_limit => l,
+                _ => return Err(bad_request(&format!("The value for variable limit is not between 1 and {}.", max_limit))),
+            },
+        };
+        let offset = match params.get("offset") {
+            None => 0,
+            Some(v) => match v.parse::<usize>() {
+                Ok(o) => o,
+                Err(_) => return Err(bad_request("Variable offset is not 0 or a positive integer.")),
+            },
+        };
+        let descending = match params.get("sort_order").map(String::as_str) {
+            None => None,
+            Some("asc") => Some(false),
+            Some("desc") => Some(true),
+            Some(_) => return Err(bad_request("Variable sort_order is not one of the following values: 'asc', 'desc'.")),
+        };
+        let order_by = params.get("order_by").cloned();
+
+        Ok(Query { params, realtime_start, realtime_end, limit, offset, order_by, descending })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, (u16, String)> {
+        match self.get(name) {
+            Some(v) if !v.is_empty() => Ok(v),
+            _ => Err(bad_request(&format!("Variable {} is not set.", name))),
+        }
+    }
+
+    fn id(&self, name: &str) -> Result<usize, (u16, String)> {
+        match self.required(name)?.parse::<usize>() {
+            Ok(id) => Ok(id),
+            Err(_) => Err(bad_request(&format!("Variable {} is not a non-negative integer.", name))),
+        }
+    }
+
+    /// Returns a list argument separated by semicolons (e.g. tag_names)
+    fn list(&self, name: &str) -> Vec<String> {
+        match self.get(name) {
+            Some(v) => v.split(';').filter(|s| !s.is_empty()).map(String::from).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn envelope(&self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert(String::from("realtime_start"), json!(self.realtime_start));
+        map.insert(String::from("realtime_end"), json!(self.realtime_end));
+        map
+    }
+
+    /// Sorts and pages a list of items into a FRED list response
+    ///
+    /// # Arguments
+    /// * `key` - name of the list in the response (e.g. seriess)
+    /// * `items` - every matching item
+    /// * `default_order` - order_by value used when the request has none
+    /// * `default_desc` - true if the endpoint sorts in descending order by default
+    fn page(&self, key: &str, mut items: Vec<Value>, default_order: &str, default_desc: bool) -> Map<String, Value> {
+        let order_by = match &self.order_by {
+            Some(o) => o.as_str(),
+            None => default_order,
+        };
+        let descending = self.descending.unwrap_or(default_desc);
+
+        if let Some(field) = order_field(order_by) {
+            items.sort_by(|a, b| compare(sort_key(a, field), sort_key(b, field)));
+        }
+        if descending {
+            items.reverse();
+        }
+
+        let count = items.len();
+        let items: Vec<Value> = items.into_iter().skip(self.offset).take(self.limit).collect();
+
+        let mut map = self.envelope();
+        map.insert(String::from("order_by"), json!(order_by));
+        map.insert(String::from("sort_order"), json!(if descending { "desc" } else { "asc" }));
+        map.insert(String::from("count"), json!(count));
+        map.insert(String::from("offset"), json!(self.offset));
+        map.insert(String::from("limit"), json!(self.limit));
+        map.insert(String::from(key), Value::Array(items));
+        map
+    }
+}
+
+fn date_param(params: &HashMap<String, String>, name: &str) -> Result<Option<String>, (u16, String)> {
+    match params.get(name) {
+        None => Ok(None),
+        Some(v) => match NaiveDate::parse_from_str(v, "%Y-%m-%d") {
+            Ok(_) => Ok(Some(v.clone())),
+            Err(_) => Err(bad_request(&format!("Variable {} can not be parsed as a date (YYYY-MM-DD).", name))),
+        },
+    }
+}
+
+/// Maps an order_by value to the field compared, or None to keep the fixture order
+fn order_field(order_by: &str) -> Option<&str> {
+    match order_by {
+        "search_rank" => None,
+        "series_id" | "source_id" | "release_id" | "category_id" => Some("id"),
+        "observation_date" | "release_date" => Some("date"),
+        other => Some(other),
+    }
+}
+
+fn sort_key<'a>(item: &'a Value, field: &str) -> &'a Value {
+    match item {
+        Value::Object(map) => map.get(field).unwrap_or(&Value::Null),
+        other => other,
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> CmpOrdering {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(CmpOrdering::Equal),
+            _ => CmpOrdering::Equal,
+        },
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        _ => CmpOrdering::Equal,
+    }
+}
+
+fn id_of(map: &Map<String, Value>) -> Option<usize> {
+    map.get("id").and_then(Value::as_u64).map(|id| id as usize)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query.split('&')
+        .filter(|p| !p.is_empty())
+        .map(|pair| match pair.find('=') {
+            Some(idx) => (percent_decode(&pair[..idx]), percent_decode(&pair[idx + 1..])),
+            None => (percent_decode(pair), String::new()),
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    },
+                    None => out.push(b'%'),
+                }
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ----------------------------------------------------------------------
+// Routes
+
+fn route(fixtures: &Fixtures, endpoint: &str, q: &Query) -> RouteResult {
+    match endpoint {
+        "series" => series(fixtures, q),
+        "series/categories" => series_categories(fixtures, q),
+        "series/observations" => series_observations(fixtures, q),
+        "series/release" => series_release(fixtures, q),
+        "series/tags" => {
+            let s = find_series(fixtures, q)?;
+            Ok(tag_page(fixtures, q, s.tags.clone()))
+        },
+        "series/updates" => series_updates(fixtures, q),
+        "series/vintagedates" => series_vintagedates(fixtures, q),
+        "series/search" => {
+            let matches = search(fixtures, q, q.required("search_text")?);
+            Ok(Value::Object(q.page("seriess", series_values(&filter_tags(q, matches)), "search_rank", false)))
+        },
+        "series/search/tags" => {
+            let matches = search(fixtures, q, q.required("series_search_text")?);
+            Ok(tag_page(fixtures, q, tags_of(&matches)))
+        },
+        "series/search/related_tags" => {
+            let matches = search(fixtures, q, q.required("series_search_text")?);
+            related_tags(fixtures, q, matches)
+        },
+        "category" => {
+            let id = match q.get("category_id") {
+                Some(_) => q.id("category_id")?,
+                None => 0,
+            };
+            let category = find_category(fixtures, id)?;
+            Ok(json!({ "categories": [category] }))
+        },
+        "category/children" => {
+            let id = q.id("category_id")?;
+            find_category(fixtures, id)?;
+            let children: Vec<Value> = fixtures.categories.iter()
+                .filter(|c| c["parent_id"].as_u64() == Some(id as u64) && c["id"].as_u64() != Some(id as u64))
+                .cloned()
+                .collect();
+            Ok(json!({ "categories": children }))
+        },
+        "category/related" => {
+            find_category(fixtures, q.id("category_id")?)?;
+            Ok(json!({ "categories": [] }))
+        },
+        "category/series" => {
+            let matches = category_series(fixtures, q)?;
+            Ok(Value::Object(q.page("seriess", series_values(&filter_tags(q, matches)), "series_id", false)))
+        },
+        "category/tags" => {
+            let matches = category_series(fixtures, q)?;
+            Ok(tag_page(fixtures, q, tags_of(&matches)))
+        },
+        "category/related_tags" => {
+            let matches = category_series(fixtures, q)?;
+            related_tags(fixtures, q, matches)
+        },
+        "releases" => {
+            let releases = fixtures.releases.iter().map(|r| Value::Object(r.release.clone())).collect();
+            Ok(Value::Object(q.page("releases", releases, "release_id", false)))
+        },
+        "releases/dates" => release_dates(fixtures, q, None),
+        "release" => {
+            let r = find_release(fixtures, q)?;
+            let mut map = q.envelope();
+            map.insert(String::from("releases"), json!([Value::Object(r.release.clone())]));
+            Ok(Value::Object(map))
+        },
+        "release/dates" => {
+            let r = find_release(fixtures, q)?;
+            release_dates(fixtures, q, id_of(&r.release))
+        },
+        "release/series" => {
+            let matches = release_series(fixtures, q)?;
+            Ok(Value::Object(q.page("seriess", series_values(&filter_tags(q, matches)), "series_id", false)))
+        },
+        "release/sources" => {
+            let r = find_release(fixtures, q)?;
+            let sources: Vec<Value> = r.source_ids.iter()
+                .filter_map(|id| fixtures.source_by_id(*id))
+                .cloned()
+                .collect();
+            let mut map = q.envelope();
+            map.insert(String::from("sources"), Value::Array(sources));
+            Ok(Value::Object(map))
+        },
+        "release/tags" => {
+            let matches = release_series(fixtures, q)?;
+            Ok(tag_page(fixtures, q, tags_of(&matches)))
+        },
+        "release/related_tags" => {
+            let matches = release_series(fixtures, q)?;
+            related_tags(fixtures, q, matches)
+        },
+        "release/tables" => {
+            let r = find_release(fixtures, q)?;
+            Ok(json!({
+                "name": r.release.get("name"),
+                "element_id": Value::Null,
+                "release_id": id_of(&r.release).unwrap_or(0).to_string(),
+                "elements": {}
+            }))
+        },
+        "tags" => {
+            let mut names: Vec<String> = fixtures.tags.iter()
+                .filter_map(|t| t["name"].as_str().map(String::from))
+                .collect();
+            for name in tags_of(&fixtures.series.iter().collect::<Vec<_>>()) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            let requested = q.list("tag_names");
+            if !requested.is_empty() {
+                names.retain(|n| requested.contains(n));
+            }
+            Ok(tag_page(fixtures, q, names))
+        },
+        "tags/series" => {
+            q.required("tag_names")?;
+            let all: Vec<&SeriesFixture> = fixtures.series.iter().collect();
+            Ok(Value::Object(q.page("seriess", series_values(&filter_tags(q, all)), "series_id", false)))
+        },
+        "related_tags" => {
+            let all: Vec<&SeriesFixture> = fixtures.series.iter().collect();
+            related_tags(fixtures, q, all)
+        },
+        "sources" => Ok(Value::Object(q.page("sources", fixtures.sources.clone(), "source_id", false))),
+        "source" => {
+            let source = find_source(fixtures, q)?;
+            let mut map = q.envelope();
+            map.insert(String::from("sources"), json!([source]));
+            Ok(Value::Object(map))
+        },
+        "source/releases" => {
+            let source = find_source(fixtures, q)?;
+            let id = source["id"].as_u64().unwrap_or(0) as usize;
+            let releases = fixtures.releases.iter()
+                .filter(|r| r.source_ids.contains(&id))
+                .map(|r| Value::Object(r.release.clone()))
+                .collect();
+            Ok(Value::Object(q.page("releases", releases, "release_id", false)))
+        },
+        _ => Err((404, String::from("Not Found."))),
+    }
+}
+
+fn find_series<'a>(fixtures: &'a Fixtures, q: &Query) -> Result<&'a SeriesFixture, (u16, String)> {
+    match fixtures.series_by_id(q.required("series_id")?) {
+        Some(s) => Ok(s),
+        None => Err(bad_request("The series does not exist.")),
+    }
+}
+
+fn find_category(fixtures: &Fixtures, id: usize) -> Result<&Value, (u16, String)> {
+    match fixtures.category_by_id(id) {
+        Some(c) => Ok(c),
+        None => Err(bad_request("The category does not exist.")),
+    }
+}
+
+fn find_release<'a>(fixtures: &'a Fixtures, q: &Query) -> Result<&'a ReleaseFixture, (u16, String)> {
+    match fixtures.release_by_id(q.id("release_id")?) {
+        Some(r) => Ok(r),
+        None => Err(bad_request("The release does not exist.")),
+    }
+}
+
+fn find_source<'a>(fixtures: &'a Fixtures, q: &Query) -> Result<&'a Value, (u16, String)> {
+    match fixtures.source_by_id(q.id("source_id")?) {
+        Some(s) => Ok(s),
+        None => Err(bad_request("The source does not exist.")),
+    }
+}
+
+fn series_values(series: &[&SeriesFixture]) -> Vec<Value> {
+    series.iter().map(|s| Value::Object(s.series.clone())).collect()
+}
+
+/// Returns the distinct tag names of a set of series
+fn tags_of(series: &[&SeriesFixture]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for s in series {
+        for tag in &s.tags {
+            if !names.contains(tag) {
+                names.push(tag.clone());
+            }
+        }
+    }
+    names
+}
+
+/// Keeps the series that carry every tag in tag_names and none in exclude_tag_names
+fn filter_tags<'a>(q: &Query, series: Vec<&'a SeriesFixture>) -> Vec<&'a SeriesFixture> {
+    let include = q.list("tag_names");
+    let exclude = q.list("exclude_tag_names");
+    series.into_iter()
+        .filter(|s| include.iter().all(|t| s.tags.contains(t)))
+        .filter(|s| !exclude.iter().any(|t| s.tags.contains(t)))
+        .collect()
+}
+
+fn tag_page(fixtures: &Fixtures, q: &Query, names: Vec<String>) -> Value {
+    let group = q.get("tag_group_id");
+    let tags = names.iter()
+        .map(|n| fixtures.tag(n))
+        .filter(|t| group.is_none() || t["group_id"].as_str() == group)
+        .collect();
+    Value::Object(q.page("tags", tags, "series_count", true))
+}
+
+/// Returns the tags of the series matching tag_names, other than the requested tags
+fn related_tags(fixtures: &Fixtures, q: &Query, series: Vec<&SeriesFixture>) -> RouteResult {
+    q.required("tag_names")?;
+    let requested = q.list("tag_names");
+    let names = tags_of(&filter_tags(q, series))
+        .into_iter()
+        .filter(|t| !requested.contains(t))
+        .collect();
+    Ok(tag_page(fixtures, q, names))
+}
+
+fn category_series<'a>(fixtures: &'a Fixtures, q: &Query) -> Result<Vec<&'a SeriesFixture>, (u16, String)> {
+    let id = q.id("category_id")?;
+    find_category(fixtures, id)?;
+    Ok(fixtures.series.iter().filter(|s| s.category_ids.contains(&id)).collect())
+}
+
+fn release_series<'a>(fixtures: &'a Fixtures, q: &Query) -> Result<Vec<&'a SeriesFixture>, (u16, String)> {
+    let r = find_release(fixtures, q)?;
+    let id = id_of(&r.release);
+    Ok(fixtures.series.iter().filter(|s| s.release_id.is_some() && s.release_id == id).collect())
+}
+
+/// Finds series whose id or title contains every word of the search text
+fn search<'a>(fixtures: &'a Fixtures, q: &Query, text: &str) -> Vec<&'a SeriesFixture> {
+    let by_id = q.get("search_type") == Some("series_id");
+    let words: Vec<String> = text.split_whitespace().map(|w| w.trim_matches('*').to_lowercase()).collect();
+
+    fixtures.series.iter()
+        .filter(|s| {
+            let id = s.series.get("id").and_then(Value::as_str).unwrap_or("").to_lowercase();
+            let title = s.series.get("title").and_then(Value::as_str).unwrap_or("").to_lowercase();
+            words.iter().all(|w| id.contains(w.as_str()) || (!by_id && title.contains(w.as_str())))
+        })
+        .collect()
+}
+
+fn series(fixtures: &Fixtures, q: &Query) -> RouteResult {
+    let s = find_series(fixtures, q)?;
+    let mut map = q.envelope();
+    map.insert(String::from("seriess"), json!([Value::Object(s.series.clone())]));
+    Ok(Value::Object(map))
+}
+
+fn series_categories(fixtures: &Fixtures, q: &Query) -> RouteResult {
+    let s = find_series(fixtures, q)?;
+    let categories: Vec<Value> = s.category_ids.iter()
+        .filter_map(|id| fixtures.category_by_id(*id))
+        .cloned()
+        .collect();
+    Ok(json!({ "categories": categories }))
+}
+
+fn series_release(fixtures: &Fixtures, q: &Query) -> RouteResult {
+    let s = find_series(fixtures, q)?;
+    let releases: Vec<Value> = s.release_id
+        .and_then(|id| fixtures.release_by_id(id))
+        .map(|r| Value::Object(r.release.clone()))
+        .into_iter()
+        .collect();
+    let mut map = q.envelope();
+    map.insert(String::from("releases"), Value::Array(releases));
+    Ok(Value::Object(map))
+}
+
+fn series_updates(fixtures: &Fixtures, q: &Query) -> RouteResult {
+    let all: Vec<&SeriesFixture> = fixtures.series.iter().collect();
+    let mut map = q.page("seriess", series_values(&all), "last_updated", true);
+    map.insert(String::from("filter_variable"), json!("geography"));
+    map.insert(String::from("filter_value"), json!(q.get("filter_value").unwrap_or("all")));
+    Ok(Value::Object(map))
+}
+
+fn series_vintagedates(fixtures: &Fixtures, q: &Query) -> RouteResult {
+    let s = find_series(fixtures, q)?;
+    // vintagedates spans all of history unless a period is requested
+    let start = q.get("realtime_start").unwrap_or(EARLIEST_REALTIME);
+    let end = q.get("realtime_end").unwrap_or(LATEST_REALTIME);
+
+    let mut dates: Vec<&str> = s.observations.iter()
+        .filter_map(|o| o["realtime_start"].as_str())
+        .filter(|d| *d >= start && *d <= end)
+        .collect();
+    dates.sort_unstable();
+    dates.dedup();
+
+    let mut map = q.page("vintage_dates", dates.into_iter().map(|d| json!(d)).collect(), "vintage_date", false);
+    map.insert(String::from("realtime_start"), json!(start));
+    map.insert(String::from("realtime_end"), json!(end));
+    Ok(Value::Object(map))
+}
+
+fn series_observations(fixtures: &Fixtures, q: &Query) -> RouteResult {
+    let s = find_series(fixtures, q)?;
+    match q.get("output_type") {
+        None | Some("1") => (),
+        Some(_) => return Err(bad_request("The mock server only supports output_type 1.")),
+    }
+    let observation_start = date_param(&q.params, "observation_start")?.unwrap_or_else(|| String::from(EARLIEST_REALTIME));
+    let observation_end = date_param(&q.params, "observation_end")?.unwrap_or_else(|| String::from(LATEST_REALTIME));
+
+    // each requested vintage date is a single-day realtime period
+    let periods: Vec<(String, String)> = match q.get("vintage_dates") {
+        Some(dates) => dates.split(',').map(|d| (String::from(d), String::from(d))).collect(),
+        None => vec![(q.realtime_start.clone(), q.realtime_end.clone())],
+    };
+
+    let mut observations = Vec::new();
+    for (start, end) in &periods {
+        for o in &s.observations {
+            let rs = o["realtime_start"].as_str().unwrap_or(EARLIEST_REALTIME);
+            let re = o["realtime_end"].as_str().unwrap_or(LATEST_REALTIME);
+            let date = o["date"].as_str().unwrap_or("");
+            if rs > end.as_str() || re < start.as_str() || date < observation_start.as_str() || date > observation_end.as_str() {
+                continue;
+            }
+
+            let mut o = o.clone();
+            o["realtime_start"] = json!(rs.max(start.as_str()));
+            o["realtime_end"] = json!(re.min(end.as_str()));
+            if !observations.contains(&o) {
+                observations.push(o);
+            }
+        }
+    }
+
+    let mut map = q.page("observations", observations, "observation_date", false);
+    map.insert(String::from("observation_start"), json!(observation_start));
+    map.insert(String::from("observation_end"), json!(observation_end));
+    map.insert(String::from("units"), json!(q.get("units").unwrap_or("lin")));
+    map.insert(String::from("output_type"), json!(1));
+    map.insert(String::from("file_type"), json!("json"));
+    Ok(Value::Object(map))
+}
+
+/// Lists release dates of one release or of all releases
+fn release_dates(fixtures: &Fixtures, q: &Query, release_id: Option<usize>) -> RouteResult {
+    // release dates default to every date rather than today
+    let start = q.get("realtime_start").unwrap_or(EARLIEST_REALTIME);
+    let end = q.get("realtime_end").unwrap_or(LATEST_REALTIME);
+
+    let mut dates = Vec::new();
+    for r in &fixtures.releases {
+        let id = id_of(&r.release);
+        if release_id.is_some() && id != release_id {
+            continue;
+        }
+        for date in r.dates.iter().filter(|d| d.as_str() >= start && d.as_str() <= end) {
+            dates.push(json!({
+                "release_id": id.unwrap_or(0),
+                "release_name": r.release.get("name"),
+                "date": date,
+            }));
+        }
+    }
+
+    let mut map = q.page("release_dates", dates, "release_date", release_id.is_none());
+    map.insert(String::from("realtime_start"), json!(start));
+    map.insert(String::from("realtime_end"), json!(end));
+    Ok(Value::Object(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::FredClient;
+    use crate::*;
+
+    fn client(server: &MockServer) -> FredClient {
+        FredClient::builder()
+            .url_base(&server.url_base())
+            .api_key("abcdefghijklmnopqrstuvwxyz123456")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn mock_server_workflow() {
+        let server = MockServer::start(Fixtures::sample()).unwrap();
+        let c = client(&server);
+
+        let resp = c.category_series(106, None).unwrap();
+        assert_eq!(resp.seriess.len(), 2);
+        assert_eq!(resp.seriess[0].id, "GDPC1");
+
+        let resp = c.series_release("GNPCA", None).unwrap();
+        assert_eq!(resp.releases[0].id, 53);
+
+        let resp = c.release_sources(53, None).unwrap();
+        assert_eq!(resp.sources[0].id, 18);
+
+        let mut builder = tags::series::Builder::new();
+        builder.tag_name("gnp");
+        let resp = c.tags_series(builder).unwrap();
+        assert_eq!(resp.seriess[0].id, "GNPCA");
+
+        let resp = c.series_search("gross national", None).unwrap();
+        assert_eq!(resp.seriess.len(), 1);
+
+        let resp = c.series_tags("GNPCA", None).unwrap();
+        assert_eq!(resp.tags[0].series_count, 2);
+    }
+
+    #[test]
+    fn mock_server_observations() {
+        let server = MockServer::start(Fixtures::sample()).unwrap();
+        let c = client(&server);
+
+        let mut builder = series::observation::Builder::new();
+        builder
+            .realtime_start("2019-04-01")
+            .realtime_end("2019-04-01")
+            .sort_order(series::observation::SortOrder::Descending)
+            .limit(1);
+        let resp = c.series_observation("GNPCA", Some(builder)).unwrap();
+        assert_eq!(resp.count, 1);
+        assert_eq!(resp.observations[0].value, "18566.7");
+        assert_eq!(resp.observations[0].realtime_start, "2019-04-01");
+
+        let resp = c.series_observation("GNPCA", None).unwrap();
+        assert_eq!(resp.count, 3);
+
+        let resp = c.series_vintagedates("GNPCA", None).unwrap();
+        assert_eq!(resp.vintage_dates, vec!["2019-03-28", "2019-07-26"]);
+    }
+
+    #[test]
+    fn mock_server_errors() {
+        let server = MockServer::start(Fixtures::sample()).unwrap();
+        let c = client(&server);
+
+        match c.series("NOPE", None) {
+            Err(Error::Api { code, message }) => {
+                assert_eq!(code, 400);
+                assert_eq!(message, "Bad Request.  The series does not exist.");
+            },
+            _ => assert_eq!(2, 1),
+        }
+
+        let mut builder = series::observation::Builder::new();
+        builder.limit(100_001);
+        assert!(c.series_observation("GNPCA", Some(builder)).unwrap_err().is_bad_parameter());
+
+        let unkeyed = FredClient::builder()
+            .url_base(&server.url_base())
+            .api_key("not a key")
+            .build()
+            .unwrap();
+        assert!(unkeyed.category(0).unwrap_err().is_bad_parameter());
+    }
+
+    #[test]
+    fn mock_server_rate_limit() {
+        let server = MockServer::start(Fixtures::sample()).unwrap();
+        let c = client(&server);
+
+        server.throttle_next(1);
+        assert!(c.category(0).unwrap_err().is_rate_limited());
+        assert!(c.category(0).is_ok());
+
+        server.requests_per_minute(1);
+        assert!(c.category(0).is_ok());
+        assert!(c.category(0).unwrap_err().is_rate_limited());
+
+        assert_eq!(server.requests().len(), 4);
+        assert!(server.requests().iter().all(|r| !r.contains("api_key")));
+    }
+}
@@ -0,0 +1,398 @@
+//! Iterators over every result of a list endpoint
+//!
+//! FRED returns list endpoints (e.g. fred/release/series) one page at a time, limited to 1000 results per request (100000 for observations and 10000 for vintage dates and the release dates of a single release).  The `*_iter` methods of [FredClient](../client/struct.FredClient.html) return a [Pages](struct.Pages.html) iterator that requests the following pages as the previous ones are consumed and stops when the `count` reported by FRED is reached.
+//!
+//! The `limit` and `offset` arguments of the builder set the page size and the first result.  Use `max_items()` to stop after a number of results without requesting more pages than needed.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::release::series::Builder;
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let mut builder = Builder::new();
+//! builder.limit(200);
+//!
+//! // fetches 200 series per request until 500 series have been returned
+//! for series in c.release_series_iter(53, Some(builder)).max_items(500) {
+//!     match series {
+//!         Ok(s) => println!("{}: {}", s.id, s.title),
+//!         Err(msg) => {
+//!             println!("{}", msg);
+//!             break
+//!         },
+//!     }
+//! }
+//! ```
+
+use std::fmt::{self, Debug, Formatter};
+
+use crate::error::Error;
+use crate::request::Request;
+use crate::series::{observation, vintage, vintagedates};
+
+/// Maximum page size of most list endpoints
+pub const PAGE_LIMIT: usize = 1000;
+/// Maximum page size of fred/series/observations
+pub const OBSERVATION_PAGE_LIMIT: usize = 100_000;
+/// Maximum page size of fred/series/vintagedates and fred/release/dates
+pub const VINTAGE_DATE_PAGE_LIMIT: usize = 10_000;
+
+/// Returns the largest page FRED allows for an endpoint
+pub(crate) fn page_limit(endpoint: &str) -> usize {
+    match endpoint {
+        "series/observations" => OBSERVATION_PAGE_LIMIT,
+        "series/vintagedates" | "release/dates" => VINTAGE_DATE_PAGE_LIMIT,
+        _ => PAGE_LIMIT,
+    }
+}
+
+/// Returns the first result and the page size set by a request's offset and limit
+fn page_bounds(request: &Request) -> (usize, usize) {
+    let max = page_limit(request.endpoint());
+    let offset = request.option_value("offset")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let page_size = request.option_value("limit")
+        .and_then(|v| v.parse().ok())
+        .map_or(max, |l: usize| l.clamp(1, max));
+    (offset, page_size)
+}
+
+/// The results of one request and the total number of results reported by FRED
+pub(crate) struct Page<T> {
+    pub(crate) items: Vec<T>,
+    pub(crate) count: Option<usize>,
+}
+
+impl<T> Page<T> {
+    pub(crate) fn new(items: Vec<T>, count: Option<usize>) -> Page<T> {
+        Page { items, count }
+    }
+}
+
+type Fetch<'a, T> = Box<dyn FnMut(Request) -> Result<Page<T>, Error> + 'a>;
+
+/// Lazily yields the results of a list endpoint across pages
+///
+/// Each item is a `Result`; after an error the iterator ends.
+pub struct Pages<'a, T> {
+    request: Option<Request>,
+    error: Option<Error>,
+    fetch: Fetch<'a, T>,
+    buffer: std::vec::IntoIter<T>,
+    offset: usize,
+    page_size: usize,
+    remaining: Option<usize>,
+    done: bool,
+}
+
+impl<'a, T> Pages<'a, T> {
+    /// Creates an iterator starting at the builder's offset with the builder's limit as the page size
+    pub(crate) fn new<F>(request: Result<Request, Error>, fetch: F) -> Pages<'a, T>
+    where F: FnMut(Request) -> Result<Page<T>, Error> + 'a
+    {
+        let (request, error) = match request {
+            Ok(r) => (Some(r), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        let (offset, page_size) = match &request {
+            Some(r) => page_bounds(r),
+            None => (0, PAGE_LIMIT),
+        };
+
+        Pages {
+            request,
+            error,
+            fetch: Box::new(fetch),
+            buffer: Vec::new().into_iter(),
+            offset,
+            page_size,
+            remaining: None,
+            done: false,
+        }
+    }
+
+    /// Stops after a number of results
+    ///
+    /// The last request asks only for the results still needed.
+    ///
+    /// # Arguments
+    /// * `max` - maximum number of results to yield
+    pub fn max_items(mut self, max: usize) -> Pages<'a, T> {
+        self.remaining = Some(max);
+        self
+    }
+
+    /// Returns the number of results requested per page
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+}
+
+impl<'a, T> Iterator for Pages<'a, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        if let Some(e) = self.error.take() {
+            self.done = true;
+            return Some(Err(e));
+        }
+        if self.remaining == Some(0) {
+            return None;
+        }
+
+        if let Some(item) = self.buffer.next() {
+            self.remaining = self.remaining.map(|r| r - 1);
+            return Some(Ok(item));
+        }
+        if self.done {
+            return None;
+        }
+
+        let request = self.request.as_ref()?;
+        let limit = match self.remaining {
+            Some(r) => r.min(self.page_size),
+            None => self.page_size,
+        };
+        let page = match (self.fetch)(request.with_page(self.offset, limit)) {
+            Ok(p) => p,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            },
+        };
+
+        let received = page.items.len();
+        self.offset += received;
+        self.done = received < limit || page.count.is_some_and(|count| self.offset >= count);
+        self.buffer = page.items.into_iter();
+
+        match self.buffer.next() {
+            Some(item) => {
+                self.remaining = self.remaining.map(|r| r - 1);
+                Some(Ok(item))
+            },
+            None => {
+                self.done = true;
+                None
+            },
+        }
+    }
+}
+
+impl<'a, T> Debug for Pages<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pages")
+            .field("offset", &self.offset)
+            .field("page_size", &self.page_size)
+            .field("remaining", &self.remaining)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// A response whose results can be collected across pages
+pub(crate) trait Paged {
+    /// Returns the total number of results reported by FRED
+    fn count(&self) -> usize;
+    /// Returns the number of results in the response
+    fn received(&self) -> usize;
+    /// Appends the results of the following page
+    fn append(&mut self, next: Self);
+}
+
+impl Paged for observation::Response {
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn received(&self) -> usize {
+        self.observations.len()
+    }
+
+    fn append(&mut self, next: Self) {
+        self.observations.extend(next.observations);
+    }
+}
+
+impl Paged for vintage::Response {
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn received(&self) -> usize {
+        self.observations.len()
+    }
+
+    fn append(&mut self, next: Self) {
+        self.observations.extend(next.observations);
+    }
+}
+
+impl Paged for vintagedates::Response {
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn received(&self) -> usize {
+        self.vintage_dates.len()
+    }
+
+    fn append(&mut self, next: Self) {
+        self.vintage_dates.extend(next.vintage_dates);
+    }
+}
+
+/// Requests every page of a response and combines them into one
+///
+/// The clients call `next_request()` and `push()` until no request is left, then `finish()`.
+pub(crate) struct Collect<R> {
+    request: Request,
+    start: usize,
+    offset: usize,
+    page_size: usize,
+    response: Option<R>,
+    done: bool,
+}
+
+impl<R: Paged + Default> Collect<R> {
+    /// Starts at the request's offset with the request's limit as the page size
+    pub(crate) fn new(request: Request) -> Collect<R> {
+        let (offset, page_size) = page_bounds(&request);
+        Collect {
+            request,
+            start: offset,
+            offset,
+            page_size,
+            response: None,
+            done: false,
+        }
+    }
+
+    /// Returns the request for the next page, or None once every result was received
+    pub(crate) fn next_request(&self) -> Option<Request> {
+        if self.done {
+            return None;
+        }
+        Some(self.request.with_page(self.offset, self.page_size))
+    }
+
+    /// Adds the response to the last request
+    pub(crate) fn push(&mut self, page: R) {
+        let received = page.received();
+        self.offset += received;
+        self.done = received == 0 || self.offset >= page.count();
+        match &mut self.response {
+            Some(r) => r.append(page),
+            None => self.response = Some(page),
+        }
+    }
+
+    /// Returns the combined response
+    ///
+    /// Fails with `Error::Truncated` if FRED returned fewer results than the count it reported.
+    pub(crate) fn finish(self) -> Result<R, Error> {
+        let response = self.response.unwrap_or_default();
+        let expected = response.count().saturating_sub(self.start);
+        if response.received() < expected {
+            return Err(Error::Truncated {
+                endpoint: String::from(self.request.endpoint()),
+                count: expected,
+                received: response.received(),
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{page_body, test_client, MemoryTransport};
+    use crate::*;
+
+    fn release_page(offset: usize, ids: &[usize], count: usize) -> String {
+        let releases: Vec<String> = ids.iter()
+            .map(|id| format!(
+                r#"{{"id":{},"realtime_start":"2020-01-01","realtime_end":"2020-01-01","name":"Release {}","press_release":false}}"#,
+                id, id
+            ))
+            .collect();
+        page_body("releases", ("2020-01-01", "2020-01-01"), count, offset, &releases)
+    }
+
+    #[test]
+    fn pages_follow_count() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("releases?*&limit=2&offset=0", &release_page(0, &[1, 2], 5))
+            .route("releases?*&limit=2&offset=2", &release_page(2, &[3, 4], 5))
+            .route("releases?*&limit=2&offset=4", &release_page(4, &[5], 5));
+
+        let mut builder = releases::Builder::new();
+        builder.limit(2);
+        let ids: Vec<usize> = test_client(&transport)
+            .releases_iter(Some(builder))
+            .map(|r| r.unwrap().id)
+            .collect();
+
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn pages_max_items() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("releases?*&limit=2&offset=1", &release_page(1, &[2, 3], 5))
+            .route("releases?*&limit=1&offset=3", &release_page(3, &[4], 5));
+
+        let mut builder = releases::Builder::new();
+        builder.limit(2).offset(1);
+        let ids: Vec<usize> = test_client(&transport)
+            .releases_iter(Some(builder))
+            .max_items(3)
+            .map(|r| r.unwrap().id)
+            .collect();
+
+        assert_eq!(ids, vec![2, 3, 4]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn pages_stop_after_error() {
+        let transport = MemoryTransport::new();
+        let c = test_client(&transport);
+
+        let mut pages = c.releases_iter(None);
+        assert_eq!(pages.page_size(), PAGE_LIMIT);
+        assert!(pages.next().unwrap().is_err());
+        assert!(pages.next().is_none());
+
+        let mut pages = c.series_observation_iter("GNPCA", None);
+        assert_eq!(pages.page_size(), OBSERVATION_PAGE_LIMIT);
+        assert!(pages.next().unwrap().unwrap_err().is_transport());
+
+        // builder errors are returned by the first call to next()
+        let mut pages = c.tags_series_iter(tags::series::Builder::new());
+        assert!(matches!(pages.next(), Some(Err(Error::Builder(_)))));
+        assert!(pages.next().is_none());
+    }
+
+    #[test]
+    fn pages_release_dates_limit() {
+        let mut builder = release::dates::Builder::new();
+        builder.limit(5000);
+        assert_eq!(page_bounds(&request::release_dates(50, Some(builder))), (0, 5000));
+        assert_eq!(page_bounds(&request::release_dates(50, None)), (0, VINTAGE_DATE_PAGE_LIMIT));
+        assert_eq!(page_bounds(&request::releases_dates(None)), (0, PAGE_LIMIT));
+    }
+}
@@ -0,0 +1,455 @@
+//! Several series aligned on a common date index
+//!
+//! A [Panel](struct.Panel.html) is a wide table with one row per date and one column per series, built from [TimeSeries](../timeseries/struct.TimeSeries.html) values with a [Builder](struct.Builder.html).  The builder controls:
+//! * which dates appear in the result ([Join](enum.Join.html))
+//! * how missing cells are filled after joining ([Fill](enum.Fill.html))
+//! * an optional frequency every series is aggregated to before joining
+//!
+//! Columns added with `series_with()` carry the title, units, frequency and seasonal adjustment of a `series::Series`.
+//!
+//! ```
+//! use fred_rs::panel::{Builder, Join, Fill};
+//! use fred_rs::timeseries::TimeSeries;
+//! use chrono::NaiveDate;
+//!
+//! let date = |m| NaiveDate::from_ymd_opt(2020, m, 1).unwrap();
+//! let unrate = TimeSeries::new(vec![(date(1), Some(3.6)), (date(2), Some(3.5)), (date(3), Some(4.4))]);
+//! let fedfunds = TimeSeries::new(vec![(date(1), Some(1.55)), (date(3), Some(0.65))]);
+//!
+//! let mut builder = Builder::new();
+//! builder.series("UNRATE", unrate)
+//!     .series("FEDFUNDS", fedfunds)
+//!     .join(Join::Outer)
+//!     .fill(Fill::Forward);
+//! let panel = builder.build();
+//!
+//! assert_eq!(panel.dates(), &[date(1), date(2), date(3)]);
+//! assert_eq!(panel.column("FEDFUNDS").unwrap(), &[Some(1.55), Some(1.55), Some(0.65)]);
+//! ```
+
+use chrono::NaiveDate;
+
+use crate::series::Series;
+use crate::series::observation::{AggregationMethod, Frequency};
+use crate::timeseries::TimeSeries;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Which dates appear in a panel
+pub enum Join {
+    /// Dates present in every series
+    Inner,
+    /// Dates present in any series
+    Outer,
+    /// Dates of the first series
+    Left,
+    /// Dates of the first series; each other series takes its last value present on or before the date
+    Asof,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// How missing cells are filled after joining
+pub enum Fill {
+    /// Leave missing cells empty
+    None,
+    /// Carry the last value present forward
+    Forward,
+    /// Interpolate linearly in time between the surrounding values present
+    ///
+    /// Cells before the first or after the last value of a column stay missing.
+    Interpolate,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Name and descriptive metadata of a panel column
+pub struct Column {
+    /// Column name, usually the series ID
+    pub name: String,
+    /// The series title
+    pub title: Option<String>,
+    /// The data series units (e.g. Percent)
+    pub units: Option<String>,
+    /// The series natural frequency (e.g. Monthly)
+    pub frequency: Option<String>,
+    /// Seasonal Adjustment Information
+    pub seasonal_adjustment: Option<String>,
+}
+
+impl Column {
+    /// Creates a column without metadata
+    ///
+    /// # Arguments
+    /// * `name` - the column name
+    pub fn new(name: &str) -> Column {
+        Column {
+            name: String::from(name),
+            ..Column::default()
+        }
+    }
+
+    /// Creates a column named after a series and carrying its metadata
+    ///
+    /// # Arguments
+    /// * `series` - series information returned by fred/series or a series search
+    pub fn from_series(series: &Series) -> Column {
+        Column {
+            name: series.id.clone(),
+            title: Some(series.title.clone()),
+            units: Some(series.units.clone()),
+            frequency: Some(series.frequency.clone()),
+            seasonal_adjustment: Some(series.seasonal_adjustment.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Arguments used to align series into a panel
+pub struct Builder {
+    series: Vec<(Column, TimeSeries)>,
+    join: Join,
+    fill: Fill,
+    frequency: Option<(Frequency, AggregationMethod)>,
+}
+
+impl Builder {
+    /// Creates a builder with an outer join and no fill
+    pub fn new() -> Builder {
+        Builder {
+            series: Vec::new(),
+            join: Join::Outer,
+            fill: Fill::None,
+            frequency: None,
+        }
+    }
+
+    /// Adds a column without metadata
+    ///
+    /// # Arguments
+    /// * `name` - the column name
+    /// * `data` - the column values
+    pub fn series(&mut self, name: &str, data: TimeSeries) -> &mut Builder {
+        self.series.push((Column::new(name), data));
+        self
+    }
+
+    /// Adds a column named after a series and carrying its metadata
+    ///
+    /// # Arguments
+    /// * `series` - series information returned by fred/series or a series search
+    /// * `data` - the column values
+    pub fn series_with(&mut self, series: &Series, data: TimeSeries) -> &mut Builder {
+        self.series.push((Column::from_series(series), data));
+        self
+    }
+
+    /// Adds a column with the given metadata
+    ///
+    /// # Arguments
+    /// * `column` - the column name and metadata
+    /// * `data` - the column values
+    pub fn column(&mut self, column: Column, data: TimeSeries) -> &mut Builder {
+        self.series.push((column, data));
+        self
+    }
+
+    /// Sets which dates appear in the panel
+    ///
+    /// # Arguments
+    /// * `join` - the join type (default Outer)
+    pub fn join(&mut self, join: Join) -> &mut Builder {
+        self.join = join;
+        self
+    }
+
+    /// Sets how missing cells are filled after joining
+    ///
+    /// # Arguments
+    /// * `fill` - the fill policy (default None)
+    pub fn fill(&mut self, fill: Fill) -> &mut Builder {
+        self.fill = fill;
+        self
+    }
+
+    /// Aggregates every series to a frequency before joining
+    ///
+    /// See `TimeSeries::resample()`.
+    ///
+    /// # Arguments
+    /// * `frequency` - the frequency of the panel
+    /// * `method` - how the values in each period are combined
+    pub fn frequency(&mut self, frequency: Frequency, method: AggregationMethod) -> &mut Builder {
+        self.frequency = Some((frequency, method));
+        self
+    }
+
+    /// Aligns the series into a panel
+    pub fn build(&self) -> Panel {
+        let series: Vec<(Column, TimeSeries)> = self.series.iter()
+            .map(|(column, data)| match self.frequency {
+                Some((frequency, method)) => (column.clone(), data.resample(frequency, method)),
+                None => (column.clone(), data.clone()),
+            })
+            .collect();
+
+        let dates = join_dates(&series, self.join);
+        let mut columns = Vec::with_capacity(series.len());
+        let mut values = Vec::with_capacity(series.len());
+        for (idx, (column, data)) in series.into_iter().enumerate() {
+            let mut cells: Vec<Option<f64>> = match self.join {
+                Join::Asof if idx > 0 => dates.iter().map(|d| last_on_or_before(&data, *d)).collect(),
+                _ => dates.iter().map(|d| data.get(*d).flatten()).collect(),
+            };
+            match self.fill {
+                Fill::None => (),
+                Fill::Forward => forward_fill(&mut cells),
+                Fill::Interpolate => interpolate(&dates, &mut cells),
+            }
+            columns.push(column);
+            values.push(cells);
+        }
+
+        Panel { dates, columns, values }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A table of series values with one row per date
+pub struct Panel {
+    dates: Vec<NaiveDate>,
+    columns: Vec<Column>,
+    /// One vector of cells per column, each the length of `dates`
+    values: Vec<Vec<Option<f64>>>,
+}
+
+impl Panel {
+    /// Returns the row dates in ascending order
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    /// Returns the columns in the order they were added
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Returns the number of rows
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    /// Returns true if the panel has no rows
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// Returns the values of a column by name
+    ///
+    /// # Arguments
+    /// * `name` - the column name
+    pub fn column(&self, name: &str) -> Option<&[Option<f64>]> {
+        self.columns.iter()
+            .position(|c| c.name == name)
+            .map(|idx| self.values[idx].as_slice())
+    }
+
+    /// Returns the values of a column by position
+    ///
+    /// # Arguments
+    /// * `idx` - the column position
+    pub fn column_at(&self, idx: usize) -> Option<&[Option<f64>]> {
+        self.values.get(idx).map(|v| v.as_slice())
+    }
+
+    /// Returns the values of a row, one per column
+    ///
+    /// # Arguments
+    /// * `idx` - the row position
+    pub fn row(&self, idx: usize) -> Option<Vec<Option<f64>>> {
+        if idx >= self.dates.len() {
+            return None;
+        }
+        Some(self.values.iter().map(|v| v[idx]).collect())
+    }
+
+    /// Returns the value of a column on a date
+    ///
+    /// Returns None if the date or column is not in the panel and Some(None) if the cell is missing.
+    ///
+    /// # Arguments
+    /// * `date` - the row date
+    /// * `name` - the column name
+    pub fn get(&self, date: NaiveDate, name: &str) -> Option<Option<f64>> {
+        let row = self.dates.binary_search(&date).ok()?;
+        self.column(name).map(|v| v[row])
+    }
+
+    /// Returns a column as a time series
+    ///
+    /// # Arguments
+    /// * `name` - the column name
+    pub fn to_timeseries(&self, name: &str) -> Option<TimeSeries> {
+        self.column(name)
+            .map(|v| TimeSeries::new(self.dates.iter().cloned().zip(v.iter().cloned()).collect()))
+    }
+}
+
+/// Returns the row dates for a join
+fn join_dates(series: &[(Column, TimeSeries)], join: Join) -> Vec<NaiveDate> {
+    let dates_of = |data: &TimeSeries| -> Vec<NaiveDate> {
+        data.points().iter().map(|(d, _)| *d).collect()
+    };
+
+    match join {
+        Join::Left | Join::Asof => match series.first() {
+            Some((_, data)) => dates_of(data),
+            None => Vec::new(),
+        },
+        Join::Inner => match series.split_first() {
+            Some(((_, first), rest)) => dates_of(first).into_iter()
+                .filter(|d| rest.iter().all(|(_, data)| data.get(*d).is_some()))
+                .collect(),
+            None => Vec::new(),
+        },
+        Join::Outer => {
+            let mut dates: Vec<NaiveDate> = series.iter().flat_map(|(_, data)| dates_of(data)).collect();
+            dates.sort();
+            dates.dedup();
+            dates
+        },
+    }
+}
+
+/// Returns the last value present on or before a date
+fn last_on_or_before(data: &TimeSeries, date: NaiveDate) -> Option<f64> {
+    let end = data.points().partition_point(|(d, _)| *d <= date);
+    data.points()[..end].iter().rev().find_map(|(_, v)| *v)
+}
+
+fn forward_fill(cells: &mut [Option<f64>]) {
+    let mut last = None;
+    for cell in cells.iter_mut() {
+        match cell {
+            Some(v) => last = Some(*v),
+            None => *cell = last,
+        }
+    }
+}
+
+fn interpolate(dates: &[NaiveDate], cells: &mut [Option<f64>]) {
+    let mut prev: Option<usize> = None;
+    for idx in 0..cells.len() {
+        if cells[idx].is_none() {
+            continue;
+        }
+        if let Some(p) = prev {
+            if idx > p + 1 {
+                let (x0, x1) = (cells[p].unwrap_or_default(), cells[idx].unwrap_or_default());
+                let span = (dates[idx] - dates[p]).num_days() as f64;
+                for gap in p + 1..idx {
+                    let t = (dates[gap] - dates[p]).num_days() as f64 / span;
+                    cells[gap] = Some(x0 + (x1 - x0) * t);
+                }
+            }
+        }
+        prev = Some(idx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn builder(join: Join, fill: Fill) -> Builder {
+        let a = TimeSeries::new(vec![
+            (date(2020, 1, 1), Some(1.0)),
+            (date(2020, 1, 3), None),
+            (date(2020, 1, 5), Some(5.0)),
+        ]);
+        let b = TimeSeries::new(vec![
+            (date(2020, 1, 2), Some(20.0)),
+            (date(2020, 1, 3), Some(30.0)),
+            (date(2020, 1, 6), Some(60.0)),
+        ]);
+
+        let mut builder = Builder::new();
+        builder.series("A", a).series("B", b).join(join).fill(fill);
+        builder
+    }
+
+    #[test]
+    fn panel_joins() {
+        let outer = builder(Join::Outer, Fill::None).build();
+        assert_eq!(outer.len(), 5);
+        assert_eq!(outer.column("A").unwrap(), &[Some(1.0), None, None, Some(5.0), None]);
+        assert_eq!(outer.column("B").unwrap(), &[None, Some(20.0), Some(30.0), None, Some(60.0)]);
+        assert_eq!(outer.row(2), Some(vec![None, Some(30.0)]));
+        assert_eq!(outer.get(date(2020, 1, 6), "B"), Some(Some(60.0)));
+        assert_eq!(outer.get(date(2020, 1, 7), "B"), None);
+
+        let inner = builder(Join::Inner, Fill::None).build();
+        assert_eq!(inner.dates(), &[date(2020, 1, 3)]);
+        assert_eq!(inner.row(0), Some(vec![None, Some(30.0)]));
+
+        let left = builder(Join::Left, Fill::None).build();
+        assert_eq!(left.dates(), &[date(2020, 1, 1), date(2020, 1, 3), date(2020, 1, 5)]);
+        assert_eq!(left.column("B").unwrap(), &[None, Some(30.0), None]);
+
+        let asof = builder(Join::Asof, Fill::None).build();
+        assert_eq!(asof.dates(), left.dates());
+        assert_eq!(asof.column("A").unwrap(), &[Some(1.0), None, Some(5.0)]);
+        assert_eq!(asof.column("B").unwrap(), &[None, Some(30.0), Some(30.0)]);
+    }
+
+    #[test]
+    fn panel_fills() {
+        let ffill = builder(Join::Outer, Fill::Forward).build();
+        assert_eq!(ffill.column("A").unwrap(), &[Some(1.0), Some(1.0), Some(1.0), Some(5.0), Some(5.0)]);
+        assert_eq!(ffill.column("B").unwrap(), &[None, Some(20.0), Some(30.0), Some(30.0), Some(60.0)]);
+
+        let interpolated = builder(Join::Outer, Fill::Interpolate).build();
+        assert_eq!(interpolated.column("A").unwrap(), &[Some(1.0), Some(2.0), Some(3.0), Some(5.0), None]);
+        assert_eq!(interpolated.column("B").unwrap(), &[None, Some(20.0), Some(30.0), Some(50.0), Some(60.0)]);
+    }
+
+    #[test]
+    fn panel_frequency_and_metadata() {
+        let series = Series {
+            id: String::from("UNRATE"),
+            title: String::from("Unemployment Rate"),
+            units: String::from("Percent"),
+            frequency: String::from("Monthly"),
+            seasonal_adjustment: String::from("Seasonally Adjusted"),
+            ..Series::default()
+        };
+        let daily = TimeSeries::new(vec![
+            (date(2020, 1, 1), Some(1.0)),
+            (date(2020, 1, 2), Some(3.0)),
+            (date(2020, 2, 1), Some(4.0)),
+        ]);
+        let monthly = TimeSeries::new(vec![(date(2020, 2, 1), Some(10.0))]);
+
+        let mut builder = Builder::new();
+        builder.series_with(&series, daily)
+            .series("M", monthly)
+            .join(Join::Left)
+            .frequency(Frequency::M, AggregationMethod::AVG);
+        let panel = builder.build();
+
+        assert_eq!(panel.columns()[0].title.as_deref(), Some("Unemployment Rate"));
+        assert_eq!(panel.columns()[0].units.as_deref(), Some("Percent"));
+        assert_eq!(panel.columns()[1], Column::new("M"));
+        assert_eq!(panel.dates(), &[date(2020, 1, 1), date(2020, 2, 1)]);
+        assert_eq!(panel.column("UNRATE").unwrap(), &[Some(2.0), Some(4.0)]);
+        assert_eq!(panel.column_at(1).unwrap(), &[None, Some(10.0)]);
+        assert_eq!(panel.to_timeseries("M").unwrap().get(date(2020, 2, 1)), Some(Some(10.0)));
+    }
+}
@@ -0,0 +1,129 @@
+//! Request handling shared by the FRED clients
+//!
+//! [FredClient](../client/struct.FredClient.html) and [AsyncFredClient](../async_client/struct.AsyncFredClient.html) answer a request the same way: look it up in the cache, send it after waiting on the rate limiter, retry failures according to the retry policy and store the body with an expiry that may follow the series' next release.  Every decision along the way is made here, so the clients only differ in how they wait and send.
+
+use chrono::Utc;
+
+use std::time::{Duration, SystemTime};
+
+use crate::*;
+use crate::cache::Cache;
+use crate::request::Request;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpRequest, HttpResponse};
+
+/// What to do with a request before it is sent
+pub(crate) enum Lookup {
+    /// Answer with the cached body
+    Cached(String),
+    /// Send the request and, if a key is given, store the body under it
+    Send(Option<String>),
+}
+
+/// Looks a request up in the cache
+///
+/// In offline mode a request that is not cached fails with `Error::NotCached`.
+pub(crate) fn lookup(cache: Option<&Cache>, url_base: &str, request: &Request) -> Result<Lookup, Error> {
+    let cache = match cache {
+        Some(c) => c,
+        None => return Ok(Lookup::Send(None)),
+    };
+
+    let key = request.redacted_url(url_base);
+    if let Some(text) = cache.lookup(&key) {
+        return Ok(Lookup::Cached(text));
+    }
+    if cache.is_offline() {
+        return Err(Error::NotCached(key));
+    }
+    Ok(Lookup::Send(Some(key)))
+}
+
+/// Returns when a response stored now expires
+///
+/// # Arguments
+/// * `release` - the expiry found from the series' next release, if any
+pub(crate) fn expiry(cache: &Cache, request: &Request, release: Option<SystemTime>) -> SystemTime {
+    match release {
+        Some(t) => t,
+        None => cache.expiry(request.endpoint()),
+    }
+}
+
+/// Returns the fred/series/release request used to find when cached observations expire
+///
+/// Returns None unless the cache is release aware and the request is for observations.
+pub(crate) fn series_release_request(cache: &Cache, request: &Request) -> Option<Request> {
+    if !cache.is_release_aware() || request.endpoint() != "series/observations" {
+        return None;
+    }
+    let series_id = request.argument_value("series_id")?;
+    Some(request::series_release(series_id, None))
+}
+
+/// Returns the release of a series with the request for its next release date
+///
+/// # Arguments
+/// * `series_release` - the body returned by fred/series/release
+pub(crate) fn release_dates_request(series_release: &str) -> Option<(usize, Request)> {
+    let resp: release::Response = request::parse("series/release", series_release).ok()?;
+    let release_id = resp.releases.first()?.id;
+    let today = Utc::now().date_naive();
+    Some((release_id, request::upcoming_release_dates(release_id, today)))
+}
+
+/// Returns when cached observations expire given the release dates
+///
+/// # Arguments
+/// * `release_id` - the release of the series
+/// * `release_dates` - the body returned for the upcoming release dates request
+pub(crate) fn release_expiry(release_id: usize, release_dates: &str) -> Option<SystemTime> {
+    let dates: release::dates::Response = request::parse("release/dates", release_dates).ok()?;
+    cache::release_expiry(release_id, &dates.release_dates, Utc::now())
+}
+
+/// Returns the HTTP request sent for a request
+pub(crate) fn http_request(request: &Request, url_base: &str, api_key: &str) -> HttpRequest {
+    HttpRequest {
+        url: request.url(url_base, api_key),
+        redacted_url: request.redacted_url(url_base),
+    }
+}
+
+/// Checks a single attempt, returning the body and any Retry-After delay
+pub(crate) fn response(result: Result<HttpResponse, Error>) -> (Result<String, Error>, Option<Duration>) {
+    match result {
+        Ok(resp) => {
+            let retry_after = resp.header("Retry-After").and_then(retry::parse_retry_after);
+            (request::check_status(resp.status, resp.body), retry_after)
+        },
+        Err(e) => (Err(e), None),
+    }
+}
+
+/// Counts the attempts of a request and decides whether a failed attempt is retried
+pub(crate) struct Attempts<'a> {
+    policy: Option<&'a RetryPolicy>,
+    attempt: usize,
+}
+
+impl<'a> Attempts<'a> {
+    pub(crate) fn new(policy: Option<&'a RetryPolicy>) -> Attempts<'a> {
+        Attempts { policy, attempt: 1 }
+    }
+
+    /// Returns the delay before the next attempt, or the error to return if the request is not retried
+    ///
+    /// # Arguments
+    /// * `err` - the error of the last attempt
+    /// * `retry_after` - the Retry-After delay sent by the server, if any
+    pub(crate) fn failed(&mut self, err: Error, retry_after: Option<Duration>) -> Result<Duration, Error> {
+        match self.policy.and_then(|p| p.next_delay(self.attempt, &err, retry_after)) {
+            Some(delay) => {
+                self.attempt += 1;
+                Ok(delay)
+            },
+            None => Err(err.after_attempts(self.attempt)),
+        }
+    }
+}
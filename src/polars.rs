@@ -0,0 +1,231 @@
+//! Polars DataFrame conversions (requires the `polars` feature)
+//!
+//! * `observations_frame()` converts a `series::observation::Response` to a date column and a nullable Float64 value column, optionally preceded by the realtime_start and realtime_end of each observation.
+//! * `panel_frame()` converts a [Panel](../panel/struct.Panel.html) of aligned series to a date column followed by one column per series.
+//! * `series_frame()`, `tags_frame()` and `releases_frame()` convert metadata responses with one row per item.
+//!
+//! Dates are polars `Date` columns.
+//!
+//! ```
+//! use fred_rs::polars::panel_frame;
+//! use fred_rs::panel::Builder;
+//! use fred_rs::timeseries::TimeSeries;
+//! use chrono::NaiveDate;
+//!
+//! let date = |m| NaiveDate::from_ymd_opt(2020, m, 1).unwrap();
+//! let mut builder = Builder::new();
+//! builder.series("UNRATE", TimeSeries::new(vec![(date(1), Some(3.6)), (date(2), None)]));
+//!
+//! let df = panel_frame(&builder.build()).unwrap();
+//! assert_eq!(df.shape(), (2, 2));
+//! assert_eq!(df.column("UNRATE").unwrap().null_count(), 1);
+//! ```
+
+use chrono::NaiveDate;
+use polars_core::prelude as pl;
+use polars_core::prelude::{IntoColumn, NamedFrom};
+
+use crate::error::Error;
+use crate::panel::Panel;
+use crate::series::observation::{parse_date, Response};
+
+/// Converts the observations of a series to a DataFrame
+///
+/// The columns are date (`Date`) and value (nullable `Float64`).  With `realtime` the frame starts with realtime_start and realtime_end columns so vintage-aware downloads keep every realtime period.  The first data point that cannot be parsed is returned as an error.
+///
+/// # Arguments
+/// * `resp` - observations returned by fred/series/observations
+/// * `realtime` - true to include the realtime columns
+pub fn observations_frame(resp: &Response, realtime: bool) -> Result<pl::DataFrame, Error> {
+    let mut observations = Vec::with_capacity(resp.observations.len());
+    for point in &resp.observations {
+        observations.push(point.to_observation()?);
+    }
+
+    let mut columns = Vec::with_capacity(4);
+    if realtime {
+        columns.push(dates("realtime_start", observations.iter().map(|o| o.realtime_start)));
+        columns.push(dates("realtime_end", observations.iter().map(|o| o.realtime_end)));
+    }
+    columns.push(dates("date", observations.iter().map(|o| o.date)));
+    let values: Vec<Option<f64>> = observations.iter().map(|o| o.value).collect();
+    columns.push(pl::Column::new("value".into(), values));
+
+    frame(columns)
+}
+
+/// Converts aligned series to a DataFrame
+///
+/// The first column is the date, followed by one nullable `Float64` column per series named after it.
+///
+/// # Arguments
+/// * `panel` - the aligned series
+pub fn panel_frame(panel: &Panel) -> Result<pl::DataFrame, Error> {
+    let mut columns = vec![dates("date", panel.dates().iter().cloned())];
+    for (idx, column) in panel.columns().iter().enumerate() {
+        let values = panel.column_at(idx).unwrap_or_default();
+        columns.push(pl::Column::new(column.name.as_str().into(), values));
+    }
+
+    frame(columns)
+}
+
+/// Converts series information to a DataFrame
+///
+/// Dates are `Date` columns, popularity scores are `Int64` and every other field is text.
+///
+/// # Arguments
+/// * `resp` - series returned by fred/series, a series search or a list endpoint
+pub fn series_frame(resp: &crate::series::Response) -> Result<pl::DataFrame, Error> {
+    let s = &resp.seriess;
+    frame(vec![
+        text("id", s.iter().map(|s| s.id.as_str())),
+        parsed_dates("realtime_start", s.iter().map(|s| s.realtime_start.as_str()))?,
+        parsed_dates("realtime_end", s.iter().map(|s| s.realtime_end.as_str()))?,
+        text("title", s.iter().map(|s| s.title.as_str())),
+        parsed_dates("observation_start", s.iter().map(|s| s.observation_start.as_str()))?,
+        parsed_dates("observation_end", s.iter().map(|s| s.observation_end.as_str()))?,
+        text("frequency", s.iter().map(|s| s.frequency.as_str())),
+        text("frequency_short", s.iter().map(|s| s.frequency_short.as_str())),
+        text("units", s.iter().map(|s| s.units.as_str())),
+        text("units_short", s.iter().map(|s| s.units_short.as_str())),
+        text("seasonal_adjustment", s.iter().map(|s| s.seasonal_adjustment.as_str())),
+        text("seasonal_adjustment_short", s.iter().map(|s| s.seasonal_adjustment_short.as_str())),
+        text("last_updated", s.iter().map(|s| s.last_updated.as_str())),
+        integers("popularity", s.iter().map(|s| Some(s.popularity as i64))),
+        integers("group_popularity", s.iter().map(|s| s.group_popularity.map(|p| p as i64))),
+        optional_text("notes", s.iter().map(|s| s.notes.as_deref())),
+    ])
+}
+
+/// Converts tags to a DataFrame
+///
+/// # Arguments
+/// * `resp` - tags returned by fred/tags or a related tags endpoint
+pub fn tags_frame(resp: &crate::tags::Response) -> Result<pl::DataFrame, Error> {
+    let t = &resp.tags;
+    frame(vec![
+        text("name", t.iter().map(|t| t.name.as_str())),
+        text("group_id", t.iter().map(|t| t.group_id.as_str())),
+        text("created", t.iter().map(|t| t.created.as_str())),
+        integers("popularity", t.iter().map(|t| Some(t.popularity as i64))),
+        integers("series_count", t.iter().map(|t| Some(t.series_count as i64))),
+        optional_text("notes", t.iter().map(|t| t.notes.as_deref())),
+    ])
+}
+
+/// Converts releases to a DataFrame
+///
+/// # Arguments
+/// * `resp` - releases returned by fred/release or fred/releases
+pub fn releases_frame(resp: &crate::release::Response) -> Result<pl::DataFrame, Error> {
+    let r = &resp.releases;
+    frame(vec![
+        integers("id", r.iter().map(|r| Some(r.id as i64))),
+        parsed_dates("realtime_start", r.iter().map(|r| r.realtime_start.as_str()))?,
+        parsed_dates("realtime_end", r.iter().map(|r| r.realtime_end.as_str()))?,
+        text("name", r.iter().map(|r| r.name.as_str())),
+        pl::Column::new("press_release".into(), r.iter().map(|r| r.press_release).collect::<Vec<bool>>()),
+        optional_text("link", r.iter().map(|r| r.link.as_deref())),
+        optional_text("notes", r.iter().map(|r| r.notes.as_deref())),
+    ])
+}
+
+/// Creates a `Date` column from dates
+fn dates<I: Iterator<Item = NaiveDate>>(name: &str, dates: I) -> pl::Column {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    let days: Vec<i32> = dates.map(|d| (d - epoch).num_days() as i32).collect();
+    pl::Int32Chunked::new(name.into(), &days).into_date().into_column()
+}
+
+/// Creates a `Date` column from FRED date strings
+fn parsed_dates<'a, I: Iterator<Item = &'a str>>(name: &str, text: I) -> Result<pl::Column, Error> {
+    let mut values = Vec::new();
+    for t in text {
+        values.push(parse_date(name, t)?);
+    }
+    Ok(dates(name, values.into_iter()))
+}
+
+fn text<'a, I: Iterator<Item = &'a str>>(name: &str, values: I) -> pl::Column {
+    pl::Column::new(name.into(), values.collect::<Vec<&str>>())
+}
+
+fn optional_text<'a, I: Iterator<Item = Option<&'a str>>>(name: &str, values: I) -> pl::Column {
+    pl::Column::new(name.into(), values.collect::<Vec<Option<&str>>>())
+}
+
+fn integers<I: Iterator<Item = Option<i64>>>(name: &str, values: I) -> pl::Column {
+    pl::Column::new(name.into(), values.collect::<Vec<Option<i64>>>())
+}
+
+fn frame(columns: Vec<pl::Column>) -> Result<pl::DataFrame, Error> {
+    match pl::DataFrame::new(columns) {
+        Ok(df) => Ok(df),
+        Err(e) => Err(Error::Convert(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::panel::Builder;
+    use crate::timeseries::TimeSeries;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn polars_observations() {
+        let resp: Response = serde_json::from_str(r#"{"realtime_start":"2020-01-01","realtime_end":"9999-12-31","observation_start":"1776-07-04","observation_end":"9999-12-31",
+            "units":"lin","output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":2,"offset":0,"limit":100000,
+            "observations":[
+                {"realtime_start":"2020-01-01","realtime_end":"2020-06-30","date":"2019-01-01","value":"1.5"},
+                {"realtime_start":"2020-07-01","realtime_end":"9999-12-31","date":"2019-01-01","value":"."}
+            ]}"#).unwrap();
+
+        let df = observations_frame(&resp, false).unwrap();
+        assert_eq!(df.get_column_names(), vec!["date", "value"]);
+        assert_eq!(df.column("date").unwrap().dtype(), &pl::DataType::Date);
+        let values: Vec<Option<f64>> = df.column("value").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(values, vec![Some(1.5), None]);
+
+        let df = observations_frame(&resp, true).unwrap();
+        assert_eq!(df.get_column_names(), vec!["realtime_start", "realtime_end", "date", "value"]);
+        let end = df.column("realtime_end").unwrap().date().unwrap().get(0);
+        assert_eq!(end, Some((date(2020, 6, 30) - date(1970, 1, 1)).num_days() as i32));
+    }
+
+    #[test]
+    fn polars_panel_and_metadata() {
+        let mut builder = Builder::new();
+        builder.series("A", TimeSeries::new(vec![(date(2020, 1, 1), Some(1.0))]))
+            .series("B", TimeSeries::new(vec![(date(2020, 2, 1), Some(2.0))]));
+        let df = panel_frame(&builder.build()).unwrap();
+        assert_eq!(df.shape(), (2, 3));
+        assert_eq!(df.column("B").unwrap().null_count(), 1);
+
+        let releases: crate::release::Response = serde_json::from_str(r#"{"realtime_start":"2020-01-01","realtime_end":"2020-01-01",
+            "releases":[{"id":53,"realtime_start":"2020-01-01","realtime_end":"2020-01-01","name":"Gross Domestic Product","press_release":true}]}"#).unwrap();
+        let df = releases_frame(&releases).unwrap();
+        assert_eq!(df.shape(), (1, 7));
+        assert_eq!(df.column("id").unwrap().i64().unwrap().get(0), Some(53));
+        assert_eq!(df.column("link").unwrap().null_count(), 1);
+
+        let tags: crate::tags::Response = serde_json::from_str(r#"{"realtime_start":"2020-01-01","realtime_end":"2020-01-01","order_by":"series_count",
+            "sort_order":"desc","count":1,"offset":0,"limit":1000,
+            "tags":[{"name":"gdp","group_id":"gen","notes":null,"created":"2012-02-27 10:18:19-06","popularity":81,"series_count":23134}]}"#).unwrap();
+        let df = tags_frame(&tags).unwrap();
+        assert_eq!(df.column("series_count").unwrap().i64().unwrap().get(0), Some(23134));
+
+        let series: crate::series::Response = serde_json::from_str(r#"{"realtime_start":"2020-01-01","realtime_end":"2020-01-01",
+            "seriess":[{"id":"GNPCA","realtime_start":"2020-01-01","realtime_end":"2020-01-01","title":"Real Gross National Product",
+            "observation_start":"1929-01-01","observation_end":"bad","frequency":"Annual","frequency_short":"A","units":"Billions","units_short":"Bil.",
+            "seasonal_adjustment":"Not Seasonally Adjusted","seasonal_adjustment_short":"NSA","last_updated":"2019-12-19 07:56:02-06","popularity":12}]}"#).unwrap();
+        match series_frame(&series) {
+            Err(Error::Parse { field, .. }) => assert_eq!(field, "observation_end"),
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
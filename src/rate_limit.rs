@@ -0,0 +1,134 @@
+//! Client-side rate limiting for FRED requests
+//!
+//! FRED throttles each API key to roughly 120 requests per minute and answers additional requests with status 429.  A [RateLimiter](struct.RateLimiter.html) attached to a client spaces out requests so the quota is not exceeded.  The blocking client sleeps and the async client awaits until a request slot is free.
+//!
+//! Clones of a client (and clones of a limiter) share the same token bucket, so every thread in a process draws from one quota.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::rate_limit::RateLimiter;
+//!
+//! // allow 120 requests per minute with bursts of up to 10 requests
+//! let client = match FredClient::builder()
+//!     .rate_limiter(RateLimiter::new(120, 10))
+//!     .build()
+//! {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Approximate number of requests per minute FRED allows for a single API key
+pub const FRED_REQUESTS_PER_MINUTE: u32 = 120;
+
+#[derive(Clone, Debug)]
+/// Token bucket shared by every client holding a clone of it
+///
+/// The bucket holds up to `burst` tokens and refills at `per_minute` tokens per minute.  Each request takes one token and waits for the bucket to refill when it is empty.
+pub struct RateLimiter {
+    per_minute: u32,
+    burst: u32,
+    state: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens available; negative when requests are queued waiting for a refill
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter with a full bucket
+    ///
+    /// # Arguments
+    /// * `per_minute` - sustained number of requests allowed per minute (minimum 1)
+    /// * `burst` - number of requests that may be sent back to back (minimum 1)
+    pub fn new(per_minute: u32, burst: u32) -> RateLimiter {
+        let per_minute = per_minute.max(1);
+        let burst = burst.max(1);
+
+        RateLimiter {
+            per_minute,
+            burst,
+            state: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// Returns the sustained number of requests allowed per minute
+    pub fn per_minute(&self) -> u32 {
+        self.per_minute
+    }
+
+    /// Returns the number of requests that may be sent back to back
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Takes a token from the bucket and returns how long the caller must wait before sending its request
+    ///
+    /// Waiting callers hold a reservation, so concurrent callers are spaced out instead of all waking at once.
+    pub(crate) fn reserve(&self) -> Duration {
+        let mut bucket = match self.state.lock() {
+            Ok(b) => b,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let rate = f64::from(self.per_minute) / 60.0; // tokens per second
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(f64::from(self.burst));
+        bucket.last_refill = now;
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    /// A limiter matching FRED's quota of 120 requests per minute with no bursts above that rate
+    fn default() -> RateLimiter {
+        RateLimiter::new(FRED_REQUESTS_PER_MINUTE, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_burst_then_wait() {
+        let limiter = RateLimiter::new(60, 2);
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+
+        // the bucket is empty and refills one token per second
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+
+        // the next caller queues behind the previous reservation
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(1900) && wait <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn rate_limiter_clones_share_bucket() {
+        let limiter = RateLimiter::new(60, 1);
+        let clone = limiter.clone();
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert!(clone.reserve() > Duration::from_millis(900));
+    }
+}
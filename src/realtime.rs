@@ -0,0 +1,237 @@
+//! Point-in-time views of a series from its realtime periods
+//!
+//! In real-time mode (output type 1) every observation carries the realtime_start and realtime_end of the period during which FRED reported that value.  [RealtimeObservations](struct.RealtimeObservations.html) holds those periods and answers "what did the series look like on a given date" locally: `as_of()` keeps, for each observation date, the value whose realtime period contains the as-of date.
+//!
+//! `FredClient::series_observation_as_of()` downloads and resolves a single date.  `FredClient::series_observation_as_of_dates()` resolves any number of dates from one download covering the realtime range between the earliest and latest of them.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use chrono::NaiveDate;
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let dates = [
+//!     NaiveDate::from_ymd_opt(2008, 10, 15).unwrap(),
+//!     NaiveDate::from_ymd_opt(2012, 10, 15).unwrap(),
+//! ];
+//! let views = match c.series_observation_as_of_dates("GNPCA", &dates, None) {
+//!     Ok(v) => v,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! for (as_of, series) in views {
+//!     println!("known on {}: {} observations", as_of, series.len());
+//! }
+//! ```
+
+use chrono::NaiveDate;
+
+use crate::error::Error;
+use crate::series::observation::{Observation, Response};
+use crate::timeseries::TimeSeries;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Observations with the realtime periods during which each value was reported
+pub struct RealtimeObservations {
+    /// Sorted by date, then realtime_start
+    observations: Vec<Observation>,
+}
+
+impl RealtimeObservations {
+    /// Creates the realtime history from typed observations
+    ///
+    /// # Arguments
+    /// * `observations` - observations in any order
+    pub fn new(mut observations: Vec<Observation>) -> RealtimeObservations {
+        observations.sort_by_key(|o| (o.date, o.realtime_start));
+        RealtimeObservations { observations }
+    }
+
+    /// Creates the realtime history from a real-time mode observation response
+    ///
+    /// The first data point that cannot be parsed is returned as an error.
+    ///
+    /// # Arguments
+    /// * `resp` - observations returned by fred/series/observations with output type 1
+    pub fn from_response(resp: &Response) -> Result<RealtimeObservations, Error> {
+        let mut observations = Vec::with_capacity(resp.observations.len());
+        for point in &resp.observations {
+            observations.push(point.to_observation()?);
+        }
+        Ok(RealtimeObservations::new(observations))
+    }
+
+    /// Returns the observations sorted by date and realtime_start
+    pub fn observations(&self) -> &[Observation] {
+        &self.observations
+    }
+
+    /// Returns the number of realtime periods
+    pub fn len(&self) -> usize {
+        self.observations.len()
+    }
+
+    /// Returns true if there are no observations
+    pub fn is_empty(&self) -> bool {
+        self.observations.is_empty()
+    }
+
+    /// Returns the series as it was known on a date
+    ///
+    /// Observation dates with no realtime period containing `as_of` (not yet released, or outside the downloaded realtime range) are left out.
+    ///
+    /// # Arguments
+    /// * `as_of` - the date the data is viewed from
+    pub fn as_of(&self, as_of: NaiveDate) -> TimeSeries {
+        TimeSeries::new(self.observations.iter()
+            .filter(|o| o.realtime_start <= as_of && as_of <= o.realtime_end)
+            .map(|o| (o.date, o.value))
+            .collect())
+    }
+
+    /// Returns the series as it was known on each of several dates
+    ///
+    /// # Arguments
+    /// * `dates` - the dates the data is viewed from
+    pub fn as_of_dates(&self, dates: &[NaiveDate]) -> Vec<(NaiveDate, TimeSeries)> {
+        dates.iter().map(|d| (*d, self.as_of(*d))).collect()
+    }
+}
+
+impl std::convert::TryFrom<&Response> for RealtimeObservations {
+    type Error = Error;
+
+    fn try_from(resp: &Response) -> Result<RealtimeObservations, Error> {
+        RealtimeObservations::from_response(resp)
+    }
+}
+
+/// Returns the earliest realtime_start FRED accepts
+pub fn earliest_realtime() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1776, 7, 4).unwrap_or_default()
+}
+
+/// Returns the realtime_end FRED reports for values that are still current
+pub fn latest_realtime() -> NaiveDate {
+    NaiveDate::from_ymd_opt(9999, 12, 31).unwrap_or_default()
+}
+
+/// Returns the earliest and latest of a list of dates
+pub(crate) fn date_bounds(dates: &[NaiveDate]) -> Result<(NaiveDate, NaiveDate), Error> {
+    match (dates.iter().min(), dates.iter().max()) {
+        (Some(first), Some(last)) => Ok((*first, *last)),
+        _ => Err(Error::Builder(String::from("At least one as-of date must be given."))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::series::observation::Builder;
+    use crate::transport::{page_body, realtime_point, test_client, MemoryTransport};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    const REALTIME: &str = r#"{"realtime_start":"2008-01-01","realtime_end":"2012-12-31","observation_start":"1776-07-04","observation_end":"9999-12-31",
+        "units":"lin","output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":5,"offset":0,"limit":100000,
+        "observations":[
+            {"realtime_start":"2008-01-01","realtime_end":"2009-07-30","date":"2006-01-01","value":"100.0"},
+            {"realtime_start":"2009-07-31","realtime_end":"2012-12-31","date":"2006-01-01","value":"101.5"},
+            {"realtime_start":"2008-01-01","realtime_end":"2008-01-30","date":"2007-01-01","value":"."},
+            {"realtime_start":"2008-01-31","realtime_end":"2012-12-31","date":"2007-01-01","value":"103.0"},
+            {"realtime_start":"2009-01-30","realtime_end":"2012-12-31","date":"2008-01-01","value":"104.0"}
+        ]}"#;
+
+    #[test]
+    fn realtime_as_of() {
+        let resp: Response = serde_json::from_str(REALTIME).unwrap();
+        let history = RealtimeObservations::from_response(&resp).unwrap();
+        assert_eq!(history.len(), 5);
+
+        assert_eq!(history.as_of(date(2008, 1, 15)).points(), &[
+            (date(2006, 1, 1), Some(100.0)),
+            (date(2007, 1, 1), None),
+        ]);
+        assert_eq!(history.as_of(date(2010, 1, 1)).points(), &[
+            (date(2006, 1, 1), Some(101.5)),
+            (date(2007, 1, 1), Some(103.0)),
+            (date(2008, 1, 1), Some(104.0)),
+        ]);
+        assert!(history.as_of(date(2007, 1, 1)).is_empty());
+    }
+
+    #[test]
+    fn realtime_as_of_dates_one_request() {
+        let transport = MemoryTransport::new();
+        transport.route("series_id=GNPCA&*realtime_start=2008-01-15&realtime_end=2010-01-01&output_type=1", REALTIME);
+        let c = test_client(&transport);
+
+        // realtime arguments set by the builder are replaced
+        let mut builder = Builder::new();
+        builder.realtime_start("1999-01-01").observation_start("2006-01-01");
+        let views = c.series_observation_as_of_dates("GNPCA", &[date(2010, 1, 1), date(2008, 1, 15)], Some(builder)).unwrap();
+
+        assert_eq!(transport.requests().len(), 1);
+        assert!(transport.requests()[0].contains("observation_start=2006-01-01"));
+        assert!(!transport.requests()[0].contains("1999-01-01"));
+        assert_eq!(views[0].0, date(2010, 1, 1));
+        assert_eq!(views[0].1.len(), 3);
+        assert_eq!(views[1].1.get(date(2006, 1, 1)), Some(Some(100.0)));
+
+        match c.series_observation_as_of_dates("GNPCA", &[], None) {
+            Err(Error::Builder(_)) => (),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    const WINDOW: (&str, &str) = ("2008-01-01", "2012-12-31");
+
+    fn points(dates: &[&str]) -> Vec<String> {
+        dates.iter().map(|d| realtime_point("2008-01-01", "9999-12-31", d, "1.0")).collect()
+    }
+
+    #[test]
+    fn realtime_pages_until_count() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("series_id=GNPCA&*limit=2&offset=0", &page_body("observations", WINDOW, 5, 0, &points(&["2006-01-01", "2007-01-01"])))
+            .route("series_id=GNPCA&*limit=2&offset=2", &page_body("observations", WINDOW, 5, 2, &points(&["2008-01-01", "2009-01-01"])))
+            .route("series_id=GNPCA&*limit=2&offset=4", &page_body("observations", WINDOW, 5, 4, &points(&["2010-01-01"])));
+        let c = test_client(&transport);
+
+        let mut builder = Builder::new();
+        builder.limit(2);
+        let history = c.series_observation_realtime("GNPCA", date(2008, 1, 1), date(2012, 12, 31), Some(builder)).unwrap();
+        assert_eq!(history.len(), 5);
+        assert_eq!(transport.requests().len(), 3);
+        assert_eq!(history.as_of(date(2012, 1, 1)).len(), 5);
+
+        // a page that comes back short of the reported count fails instead of dropping periods
+        let transport = MemoryTransport::new();
+        transport
+            .route("series_id=GNPCA&*limit=2&offset=0", &page_body("observations", WINDOW, 5, 0, &points(&["2006-01-01", "2007-01-01"])))
+            .route("series_id=GNPCA&*limit=2&offset=2", &page_body("observations", WINDOW, 5, 2, &points(&[])));
+        let c = test_client(&transport);
+
+        let mut builder = Builder::new();
+        builder.limit(2);
+        match c.series_observation_realtime("GNPCA", date(2008, 1, 1), date(2012, 12, 31), Some(builder)) {
+            Err(Error::Truncated { count, received, .. }) => {
+                assert_eq!(count, 5);
+                assert_eq!(received, 2);
+            },
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
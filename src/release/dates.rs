@@ -0,0 +1,242 @@
+//! Get release dates for a release of economic data
+//!
+//! [https://research.stlouisfed.org/docs/api/fred/release_dates.html](https://research.stlouisfed.org/docs/api/fred/release_dates.html)
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::release::dates::{Builder, Response, SortOrder};
+//!
+//! // Create the client object
+//! let mut c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! // Create the argument builder
+//! let mut builder = Builder::new();
+//! builder
+//!     .limit(5)
+//!     .sort_order(SortOrder::Descending);
+//!
+//! // Make the request and pass in the builder to apply the arguments
+//! let resp: Response = match c.release_dates(50, Some(builder)) {
+//!     Ok(resp) => resp,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! ```
+
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+
+pub use crate::releases::dates::ReleaseDate;
+
+#[derive(Deserialize, Clone, Debug, Default)]
+/// Response data structure for the fred/release/dates endpoint
+///
+/// [https://research.stlouisfed.org/docs/api/fred/release_dates.html] (https://research.stlouisfed.org/docs/api/fred/release_dates.html)
+pub struct Response {
+    /// The Real Time start date for the request
+    pub realtime_start: String,
+    /// The Real Time end data for the request
+    pub realtime_end: String,
+    /// How the results are ordered
+    pub order_by: String,
+    // Results are listed in ascending or descending order
+    pub sort_order: String,
+    /// Number of results returned
+    pub count: usize,
+    /// First result returned
+    pub offset: usize,
+    /// Maximum number of results to return
+    pub limit: usize,
+    /// List of release dates for the release
+    pub release_dates: Vec<ReleaseDate>,
+}
+
+impl Display for Response {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for item in self.release_dates.iter() {
+            match item.fmt(f) {
+                Ok(_) => (),
+                Err(e) => return Err(e),
+            }
+            match writeln!(f) {
+                Ok(_) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sort order options for the fred/release/dates endpoint
+///
+/// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#sort_order](https://research.stlouisfed.org/docs/api/fred/release_dates.html#sort_order)
+pub enum SortOrder {
+    /// Dates returned in ascending order (default)
+    Ascending,
+    /// Dates returned in descending order
+    Descending,
+}
+
+pub struct Builder {
+    option_string: String
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+impl Builder {
+
+    /// Initializes a new release::dates::Builder that can be used to add commands to an API request
+    ///
+    /// The builder does not do validity checking of the arguments nor does it check for duplicates.
+    ///
+    /// ```
+    /// use fred_rs::release::dates::Builder;
+    /// // Create a new builder
+    /// let mut builder = Builder::new();
+    /// // add arguments to the builder
+    /// builder
+    ///     .realtime_start("1900-01-01")
+    ///     .realtime_end("2000-01-01");
+    /// ```
+    pub fn new() -> Builder {
+        Builder {
+            option_string: String::new(),
+        }
+    }
+
+    /// Returns the current arguments as a URL formatted string
+    pub(crate) fn build(self) -> String {
+        self.option_string
+    }
+
+    /// Adds a realtime_start argument to the builder
+    ///
+    /// # Arguments
+    /// * `start_date` - date formatted as YYYY-MM-DD
+    ///
+    /// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#realtime_start](https://research.stlouisfed.org/docs/api/fred/release_dates.html#realtime_start)
+    pub fn realtime_start(&mut self, start_date: &str) -> &mut Builder {
+        self.option_string += format!("&realtime_start={}", start_date).as_str();
+        self
+    }
+
+    /// Adds a realtime_end argument to the builder
+    ///
+    /// # Arguments
+    /// * `end_date` - date formatted as YYYY-MM-DD
+    ///
+    /// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#realtime_end](https://research.stlouisfed.org/docs/api/fred/release_dates.html#realtime_end)
+    pub fn realtime_end(&mut self, end_date: &str) -> &mut Builder {
+        self.option_string += format!("&realtime_end={}", end_date).as_str();
+        self
+    }
+
+    /// Adds a limit argument to the builder
+    ///
+    /// The limit argument specifies a maximum number of observations to return.
+    ///
+    /// # Arguments
+    /// * `num_results` - Maximum number of results to return
+    ///
+    /// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#limit](https://research.stlouisfed.org/docs/api/fred/release_dates.html#limit)
+    pub fn limit(&mut self, num_results: usize) -> &mut Builder {
+        let num_results = if num_results > 10000 { // max value is 10000
+            10000
+        } else {
+            num_results
+        };
+        self.option_string += format!("&limit={}", num_results).as_str();
+        self
+    }
+
+    /// Adds an offset argument to the builder
+    ///
+    /// Adding an offset shifts the starting result number.  For example, if limit is 5 and offset is 0 then results 1-5 will be returned, but if offset was 5 then results 6-10 would be returned.
+    ///
+    /// # Arguments
+    /// * `ofs` - the offset amount
+    ///
+    /// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#offset](https://research.stlouisfed.org/docs/api/fred/release_dates.html#offset)
+    pub fn offset(&mut self, ofs: usize) -> &mut Builder {
+        self.option_string += format!("&offset={}", ofs).as_str();
+        self
+    }
+
+    /// Change the sort order of the data
+    ///
+    /// # Arguments
+    /// * `order` - Data sort order enum
+    ///
+    /// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#sort_order](https://research.stlouisfed.org/docs/api/fred/release_dates.html#sort_order)
+    pub fn sort_order(&mut self, order: SortOrder) -> &mut Builder {
+        match order {
+            SortOrder::Ascending => (), // ASC is the default so do nothing
+            SortOrder::Descending => {
+                self.option_string += "&sort_order=desc"
+            },
+        }
+        self
+    }
+
+    /// Include release dates with no data available
+    ///
+    /// The default is false.  Calling this will set the argument to true.
+    ///
+    /// [https://research.stlouisfed.org/docs/api/fred/release_dates.html#include_release_dates_with_no_data](https://research.stlouisfed.org/docs/api/fred/release_dates.html#include_release_dates_with_no_data)
+    pub fn include_release_dates_with_no_data(&mut self) -> &mut Builder {
+        self.option_string += "&include_release_dates_with_no_data=true";
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{test_client, MemoryTransport};
+
+    #[test]
+    fn release_dates_with_options() {
+        let transport = MemoryTransport::new();
+        transport.route(
+            "release/dates?release_id=50&*realtime_start=2020-01-01&limit=2&offset=1&sort_order=desc&include_release_dates_with_no_data=true",
+            r#"{"realtime_start":"2020-01-01","realtime_end":"9999-12-31","order_by":"release_date","sort_order":"desc","count":12,"offset":1,"limit":2,
+            "release_dates":[{"release_id":50,"date":"2020-11-06"},{"release_id":50,"date":"2020-10-02"}]}"#
+        );
+        let c = test_client(&transport);
+
+        let mut builder = Builder::new();
+        builder
+            .realtime_start("2020-01-01")
+            .limit(2)
+            .offset(1)
+            .sort_order(SortOrder::Descending)
+            .include_release_dates_with_no_data();
+
+        let resp: Response = match c.release_dates(50, Some(builder)) {
+            Ok(resp) => resp,
+            Err(msg) => {
+                println!("{}", msg);
+                assert_eq!(2, 1);
+                return
+            },
+        };
+
+        assert_eq!(resp.count, 12);
+        assert_eq!(resp.release_dates.len(), 2);
+        assert_eq!(resp.release_dates[0].release_id, 50);
+        assert_eq!(resp.release_dates[0].release_name, None);
+        assert_eq!(resp.release_dates[1].date, "2020-10-02");
+    }
+}
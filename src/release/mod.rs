@@ -38,6 +38,7 @@ pub mod sources;
 pub mod tags;
 pub mod related_tags;
 pub mod tables;
+pub mod dates;
 
 // -----------------------------------------------------------------------------
 use serde::Deserialize;
@@ -0,0 +1,697 @@
+//! Request construction and response parsing shared by the FRED clients
+//! 
+//! Both [FredClient](../client/struct.FredClient.html) and [AsyncFredClient](../async_client/struct.AsyncFredClient.html) describe each endpoint call with a `Request`, check the response with `check_status()` and decode the returned text with `parse()`, so the blocking and async clients always send the same URLs and produce the same responses.
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+
+use crate::*;
+use crate::error::FredError;
+
+pub(crate) const FRED_BASE_URL: &str = "https://api.stlouisfed.org/fred/";
+pub(crate) const FRED_API_KEY: &str = "FRED_API_KEY";
+
+/// A single call to a FRED endpoint, independent of the client making it
+#[derive(Clone)]
+pub(crate) struct Request {
+    endpoint: &'static str,
+    argument: Option<(&'static str, String)>,
+    options: String,
+}
+
+impl Request {
+    fn new(endpoint: &'static str) -> Request {
+        Request {
+            endpoint,
+            argument: None,
+            options: String::new(),
+        }
+    }
+
+    fn argument(mut self, name: &'static str, value: String) -> Request {
+        self.argument = Some((name, value));
+        self
+    }
+
+    fn options(mut self, options: String) -> Request {
+        self.options = options;
+        self
+    }
+
+    /// Returns the endpoint path (e.g. series/observations)
+    pub(crate) fn endpoint(&self) -> &'static str {
+        self.endpoint
+    }
+
+    /// Returns the value of the required argument if it has the given name
+    pub(crate) fn argument_value(&self, name: &str) -> Option<&str> {
+        match &self.argument {
+            Some((n, value)) if *n == name => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an optional argument set by the builder
+    pub(crate) fn option_value(&self, name: &str) -> Option<&str> {
+        self.options.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns a copy of the request for one page of results
+    /// 
+    /// Any limit and offset set by the builder are replaced.
+    pub(crate) fn with_page(&self, offset: usize, limit: usize) -> Request {
+        self.with_options(&[("limit", limit.to_string()), ("offset", offset.to_string())])
+    }
+
+    /// Returns a copy of the request with the given options
+    /// 
+    /// Any values the builder set for these options are replaced.
+    pub(crate) fn with_options(&self, replaced: &[(&str, String)]) -> Request {
+        let mut options: String = self.options.split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter(|pair| match pair.split_once('=') {
+                Some((name, _)) => !replaced.iter().any(|(n, _)| *n == name),
+                None => true,
+            })
+            .map(|pair| format!("&{}", pair))
+            .collect();
+        for (name, value) in replaced {
+            options += format!("&{}={}", name, value).as_str();
+        }
+
+        Request {
+            endpoint: self.endpoint,
+            argument: self.argument.clone(),
+            options,
+        }
+    }
+
+    /// Returns the full request URL for the given base URL and API key
+    pub(crate) fn url(&self, url_base: &str, api_key: &str) -> String {
+        format!(
+            "{}{}?{}api_key={}&file_type=json{}",
+            url_base,
+            self.endpoint,
+            self.argument_string(),
+            api_key,
+            self.options
+        )
+    }
+
+    /// Returns the request URL without the API key
+    /// 
+    /// The redacted URL identifies a request independently of the key used to send it.
+    pub(crate) fn redacted_url(&self, url_base: &str) -> String {
+        format!(
+            "{}{}?{}file_type=json{}",
+            url_base,
+            self.endpoint,
+            self.argument_string(),
+            self.options
+        )
+    }
+
+    fn argument_string(&self) -> String {
+        match &self.argument {
+            Some((name, value)) => format!("{}={}&", name, value),
+            None => String::new(),
+        }
+    }
+}
+
+/// Returns the response body if the status is successful
+/// 
+/// For any other status the body is returned as an `Error::Api` if it is a FRED error message, or as an `Error::Status` otherwise.
+pub(crate) fn check_status(status: u16, text: String) -> Result<String, Error> {
+    if (200..300).contains(&status) {
+        return Ok(text);
+    }
+
+    match serde_json::from_str::<FredError>(&text) {
+        Ok(err) => Err(Error::from(err)),
+        Err(_) => Err(Error::status_code(status, &text)),
+    }
+}
+
+/// Decodes a response body into the requested data object
+/// 
+/// A FRED error message is returned as an `Error::Api`.  Any other body that cannot be decoded is returned as an `Error::Decode`.
+pub(crate) fn parse<T: DeserializeOwned>(endpoint: &str, text: &str) -> Result<T, Error> {
+    match serde_json::from_str(text) {
+        Ok(val) => Ok(val),
+        Err(e) => match serde_json::from_str::<FredError>(text) {
+            Ok(err) => Err(Error::from(err)),
+            Err(_) => Err(Error::decode(endpoint, e, text)),
+        },
+    }
+}
+
+// ----------------------------------------------------------------------
+// Series
+
+pub(crate) fn series(
+    series_id: &str,
+    builder: Option<series::Builder>
+) -> Request {
+    let request = Request::new("series")
+        .argument("series_id", series_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_categories(
+    series_id: &str,
+    builder: Option<series::categories::Builder>
+) -> Request {
+    let request = Request::new("series/categories")
+        .argument("series_id", series_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_observation(
+    series_id: &str,
+    builder: Option<series::observation::Builder>
+) -> Request {
+    let request = Request::new("series/observations")
+        .argument("series_id", series_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_observation_vintages(
+    series_id: &str,
+    builder: Option<series::observation::Builder>
+) -> Request {
+    let request = series_observation(series_id, builder);
+
+    match request.option_value("output_type") {
+        Some(_) => request,
+        None => {
+            let options = format!("{}&output_type=2", request.options);
+            request.options(options)
+        },
+    }
+}
+
+pub(crate) fn series_observation_realtime(
+    series_id: &str,
+    realtime_start: NaiveDate,
+    realtime_end: NaiveDate,
+    builder: Option<series::observation::Builder>
+) -> Request {
+    series_observation(series_id, builder).with_options(&[
+        ("realtime_start", realtime_start.format("%Y-%m-%d").to_string()),
+        ("realtime_end", realtime_end.format("%Y-%m-%d").to_string()),
+        ("output_type", String::from("1")),
+    ])
+}
+
+pub(crate) fn series_release(
+    series_id: &str,
+    builder: Option<series::release::Builder>
+) -> Request {
+    let request = Request::new("series/release")
+        .argument("series_id", series_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_tags(
+    series_id: &str,
+    builder: Option<series::tags::Builder>
+) -> Request {
+    let request = Request::new("series/tags")
+        .argument("series_id", series_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_updates(
+    builder: Option<series::updates::Builder>
+) -> Request {
+    let request = Request::new("series/updates");
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_vintagedates(
+    series_id: &str,
+    builder: Option<series::vintagedates::Builder>
+) -> Request {
+    let request = Request::new("series/vintagedates")
+        .argument("series_id", series_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+// ----------------------------------------------------------------------
+// Series/Search
+
+pub(crate) fn series_search(
+    search_text: &str,
+    builder: Option<series::search::Builder>
+) -> Request {
+    let request = Request::new("series/search")
+        .argument("search_text", search_text.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_search_tags(
+    series_search_text: &str,
+    builder: Option<series::search::tags::Builder>
+) -> Request {
+    let request = Request::new("series/search/tags")
+        .argument("series_search_text", series_search_text.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn series_search_related_tags(
+    series_search_text: &str,
+    builder: series::search::related_tags::Builder
+) -> Result<Request, Error> {
+    let request = Request::new("series/search/related_tags")
+        .argument("series_search_text", series_search_text.to_string());
+
+    match builder.build() {
+        Ok(opt) => Ok(request.options(opt)),
+        Err(msg) => Err(Error::Builder(msg)),
+    }
+}
+
+// ----------------------------------------------------------------------
+// Tags
+
+pub(crate) fn tags(builder: Option<tags::Builder>) -> Request {
+    let request = Request::new("tags");
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn tags_series(
+    builder: tags::series::Builder
+) -> Result<Request, Error> {
+    let request = Request::new("tags/series");
+
+    match builder.build() {
+        Ok(opt) => Ok(request.options(opt)),
+        Err(msg) => Err(Error::Builder(msg)),
+    }
+}
+
+// ----------------------------------------------------------------------
+// Related Tags
+
+pub(crate) fn related_tags(
+    builder: related_tags::Builder
+) -> Result<Request, Error> {
+    let request = Request::new("related_tags");
+
+    match builder.build() {
+        Ok(opt) => Ok(request.options(opt)),
+        Err(msg) => Err(Error::Builder(msg)),
+    }
+}
+
+// ----------------------------------------------------------------------
+// Sources
+
+pub(crate) fn sources(builder: Option<sources::Builder>) -> Request {
+    let request = Request::new("sources");
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+// ----------------------------------------------------------------------
+// Source
+
+pub(crate) fn source(
+    source_id: usize,
+    builder: Option<source::Builder>
+) -> Request {
+    let request = Request::new("source")
+        .argument("source_id", source_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn source_releases(
+    source_id: usize,
+    builder: Option<source::releases::Builder>
+) -> Request {
+    let request = Request::new("source/releases")
+        .argument("source_id", source_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+// ----------------------------------------------------------------------
+// Category
+
+pub(crate) fn category(category_id: usize) -> Request {
+    Request::new("category")
+        .argument("category_id", category_id.to_string())
+}
+
+pub(crate) fn category_children(
+    category_id: usize,
+    builder: Option<category::children::Builder>
+) -> Request {
+    let request = Request::new("category/children")
+        .argument("category_id", category_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn category_related(
+    category_id: usize,
+    builder: Option<category::related::Builder>
+) -> Request {
+    let request = Request::new("category/related")
+        .argument("category_id", category_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn category_series(
+    category_id: usize,
+    builder: Option<category::series::Builder>
+) -> Request {
+    let request = Request::new("category/series")
+        .argument("category_id", category_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn category_tags(
+    category_id: usize,
+    builder: Option<category::tags::Builder>
+) -> Request {
+    let request = Request::new("category/tags")
+        .argument("category_id", category_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn category_related_tags(
+    category_id: usize,
+    builder: category::related_tags::Builder
+) -> Result<Request, Error> {
+    let request = Request::new("category/related_tags")
+        .argument("category_id", category_id.to_string());
+
+    match builder.build() {
+        Ok(opt) => Ok(request.options(opt)),
+        Err(msg) => Err(Error::Builder(msg)),
+    }
+}
+
+// ----------------------------------------------------------------------
+// Releases
+
+pub(crate) fn releases(builder: Option<releases::Builder>) -> Request {
+    let request = Request::new("releases");
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn releases_dates(
+    builder: Option<releases::dates::Builder>
+) -> Request {
+    let request = Request::new("releases/dates");
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+/// The next date of a release, starting today
+pub(crate) fn upcoming_release_dates(release_id: usize, today: NaiveDate) -> Request {
+    let mut builder = release::dates::Builder::new();
+    builder
+        .realtime_start(&today.format("%Y-%m-%d").to_string())
+        .limit(1)
+        .sort_order(release::dates::SortOrder::Ascending)
+        .include_release_dates_with_no_data();
+
+    release_dates(release_id, Some(builder))
+}
+
+// ----------------------------------------------------------------------
+// Release
+
+pub(crate) fn release(
+    release_id: usize,
+    builder: Option<release::Builder>
+) -> Request {
+    let request = Request::new("release")
+        .argument("release_id", release_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn release_series(
+    release_id: usize,
+    builder: Option<release::series::Builder>
+) -> Request {
+    let request = Request::new("release/series")
+        .argument("release_id", release_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn release_dates(
+    release_id: usize,
+    builder: Option<release::dates::Builder>
+) -> Request {
+    let request = Request::new("release/dates")
+        .argument("release_id", release_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn release_sources(
+    release_id: usize,
+    builder: Option<release::sources::Builder>
+) -> Request {
+    let request = Request::new("release/sources")
+        .argument("release_id", release_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn release_tags(
+    release_id: usize,
+    builder: Option<release::tags::Builder>
+) -> Request {
+    let request = Request::new("release/tags")
+        .argument("release_id", release_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+pub(crate) fn release_related_tags(
+    release_id: usize,
+    builder: release::related_tags::Builder
+) -> Result<Request, Error> {
+    let request = Request::new("release/related_tags")
+        .argument("release_id", release_id.to_string());
+
+    match builder.build() {
+        Ok(opt) => Ok(request.options(opt)),
+        Err(msg) => Err(Error::Builder(msg)),
+    }
+}
+
+pub(crate) fn release_tables(
+    release_id: usize,
+    builder: Option<release::tables::Builder>
+) -> Request {
+    let request = Request::new("release/tables")
+        .argument("release_id", release_id.to_string());
+
+    match builder {
+        Some(b) => request.options(b.build()),
+        None => request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_url_with_argument_and_options() {
+        let mut builder = series::observation::Builder::new();
+        builder
+            .limit(5)
+            .vintage_date("2020-01-01")
+            .vintage_date("2020-02-01");
+
+        let req = series_observation("GNPCA", Some(builder));
+        assert_eq!(
+            req.url(FRED_BASE_URL, "abc"),
+            "https://api.stlouisfed.org/fred/series/observations?series_id=GNPCA&api_key=abc&file_type=json&limit=5&vintage_dates=2020-01-01,2020-02-01"
+        );
+    }
+
+    #[test]
+    fn request_redacted_url() {
+        let mut builder = series::Builder::new();
+        builder.realtime_start("2000-01-01");
+
+        let req = series("UNRATE", Some(builder));
+        assert_eq!(req.argument_value("series_id"), Some("UNRATE"));
+        assert_eq!(req.argument_value("category_id"), None);
+        assert_eq!(
+            req.redacted_url(FRED_BASE_URL),
+            "https://api.stlouisfed.org/fred/series?series_id=UNRATE&file_type=json&realtime_start=2000-01-01"
+        );
+    }
+
+    #[test]
+    fn request_url_without_argument() {
+        let req = releases_dates(None);
+        assert_eq!(
+            req.url("http://localhost/fred/", "abc"),
+            "http://localhost/fred/releases/dates?api_key=abc&file_type=json"
+        );
+    }
+
+    #[test]
+    fn request_upcoming_release_dates() {
+        let req = upcoming_release_dates(50, NaiveDate::from_ymd_opt(2020, 3, 6).unwrap());
+        assert_eq!(
+            req.redacted_url("http://localhost/fred/"),
+            "http://localhost/fred/release/dates?release_id=50&file_type=json&realtime_start=2020-03-06&limit=1&include_release_dates_with_no_data=true"
+        );
+    }
+
+    #[test]
+    fn request_with_page() {
+        let mut builder = release::series::Builder::new();
+        builder
+            .limit(5)
+            .offset(10)
+            .realtime_start("2000-01-01");
+        let request = release_series(53, Some(builder));
+        assert_eq!(request.option_value("limit"), Some("5"));
+        assert_eq!(request.option_value("offset"), Some("10"));
+
+        assert_eq!(
+            request.with_page(15, 5).redacted_url(FRED_BASE_URL),
+            "https://api.stlouisfed.org/fred/release/series?release_id=53&file_type=json&realtime_start=2000-01-01&limit=5&offset=15"
+        );
+    }
+
+    #[test]
+    fn request_builder_error() {
+        match related_tags(related_tags::Builder::new()) {
+            Err(Error::Builder(msg)) => assert_eq!(msg.as_str(), error::TAG_NAME_REQUIRED_ERROR_TEXT),
+            _ => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn parse_fred_error() {
+        let text = r#"{"error_code":400,"error_message":"Bad Request.  Variable api_key is not set."}"#;
+        match check_status(400, String::from(text)) {
+            Err(e) => {
+                assert!(e.is_bad_parameter());
+                assert_eq!(e.to_string(), "ERROR 400: Bad Request.  Variable api_key is not set.");
+            },
+            Ok(_) => assert_eq!(2, 1),
+        }
+    }
+
+    #[test]
+    fn parse_unexpected_body() {
+        match parse::<category::Response>("category", "{\"categories\": 5}") {
+            Err(Error::Decode { endpoint, snippet, .. }) => {
+                assert_eq!(endpoint.as_str(), "category");
+                assert_eq!(snippet.as_str(), "{\"categories\": 5}");
+            },
+            _ => assert_eq!(2, 1),
+        }
+
+        match check_status(502, String::from("<html>Bad Gateway</html>")) {
+            Err(Error::Status { status, .. }) => assert_eq!(status, 502),
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
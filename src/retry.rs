@@ -0,0 +1,262 @@
+//! Automatic retries for transient request failures
+//!
+//! FRED occasionally answers with status 429, 500, 502 or 503, and requests sometimes time out.  A [RetryPolicy](struct.RetryPolicy.html) attached to a client resends failed requests after an exponentially increasing delay.  When the server sends a `Retry-After` header (in seconds) that delay is used instead, capped at the policy's maximum delay.
+//!
+//! If the request still fails, the returned error is an `Error::Retried` holding the number of attempts and the last error.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::retry::RetryPolicy;
+//! use std::time::Duration;
+//!
+//! let mut policy = RetryPolicy::new();
+//! policy
+//!     .max_attempts(5)
+//!     .base_delay(Duration::from_millis(250))
+//!     .max_delay(Duration::from_secs(10));
+//!
+//! let client = match FredClient::builder().retry(policy).build() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Error, TransportErrorKind};
+
+#[derive(Clone, Debug)]
+/// Describes when and how often a failed request is resent
+pub struct RetryPolicy {
+    max_attempts: usize,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    statuses: Vec<u16>,
+    transport_kinds: Vec<TransportErrorKind>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy with the default settings
+    ///
+    /// Requests are attempted at most 3 times.  The delay starts at 500 ms, doubles after each attempt up to 30 s and is randomized with jitter.  Statuses 429, 500, 502, 503 and 504 are retried, as are timeouts and connection failures.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            statuses: vec![429, 500, 502, 503, 504],
+            transport_kinds: vec![TransportErrorKind::Timeout, TransportErrorKind::Connect],
+        }
+    }
+
+    /// Sets the maximum number of attempts, including the first request
+    ///
+    /// # Arguments
+    /// * `attempts` - total number of attempts (minimum 1)
+    pub fn max_attempts(&mut self, attempts: usize) -> &mut RetryPolicy {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry
+    ///
+    /// The delay doubles for each following retry.
+    ///
+    /// # Arguments
+    /// * `delay` - initial delay
+    pub fn base_delay(&mut self, delay: Duration) -> &mut RetryPolicy {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Sets the upper bound for the exponential delay
+    ///
+    /// Delays requested with a Retry-After header are capped at the same bound.
+    ///
+    /// # Arguments
+    /// * `delay` - maximum delay between attempts
+    pub fn max_delay(&mut self, delay: Duration) -> &mut RetryPolicy {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Enables or disables jitter
+    ///
+    /// With jitter each delay is picked randomly between half and all of the exponential delay, so clients that failed together do not retry together.
+    ///
+    /// # Arguments
+    /// * `enable` - true to randomize delays
+    pub fn jitter(&mut self, enable: bool) -> &mut RetryPolicy {
+        self.jitter = enable;
+        self
+    }
+
+    /// Replaces the HTTP statuses that are retried
+    ///
+    /// # Arguments
+    /// * `statuses` - status codes to retry (e.g. `&[429, 503]`)
+    pub fn retry_statuses(&mut self, statuses: &[u16]) -> &mut RetryPolicy {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    /// Replaces the kinds of transport failure that are retried
+    ///
+    /// # Arguments
+    /// * `kinds` - transport failures to retry
+    pub fn retry_transport_errors(&mut self, kinds: &[TransportErrorKind]) -> &mut RetryPolicy {
+        self.transport_kinds = kinds.to_vec();
+        self
+    }
+
+    /// Returns true if the error may succeed when the request is resent
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        match error {
+            Error::Transport { kind, .. } => self.transport_kinds.contains(kind),
+            Error::Status { status, .. } => self.statuses.contains(status),
+            Error::Api { code, .. } => self.statuses.iter().any(|s| usize::from(*s) == *code),
+            _ => false,
+        }
+    }
+
+    /// Returns the delay before the next attempt, or None if the request should not be retried
+    ///
+    /// # Arguments
+    /// * `attempt` - number of attempts made so far (starting at 1)
+    /// * `error` - the error returned by the last attempt
+    /// * `retry_after` - the Retry-After delay sent by the server, if any
+    pub(crate) fn next_delay(
+        &self,
+        attempt: usize,
+        error: &Error,
+        retry_after: Option<Duration>
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.is_retryable(error) {
+            return None;
+        }
+        if let Some(delay) = retry_after {
+            return Some(delay.min(self.max_delay));
+        }
+
+        let exp = (attempt - 1).min(31) as u32;
+        let delay = self.base_delay
+            .checked_mul(1 << exp)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        if self.jitter {
+            Some(delay / 2 + delay.mul_f64(random_fraction() / 2.0))
+        } else {
+            Some(delay)
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::new()
+    }
+}
+
+/// Parses a Retry-After header given in seconds
+///
+/// HTTP-date values are not supported and are ignored.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => None,
+    }
+}
+
+/// Returns a pseudo-random number in [0, 1)
+///
+/// The quality only needs to be good enough to spread out retries, so a xorshift generator seeded from the clock avoids an extra dependency.
+fn random_fraction() -> f64 {
+    static STATE: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_nanos() as u64,
+        Err(_) => 0,
+    };
+    let mut x = STATE.fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed) ^ nanos;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unavailable() -> Error {
+        Error::Status { status: 503, message: String::from("Service Unavailable") }
+    }
+
+    #[test]
+    fn retry_policy_exponential_delay() {
+        let mut policy = RetryPolicy::new();
+        policy
+            .max_attempts(4)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(250))
+            .jitter(false);
+
+        assert_eq!(policy.next_delay(1, &unavailable(), None), Some(Duration::from_millis(100)));
+        assert_eq!(policy.next_delay(2, &unavailable(), None), Some(Duration::from_millis(200)));
+        assert_eq!(policy.next_delay(3, &unavailable(), None), Some(Duration::from_millis(250)));
+        assert_eq!(policy.next_delay(4, &unavailable(), None), None);
+    }
+
+    #[test]
+    fn retry_policy_jitter_and_retry_after() {
+        let mut policy = RetryPolicy::new();
+        policy.base_delay(Duration::from_millis(100));
+
+        for _ in 0..100 {
+            let delay = policy.next_delay(1, &unavailable(), None).unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+
+        let retry_after = parse_retry_after(" 7 ");
+        assert_eq!(policy.next_delay(1, &unavailable(), retry_after), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn retry_policy_caps_retry_after() {
+        let mut policy = RetryPolicy::new();
+        policy.max_delay(Duration::from_secs(10));
+
+        let retry_after = parse_retry_after("86400");
+        assert_eq!(policy.next_delay(1, &unavailable(), retry_after), Some(Duration::from_secs(10)));
+        assert_eq!(policy.next_delay(3, &unavailable(), retry_after), None);
+    }
+
+    #[test]
+    fn retry_policy_retryable_errors() {
+        let mut policy = RetryPolicy::new();
+        let bad_param = Error::Api { code: 400, message: String::from("Bad Request.") };
+        let limited = Error::Api { code: 429, message: String::from("Too Many Requests.") };
+        let timeout = Error::Transport { kind: TransportErrorKind::Timeout, message: String::new() };
+
+        assert!(!policy.is_retryable(&bad_param));
+        assert!(policy.is_retryable(&limited));
+        assert!(policy.is_retryable(&timeout));
+        assert!(!policy.is_retryable(&Error::Builder(String::new())));
+
+        policy
+            .retry_statuses(&[503])
+            .retry_transport_errors(&[]);
+        assert!(!policy.is_retryable(&limited));
+        assert!(!policy.is_retryable(&timeout));
+        assert!(policy.is_retryable(&unavailable()));
+    }
+}
@@ -0,0 +1,355 @@
+//! Revision history of a series across ALFRED vintages
+//!
+//! For each observation date a [DateRevisions](struct.DateRevisions.html) lists every published value in order: the initial release followed by each revision.  From it come the latest value, the number of revisions, the mean and mean absolute revision and the time from the initial release to the final value.  [Revisions](struct.Revisions.html) holds the history of every date and summarizes it.
+//!
+//! The history is built from either layout of vintage data:
+//! * realtime periods (output type 1), where each period with a new value is a release
+//! * a vintage matrix (output types 2 and 3), where each vintage that changes the value is a release; `FredClient::series_revisions()` fetches the vintage dates of a series and downloads its vintage matrix in chunks, paging each one
+//!
+//! `CsvWriter::write_revisions()` exports the per-date statistics as a table.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let revisions = match c.series_revisions("GDPC1", None) {
+//!     Ok(r) => r,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! for d in revisions.dates() {
+//!     println!("{}: {} revisions, {:?} in total", d.date, d.revision_count(), d.total_revision());
+//! }
+//! println!("{:?}", revisions.summary());
+//! ```
+
+use chrono::NaiveDate;
+
+use crate::realtime::RealtimeObservations;
+use crate::series::vintage::VintageMatrix;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A value published for an observation date
+pub struct Release {
+    /// The vintage date on which the value was published
+    pub vintage_date: NaiveDate,
+    /// The published value, or None if it was published as missing
+    pub value: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Every value published for one observation date
+pub struct DateRevisions {
+    /// The observation date
+    pub date: NaiveDate,
+    /// The initial release followed by each revision, in vintage order
+    pub releases: Vec<Release>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// Revision statistics over every observation date
+pub struct RevisionSummary {
+    /// Number of observation dates
+    pub observations: usize,
+    /// Number of observation dates revised at least once
+    pub revised: usize,
+    /// Total number of revisions
+    pub revisions: usize,
+    /// Mean of the latest value minus the initial value
+    pub mean_total_revision: Option<f64>,
+    /// Mean absolute difference between the latest and the initial value
+    pub mean_absolute_total_revision: Option<f64>,
+    /// Mean number of days from the initial release to the final value, over revised dates
+    pub mean_days_to_final: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// The revision history of every observation date of a series
+pub struct Revisions {
+    dates: Vec<DateRevisions>,
+}
+
+impl DateRevisions {
+    /// Returns the first published value
+    pub fn initial(&self) -> Option<&Release> {
+        self.releases.first()
+    }
+
+    /// Returns the most recently published value
+    pub fn latest(&self) -> Option<&Release> {
+        self.releases.last()
+    }
+
+    /// Returns the number of times the value was revised after the initial release
+    pub fn revision_count(&self) -> usize {
+        self.releases.len().saturating_sub(1)
+    }
+
+    /// Returns the size of each revision (new value minus previous value)
+    ///
+    /// Revisions from or to a missing value have no size and are left out.
+    pub fn revision_sizes(&self) -> Vec<f64> {
+        self.releases.windows(2)
+            .filter_map(|pair| match (pair[0].value, pair[1].value) {
+                (Some(before), Some(after)) => Some(after - before),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the mean revision size
+    pub fn mean_revision(&self) -> Option<f64> {
+        mean(&self.revision_sizes())
+    }
+
+    /// Returns the mean absolute revision size
+    pub fn mean_absolute_revision(&self) -> Option<f64> {
+        let sizes: Vec<f64> = self.revision_sizes().iter().map(|s| s.abs()).collect();
+        mean(&sizes)
+    }
+
+    /// Returns the latest value minus the initial value
+    pub fn total_revision(&self) -> Option<f64> {
+        match (self.initial()?.value, self.latest()?.value) {
+            (Some(initial), Some(latest)) => Some(latest - initial),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of days from the initial release to the publication of the latest value
+    ///
+    /// This is 0 for a date that was never revised.
+    pub fn days_to_final(&self) -> Option<i64> {
+        Some((self.latest()?.vintage_date - self.initial()?.vintage_date).num_days())
+    }
+}
+
+impl Revisions {
+    /// Creates the revision history from realtime periods
+    ///
+    /// Each realtime period is a release on its realtime_start.  Consecutive periods with the same value count as one release.  To see the true initial release the periods must cover the realtime range from the first vintage of the series.
+    ///
+    /// # Arguments
+    /// * `history` - realtime periods from a real-time mode download
+    pub fn from_realtime(history: &RealtimeObservations) -> Revisions {
+        let mut dates: Vec<DateRevisions> = Vec::new();
+        for o in history.observations() {
+            let release = Release {
+                vintage_date: o.realtime_start,
+                value: o.value,
+            };
+            match dates.last_mut() {
+                Some(d) if d.date == o.date => push_release(d, release),
+                _ => dates.push(DateRevisions { date: o.date, releases: vec![release] }),
+            }
+        }
+        Revisions { dates }
+    }
+
+    /// Creates the revision history from a vintage matrix
+    ///
+    /// Each vintage whose value differs from the previous vintage is a release.  Missing cells are skipped, so vintages published before a date was observed and the unchanged cells of output type 3 (VDNEW) are not releases.  Columns without a vintage date are ignored.
+    ///
+    /// # Arguments
+    /// * `matrix` - a vintage matrix from an output type 2 or 3 download
+    pub fn from_vintages(matrix: &VintageMatrix) -> Revisions {
+        let vintages: Vec<(usize, NaiveDate)> = matrix.columns().iter()
+            .enumerate()
+            .filter_map(|(idx, c)| c.vintage_date.map(|v| (idx, v)))
+            .collect();
+
+        let mut dates = Vec::with_capacity(matrix.dates().len());
+        for (row, date) in matrix.dates().iter().enumerate() {
+            let cells = matrix.row(row).unwrap_or_default();
+            let mut revisions = DateRevisions { date: *date, releases: Vec::new() };
+            for (col, vintage_date) in &vintages {
+                if let Some(value) = cells[*col] {
+                    push_release(&mut revisions, Release { vintage_date: *vintage_date, value: Some(value) });
+                }
+            }
+            if !revisions.releases.is_empty() {
+                dates.push(revisions);
+            }
+        }
+        Revisions { dates }
+    }
+
+    /// Returns the revision history of each observation date in date order
+    pub fn dates(&self) -> &[DateRevisions] {
+        &self.dates
+    }
+
+    /// Returns the revision history of an observation date
+    ///
+    /// # Arguments
+    /// * `date` - the observation date
+    pub fn get(&self, date: NaiveDate) -> Option<&DateRevisions> {
+        match self.dates.binary_search_by_key(&date, |d| d.date) {
+            Ok(idx) => Some(&self.dates[idx]),
+            Err(_) => None,
+        }
+    }
+
+    /// Summarizes the revisions of every observation date
+    pub fn summary(&self) -> RevisionSummary {
+        let totals: Vec<f64> = self.dates.iter().filter_map(DateRevisions::total_revision).collect();
+        let absolute: Vec<f64> = totals.iter().map(|t| t.abs()).collect();
+        let days: Vec<f64> = self.dates.iter()
+            .filter(|d| d.revision_count() > 0)
+            .filter_map(|d| d.days_to_final().map(|days| days as f64))
+            .collect();
+
+        RevisionSummary {
+            observations: self.dates.len(),
+            revised: self.dates.iter().filter(|d| d.revision_count() > 0).count(),
+            revisions: self.dates.iter().map(DateRevisions::revision_count).sum(),
+            mean_total_revision: mean(&totals),
+            mean_absolute_total_revision: mean(&absolute),
+            mean_days_to_final: mean(&days),
+        }
+    }
+}
+
+/// Appends a release unless it repeats the latest value
+fn push_release(revisions: &mut DateRevisions, release: Release) {
+    match revisions.releases.last() {
+        Some(last) if last.value == release.value => (),
+        _ => revisions.releases.push(release),
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    match values.len() {
+        0 => None,
+        n => Some(values.iter().sum::<f64>() / n as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::series::observation::{Builder, Observation};
+    use crate::series::vintage::Response;
+    use crate::transport::{page_body, test_client, MemoryTransport};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn period(start: NaiveDate, end: NaiveDate, obs: NaiveDate, value: Option<f64>) -> Observation {
+        Observation { realtime_start: start, realtime_end: end, date: obs, value }
+    }
+
+    #[test]
+    fn revisions_from_realtime() {
+        let end = date(9999, 12, 31);
+        let history = RealtimeObservations::new(vec![
+            period(date(2020, 4, 29), date(2020, 5, 27), date(2020, 1, 1), Some(100.0)),
+            period(date(2020, 5, 28), date(2020, 6, 24), date(2020, 1, 1), Some(102.0)),
+            period(date(2020, 6, 25), date(2020, 7, 29), date(2020, 1, 1), Some(102.0)),
+            period(date(2020, 7, 30), end, date(2020, 1, 1), Some(101.0)),
+            period(date(2020, 7, 30), end, date(2020, 4, 1), Some(90.0)),
+        ]);
+        let revisions = Revisions::from_realtime(&history);
+
+        let q1 = revisions.get(date(2020, 1, 1)).unwrap();
+        assert_eq!(q1.releases.len(), 3);
+        assert_eq!(q1.initial().unwrap().value, Some(100.0));
+        assert_eq!(q1.latest().unwrap().vintage_date, date(2020, 7, 30));
+        assert_eq!(q1.revision_count(), 2);
+        assert_eq!(q1.revision_sizes(), vec![2.0, -1.0]);
+        assert_eq!(q1.mean_revision(), Some(0.5));
+        assert_eq!(q1.mean_absolute_revision(), Some(1.5));
+        assert_eq!(q1.total_revision(), Some(1.0));
+        assert_eq!(q1.days_to_final(), Some(92));
+
+        let q2 = revisions.get(date(2020, 4, 1)).unwrap();
+        assert_eq!(q2.revision_count(), 0);
+        assert_eq!(q2.mean_revision(), None);
+        assert_eq!(q2.days_to_final(), Some(0));
+
+        assert_eq!(revisions.summary(), RevisionSummary {
+            observations: 2,
+            revised: 1,
+            revisions: 2,
+            mean_total_revision: Some(0.5),
+            mean_absolute_total_revision: Some(0.5),
+            mean_days_to_final: Some(92.0),
+        });
+    }
+
+    #[test]
+    fn revisions_from_vintages() {
+        let resp: Response = serde_json::from_str(r#"{"realtime_start":"2020-04-29","realtime_end":"2020-07-30","observation_start":"1776-07-04","observation_end":"9999-12-31",
+            "units":"lin","output_type":3,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":2,"offset":0,"limit":100000,
+            "observations":[
+                {"date":"2020-01-01","GDPC1_20200429":"100.0","GDPC1_20200528":"102.0","GDPC1_20200730":"101.0"},
+                {"date":"2020-04-01","GDPC1_20200730":"90.0"}
+            ]}"#).unwrap();
+        let revisions = Revisions::from_vintages(&resp.matrix().unwrap());
+
+        assert_eq!(revisions.dates().len(), 2);
+        let q1 = &revisions.dates()[0];
+        assert_eq!(q1.releases, vec![
+            Release { vintage_date: date(2020, 4, 29), value: Some(100.0) },
+            Release { vintage_date: date(2020, 5, 28), value: Some(102.0) },
+            Release { vintage_date: date(2020, 7, 30), value: Some(101.0) },
+        ]);
+        assert_eq!(revisions.dates()[1].initial().unwrap().vintage_date, date(2020, 7, 30));
+    }
+
+    #[test]
+    fn series_revisions_pages_vintages() {
+        let vintagedates = r#"{"realtime_start":"1776-07-04","realtime_end":"9999-12-31","order_by":"vintage_date","sort_order":"asc","count":3,"offset":0,
+            "limit":10000,"vintage_dates":["2020-04-29","2020-05-28","2020-07-30"]}"#;
+        let q1 = r#"{"date":"2020-01-01","GDPC1_20200429":"100.0","GDPC1_20200528":"102.0","GDPC1_20200730":"101.0"}"#;
+        let q2 = r#"{"date":"2020-04-01","GDPC1_20200429":".","GDPC1_20200528":".","GDPC1_20200730":"90.0"}"#;
+        let vintages = "vintage_dates=2020-04-29,2020-05-28,2020-07-30&output_type=2&limit=1";
+
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=GDPC1&*offset=0", vintagedates)
+            .route(&format!("{}&offset=0", vintages), &page_body("observations", ("2020-04-29", "2020-07-30"), 2, 0, &[q1]))
+            .route(&format!("{}&offset=1", vintages), &page_body("observations", ("2020-04-29", "2020-07-30"), 2, 1, &[q2]));
+        let c = test_client(&transport);
+
+        // realtime arguments set by the builder are replaced
+        let mut builder = Builder::new();
+        builder.limit(1).realtime_start("1999-01-01");
+        let revisions = c.series_revisions("GDPC1", Some(builder)).unwrap();
+        assert_eq!(transport.requests().len(), 3);
+        assert!(transport.requests().iter().all(|r| !r.contains("1999-01-01")));
+        assert_eq!(revisions.dates().len(), 2);
+        assert_eq!(revisions.dates()[0].revision_count(), 2);
+        assert_eq!(revisions.dates()[1].initial().unwrap().vintage_date, date(2020, 7, 30));
+
+        // a vintage page that comes back empty before the reported count fails
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/vintagedates?series_id=GDPC1&*offset=0", vintagedates)
+            .route(&format!("{}&offset=0", vintages), &page_body("observations", ("2020-04-29", "2020-07-30"), 2, 0, &[q1]))
+            .route(&format!("{}&offset=1", vintages), &page_body::<&str>("observations", ("2020-04-29", "2020-07-30"), 2, 1, &[]));
+        let c = test_client(&transport);
+
+        let mut builder = Builder::new();
+        builder.limit(1);
+        match c.series_revisions("GDPC1", Some(builder)) {
+            Err(Error::Truncated { count, received, .. }) => {
+                assert_eq!(count, 2);
+                assert_eq!(received, 1);
+            },
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
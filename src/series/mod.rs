@@ -45,6 +45,7 @@ pub mod tags;
 pub mod search;
 pub mod updates;
 pub mod vintagedates;
+pub mod vintage;
 
 // ----------------------------------------------------------------------------
 use serde::Deserialize;
@@ -4,7 +4,7 @@
 //! 
 //! ```
 //! use fred_rs::client::FredClient;
-//! use fred_rs::series::observation::{Builder, Units, Frequency, Response};
+//! use fred_rs::series::observation::{Builder, Units, Frequency, Response, Observation};
 //! 
 //! // Create the client object
 //! let mut c = match FredClient::new() {
@@ -32,11 +32,34 @@
 //!         return
 //!     },
 //! };
+//! 
+//! // Convert the data points to dates and numbers, skipping missing values
+//! for obs in resp.typed_observations() {
+//!     match obs {
+//!         Ok(Observation { date, value: Some(v), .. }) => println!("{}: {}", date, v),
+//!         Ok(_) => (),
+//!         Err(msg) => println!("{}", msg),
+//!     }
+//! }
 //! ```
 
+use chrono::NaiveDate;
 use serde::Deserialize;
 use std::fmt::{self, Display, Formatter};
 
+use crate::error::Error;
+
+/// The value FRED returns for a missing observation
+pub const MISSING_VALUE: &str = ".";
+
+/// Parses a date formatted as YYYY-MM-DD
+pub(crate) fn parse_date(field: &str, text: &str) -> Result<NaiveDate, Error> {
+    match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        Ok(d) => Ok(d),
+        Err(_) => Err(Error::parse_field(field, text)),
+    }
+}
+
 #[derive(Deserialize, Clone, Debug, Default)]
 /// Response data structure for the fred/series/observation endpoint
 /// 
@@ -70,6 +93,25 @@ pub struct Response {
     pub observations: Vec<DataPoint>,
 }
 
+impl Response {
+    /// Converts every data point to an [Observation](struct.Observation.html)
+    /// 
+    /// Each point is converted separately, so a malformed point does not prevent the others from being read.
+    pub fn typed_observations(&self) -> Vec<Result<Observation, Error>> {
+        self.observations.iter().map(DataPoint::to_observation).collect()
+    }
+
+    /// Returns the start of the observation period as a date
+    pub fn observation_start_date(&self) -> Result<NaiveDate, Error> {
+        parse_date("observation_start", &self.observation_start)
+    }
+
+    /// Returns the end of the observation period as a date
+    pub fn observation_end_date(&self) -> Result<NaiveDate, Error> {
+        parse_date("observation_end", &self.observation_end)
+    }
+}
+
 impl Display for Response {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         for item in self.observations.iter() {
@@ -99,12 +141,53 @@ pub struct DataPoint {
     pub value: String,
 }
 
+impl DataPoint {
+    /// Returns the date of the data point
+    pub fn parsed_date(&self) -> Result<NaiveDate, Error> {
+        parse_date("date", &self.date)
+    }
+
+    /// Returns the value of the data point, or None if FRED reports it as missing (".")
+    pub fn parsed_value(&self) -> Result<Option<f64>, Error> {
+        if self.value == MISSING_VALUE {
+            return Ok(None);
+        }
+        match self.value.parse::<f64>() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(Error::parse_field("value", &self.value)),
+        }
+    }
+
+    /// Converts the data point to an [Observation](struct.Observation.html)
+    pub fn to_observation(&self) -> Result<Observation, Error> {
+        Ok(Observation {
+            realtime_start: parse_date("realtime_start", &self.realtime_start)?,
+            realtime_end: parse_date("realtime_end", &self.realtime_end)?,
+            date: self.parsed_date()?,
+            value: self.parsed_value()?,
+        })
+    }
+}
+
 impl Display for DataPoint {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "({}: {})", self.date, self.value)
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A data point with parsed dates and value
+pub struct Observation {
+    /// The first day the value was valid
+    pub realtime_start: NaiveDate,
+    /// The last day the value was valid
+    pub realtime_end: NaiveDate,
+    /// Date of the data point
+    pub date: NaiveDate,
+    /// The value, or None if it is missing
+    pub value: Option<f64>,
+}
+
 /// Sort order options for the fred/series/observation endpoint
 /// 
 /// [https://research.stlouisfed.org/docs/api/fred/series_observations.html#sort_order](https://research.stlouisfed.org/docs/api/fred/series_observations.html#sort_order)
@@ -183,6 +266,7 @@ pub enum Frequency {
     BWEM,   
 }
 
+#[derive(Clone, Debug, Copy)]
 /// Provides an aggregation method for frequency aggregation
 /// 
 /// This argument should be used in conjunction with the frequency argument if the default aggregation method (AVG) is not preferred.
@@ -214,6 +298,7 @@ pub enum OutputType {
 /// Argument builder for the fred/series/observation endpoint.
 /// 
 /// Each method adds an argument to the builder which can then be passed to the client used to fetch the data to apply the arguments.
+#[derive(Clone, Debug)]
 pub struct Builder {
     option_string: String,
     vintage_dates: String,
@@ -242,6 +327,23 @@ impl Builder {
         }
     }
 
+    /// Returns a copy without the realtime_start, realtime_end, output_type and vintage_dates arguments
+    pub(crate) fn without_realtime(&self) -> Builder {
+        let option_string = self.option_string.split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter(|pair| match pair.split_once('=') {
+                Some((name, _)) => !matches!(name, "realtime_start" | "realtime_end" | "output_type" | "vintage_dates"),
+                None => true,
+            })
+            .map(|pair| format!("&{}", pair))
+            .collect();
+
+        Builder {
+            option_string,
+            vintage_dates: String::new(),
+        }
+    }
+
     /// Returns the current arguments as a URL formatted string
     pub(crate) fn build(mut self) -> String {
         if self.vintage_dates.len() > 0 {
@@ -420,7 +522,7 @@ impl Builder {
                 self.option_string += format!("&frequency=wew").as_str()
             },
             Frequency::WETU => {
-                self.option_string += format!("&frequency=d").as_str()
+                self.option_string += format!("&frequency=wetu").as_str()
             },
             Frequency::WEM => {
                 self.option_string += format!("&frequency=wem").as_str()
@@ -506,6 +608,48 @@ mod tests {
     use super::*;
     use crate::client::FredClient;
 
+    fn point(date: &str, value: &str) -> DataPoint {
+        DataPoint {
+            realtime_start: String::from("2020-02-20"),
+            realtime_end: String::from("9999-12-31"),
+            date: String::from(date),
+            value: String::from(value),
+        }
+    }
+
+    #[test]
+    fn series_observation_builder_frequency() {
+        let mut builder = Builder::new();
+        builder.frequency(Frequency::WETU);
+        assert_eq!(builder.build(), "&frequency=wetu");
+    }
+
+    #[test]
+    fn series_observation_typed_values() {
+        let resp = Response {
+            observation_start: String::from("1600-01-01"),
+            observation_end: String::from("9999-12-31"),
+            observations: vec![
+                point("2019-01-01", "18950.347"),
+                point("2019-04-01", "."),
+                point("2019-07-01", "n/a"),
+                point("2019-13-01", "1"),
+            ],
+            ..Response::default()
+        };
+
+        assert_eq!(resp.observation_start_date(), Ok(NaiveDate::from_ymd_opt(1600, 1, 1).unwrap()));
+        assert_eq!(resp.observation_end_date(), Ok(NaiveDate::from_ymd_opt(9999, 12, 31).unwrap()));
+
+        let obs = resp.typed_observations();
+        assert_eq!(obs.len(), 4);
+        assert_eq!(obs[0].as_ref().unwrap().value, Some(18950.347));
+        assert_eq!(obs[0].as_ref().unwrap().date, NaiveDate::from_ymd_opt(2019, 1, 1).unwrap());
+        assert_eq!(obs[1].as_ref().unwrap().value, None);
+        assert_eq!(obs[2], Err(Error::Parse { field: String::from("value"), value: String::from("n/a") }));
+        assert_eq!(obs[3], Err(Error::Parse { field: String::from("date"), value: String::from("2019-13-01") }));
+    }
+
     #[test]
     fn series_observation_with_options() {
         let c = match FredClient::new() {
@@ -301,6 +301,7 @@ mod tests {
     use super::*;
     use crate::tags::Response;
     use crate::client::FredClient;
+    use crate::Error;
 
     #[test]
     fn series_search_related_tags_with_options_passing() {
@@ -359,7 +360,7 @@ mod tests {
         let _resp: Response = match c.series_search_related_tags("monetary service index", builder) {
             Ok(resp) => resp,
             Err(msg) => {
-                assert_eq!(msg.as_str(), TAG_NAME_REQUIRED_ERROR_TEXT);
+                assert_eq!(msg, Error::Builder(String::from(TAG_NAME_REQUIRED_ERROR_TEXT)));
                 return
             },
         };
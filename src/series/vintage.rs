@@ -0,0 +1,277 @@
+//! Get the observations of a series by vintage date (output types 2, 3 and 4)
+//!
+//! With `OutputType::VDALL`, `VDNEW` or `INITIAL` FRED returns one row per observation date with a column for each vintage, keyed by the series id and the vintage date (e.g. `GNPCA_20090130`).  A [Response](struct.Response.html) keeps that layout and `matrix()` parses it into a [VintageMatrix](struct.VintageMatrix.html) of dates × vintages.
+//!
+//! Use `Builder::vintage_date()` to choose the vintages returned.  When the builder sets no output type `VDALL` is requested.
+//!
+//! [https://research.stlouisfed.org/docs/api/fred/series_observations.html#output_type](https://research.stlouisfed.org/docs/api/fred/series_observations.html#output_type)
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::series::observation::{Builder, OutputType};
+//!
+//! let c = match FredClient::new() {
+//!     Ok(c) => c,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! let mut builder = Builder::new();
+//! builder
+//!     .output_type(OutputType::VDALL)
+//!     .vintage_date("2009-01-30")
+//!     .vintage_date("2019-12-19");
+//!
+//! let matrix = match c.series_observation_vintages("GNPCA", Some(builder)).and_then(|resp| resp.matrix()) {
+//!     Ok(m) => m,
+//!     Err(msg) => {
+//!         println!("{}", msg);
+//!         return
+//!     },
+//! };
+//!
+//! for (idx, date) in matrix.dates().iter().enumerate() {
+//!     println!("{}: {:?}", date, matrix.row(idx));
+//! }
+//! ```
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::series::observation::{parse_date, MISSING_VALUE};
+use crate::timeseries::TimeSeries;
+
+#[derive(Deserialize, Clone, Debug, Default)]
+/// Response data structure for fred/series/observations with output type 2, 3 or 4
+pub struct Response {
+    /// The realtime start of the request
+    pub realtime_start: String,
+    /// The realtime end of the request
+    pub realtime_end: String,
+    /// The start of the observation period
+    pub observation_start: String,
+    /// The end of the observation period
+    pub observation_end: String,
+    /// The units of the observation (e.g. lin)
+    pub units: String,
+    /// The output type (2, 3 or 4)
+    pub output_type: usize,
+    /// The file type (will always be JSON for fred-rs)
+    pub file_type: String,
+    /// On what metric the data are order
+    pub order_by: String,
+    /// Ascending (asc) of descending (desc)
+    pub sort_order: String,
+    /// The number of data items returned
+    pub count: usize,
+    /// The first result returned
+    pub offset: usize,
+    /// The maximum number of results requested
+    pub limit: usize,
+    /// The data rows returned
+    pub observations: Vec<VintagePoint>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+/// One observation date with its value in each vintage
+pub struct VintagePoint {
+    /// Date of the data point
+    pub date: String,
+    /// String encoded values keyed by column name (e.g. GNPCA_20090130); missing values are "." or null
+    #[serde(flatten)]
+    pub values: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A vintage column of a [VintageMatrix](struct.VintageMatrix.html)
+pub struct VintageColumn {
+    /// The column name returned by FRED (e.g. GNPCA_20090130)
+    pub name: String,
+    /// The vintage date encoded in the name, if any
+    pub vintage_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Observation values by date (rows) and vintage (columns)
+pub struct VintageMatrix {
+    dates: Vec<NaiveDate>,
+    columns: Vec<VintageColumn>,
+    /// One vector of cells per date, each the length of `columns`
+    values: Vec<Vec<Option<f64>>>,
+}
+
+impl Response {
+    /// Parses the rows into a date × vintage matrix
+    ///
+    /// Rows are sorted by date and columns by vintage date.  A vintage that has no value for a date (e.g. a date observed after the vintage) is missing.  The first date or value that cannot be parsed is returned as an error.
+    pub fn matrix(&self) -> Result<VintageMatrix, Error> {
+        let mut names: Vec<&String> = self.observations.iter()
+            .flat_map(|p| p.values.keys())
+            .filter(|name| name.as_str() != "realtime_start" && name.as_str() != "realtime_end")
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let mut columns: Vec<VintageColumn> = names.into_iter()
+            .map(|name| VintageColumn {
+                name: name.clone(),
+                vintage_date: vintage_date(name),
+            })
+            .collect();
+        columns.sort_by(|a, b| (a.vintage_date, &a.name).cmp(&(b.vintage_date, &b.name)));
+
+        let mut rows = Vec::with_capacity(self.observations.len());
+        for point in &self.observations {
+            let date = parse_date("date", &point.date)?;
+            let mut cells = Vec::with_capacity(columns.len());
+            for column in &columns {
+                let cell = match point.values.get(&column.name) {
+                    Some(Some(text)) => parse_value(text)?,
+                    _ => None,
+                };
+                cells.push(cell);
+            }
+            rows.push((date, cells));
+        }
+        rows.sort_by_key(|(date, _)| *date);
+
+        let (dates, values) = rows.into_iter().unzip();
+        Ok(VintageMatrix { dates, columns, values })
+    }
+}
+
+impl VintageMatrix {
+    /// Returns the observation dates in ascending order
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    /// Returns the vintage columns in vintage date order
+    pub fn columns(&self) -> &[VintageColumn] {
+        &self.columns
+    }
+
+    /// Returns the vintage dates of the columns
+    pub fn vintage_dates(&self) -> Vec<NaiveDate> {
+        self.columns.iter().filter_map(|c| c.vintage_date).collect()
+    }
+
+    /// Returns the values of a date in every vintage
+    ///
+    /// # Arguments
+    /// * `idx` - the row position
+    pub fn row(&self, idx: usize) -> Option<&[Option<f64>]> {
+        self.values.get(idx).map(|v| v.as_slice())
+    }
+
+    /// Returns the value of a date in a vintage
+    ///
+    /// Returns None if the date or vintage is not in the matrix and Some(None) if the value is missing.
+    ///
+    /// # Arguments
+    /// * `date` - the observation date
+    /// * `vintage_date` - the vintage date
+    pub fn get(&self, date: NaiveDate, vintage_date: NaiveDate) -> Option<Option<f64>> {
+        let row = self.dates.binary_search(&date).ok()?;
+        let col = self.columns.iter().position(|c| c.vintage_date == Some(vintage_date))?;
+        Some(self.values[row][col])
+    }
+
+    /// Returns the data as published in a vintage
+    ///
+    /// # Arguments
+    /// * `vintage_date` - the vintage date
+    pub fn vintage(&self, vintage_date: NaiveDate) -> Option<TimeSeries> {
+        let col = self.columns.iter().position(|c| c.vintage_date == Some(vintage_date))?;
+        Some(self.column_at(col))
+    }
+
+    /// Returns a column by name as a time series
+    ///
+    /// # Arguments
+    /// * `name` - the column name (e.g. GNPCA_20090130)
+    pub fn column(&self, name: &str) -> Option<TimeSeries> {
+        let col = self.columns.iter().position(|c| c.name == name)?;
+        Some(self.column_at(col))
+    }
+
+    fn column_at(&self, col: usize) -> TimeSeries {
+        TimeSeries::new(self.dates.iter().cloned().zip(self.values.iter().map(|row| row[col])).collect())
+    }
+}
+
+/// Returns the vintage date encoded after the last underscore of a column name (e.g. GNPCA_20090130)
+fn vintage_date(name: &str) -> Option<NaiveDate> {
+    let (_, suffix) = name.rsplit_once('_')?;
+    NaiveDate::parse_from_str(suffix, "%Y%m%d").ok()
+}
+
+fn parse_value(text: &str) -> Result<Option<f64>, Error> {
+    if text == MISSING_VALUE {
+        return Ok(None);
+    }
+    match text.parse::<f64>() {
+        Ok(v) => Ok(Some(v)),
+        Err(_) => Err(Error::parse_field("value", text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::series::observation::{Builder, OutputType};
+    use crate::transport::{test_client, MemoryTransport};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    const VINTAGES: &str = r#"{"realtime_start":"2009-01-30","realtime_end":"2019-12-19","observation_start":"1776-07-04","observation_end":"9999-12-31",
+        "units":"lin","output_type":2,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":3,"offset":0,"limit":100000,
+        "observations":[
+            {"date":"2008-01-01","GNPCA_20190101":"12000.5","GNPCA_20090130":"11000.1"},
+            {"date":"1929-01-01","GNPCA_20190101":"1120.7","GNPCA_20090130":"865.2"},
+            {"date":"2018-01-01","GNPCA_20190101":"."}
+        ]}"#;
+
+    #[test]
+    fn series_observation_vintages() {
+        let transport = MemoryTransport::new();
+        transport.route("series/observations?series_id=GNPCA&*vintage_dates=2009-01-30,2019-01-01&output_type=2", VINTAGES);
+        let c = test_client(&transport);
+
+        let mut builder = Builder::new();
+        builder.vintage_date("2009-01-30").vintage_date("2019-01-01");
+        let matrix = c.series_observation_vintages("GNPCA", Some(builder)).unwrap().matrix().unwrap();
+
+        assert_eq!(matrix.dates(), &[date(1929, 1, 1), date(2008, 1, 1), date(2018, 1, 1)]);
+        assert_eq!(matrix.vintage_dates(), vec![date(2009, 1, 30), date(2019, 1, 1)]);
+        assert_eq!(matrix.columns()[0].name, "GNPCA_20090130");
+        assert_eq!(matrix.row(0), Some(&[Some(865.2), Some(1120.7)][..]));
+        assert_eq!(matrix.get(date(2018, 1, 1), date(2009, 1, 30)), Some(None));
+        assert_eq!(matrix.get(date(2018, 1, 1), date(2010, 1, 1)), None);
+        assert_eq!(matrix.vintage(date(2019, 1, 1)).unwrap().get(date(2008, 1, 1)), Some(Some(12000.5)));
+
+        // an output type set by the builder is kept
+        let mut builder = Builder::new();
+        builder.output_type(OutputType::INITIAL);
+        assert!(c.series_observation_vintages("GNPCA", Some(builder)).unwrap_err().is_transport());
+        assert!(transport.requests()[1].contains("output_type=4"));
+    }
+
+    #[test]
+    fn vintage_matrix_parse_errors() {
+        let resp: Response = serde_json::from_str(r#"{"realtime_start":"2009-01-30","realtime_end":"2019-12-19","observation_start":"1776-07-04","observation_end":"9999-12-31",
+            "units":"lin","output_type":4,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":1,"offset":0,"limit":100000,
+            "observations":[{"date":"2008-01-01","GNPCA_20190101":"n/a"}]}"#).unwrap();
+        match resp.matrix() {
+            Err(Error::Parse { field, value }) => assert_eq!((field.as_str(), value.as_str()), ("value", "n/a")),
+            _ => assert_eq!(2, 1),
+        }
+    }
+}
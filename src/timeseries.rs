@@ -0,0 +1,467 @@
+//! Observation data as dated values with local frequency aggregation
+//!
+//! A [TimeSeries](struct.TimeSeries.html) holds one value per date, built from a `series::observation::Response` or from any list of points.  `resample()` aggregates it to a lower frequency the same way FRED does when the `frequency` and `aggregation_method` arguments are passed to fred/series/observations, without another request.
+//!
+//! Periods follow FRED's conventions:
+//! * Monthly, quarterly, semiannual and annual periods are dated on their first day (e.g. 2020-04-01 for Q2 2020).
+//! * Weekly periods are dated on their last day.  `W` ends on Friday like `WEF`; the other variants end on the named day.
+//! * Biweekly periods are dated on their last day.  `BW` ends on Wednesday like `BWEW`.  Periods are counted in two-week steps from Wednesday 1970-01-07 (Monday 1970-01-05 for `BWEM`).
+//! * Missing values are ignored.  A period whose values are all missing is missing.  `EOP` takes the last value present in the period.
+//!
+//! `transform()` applies the [Units](../series/observation/enum.Units.html) transformations locally using FRED's formulas, so one download can produce every view of a series.
+//!
+//! ```
+//! use fred_rs::timeseries::TimeSeries;
+//! use fred_rs::series::observation::{Frequency, AggregationMethod};
+//! use chrono::NaiveDate;
+//!
+//! let daily = TimeSeries::new(vec![
+//!     (NaiveDate::from_ymd_opt(2020, 1, 30).unwrap(), Some(1.0)),
+//!     (NaiveDate::from_ymd_opt(2020, 1, 31).unwrap(), Some(3.0)),
+//!     (NaiveDate::from_ymd_opt(2020, 2, 3).unwrap(), None),
+//!     (NaiveDate::from_ymd_opt(2020, 2, 4).unwrap(), Some(5.0)),
+//! ]);
+//!
+//! let monthly = daily.resample(Frequency::M, AggregationMethod::AVG);
+//! assert_eq!(monthly.points(), &[
+//!     (NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(), Some(2.0)),
+//!     (NaiveDate::from_ymd_opt(2020, 2, 1).unwrap(), Some(5.0)),
+//! ]);
+//! ```
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::series::observation::{AggregationMethod, Frequency, Response, Units};
+
+/// Last day of the biweekly period used to align `BW` and `BWEW` periods (a Wednesday)
+const BIWEEKLY_WEDNESDAY_ANCHOR: (i32, u32, u32) = (1970, 1, 7);
+/// Last day of the biweekly period used to align `BWEM` periods (a Monday)
+const BIWEEKLY_MONDAY_ANCHOR: (i32, u32, u32) = (1970, 1, 5);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A series of dated values sorted by date
+pub struct TimeSeries {
+    points: Vec<(NaiveDate, Option<f64>)>,
+}
+
+impl TimeSeries {
+    /// Creates a time series from dated values
+    ///
+    /// The points are sorted by date.  If a date appears more than once the last value given for it is kept.
+    ///
+    /// # Arguments
+    /// * `points` - (date, value) pairs; None marks a missing value
+    pub fn new(mut points: Vec<(NaiveDate, Option<f64>)>) -> TimeSeries {
+        points.reverse();
+        points.sort_by_key(|(date, _)| *date); // stable, so the last value given comes first
+        points.dedup_by_key(|(date, _)| *date);
+        TimeSeries { points }
+    }
+
+    /// Creates a time series from an observation response
+    ///
+    /// When the response holds several realtime periods for a date (e.g. output type 1 with a realtime range), the most recent value is used.  The first data point that cannot be parsed is returned as an error.
+    ///
+    /// # Arguments
+    /// * `resp` - observations returned by fred/series/observations
+    pub fn from_response(resp: &Response) -> Result<TimeSeries, Error> {
+        let mut observations = Vec::with_capacity(resp.observations.len());
+        for point in &resp.observations {
+            observations.push(point.to_observation()?);
+        }
+        observations.sort_by_key(|o| (o.date, o.realtime_start));
+
+        Ok(TimeSeries::new(observations.into_iter().map(|o| (o.date, o.value)).collect()))
+    }
+
+    /// Returns the dated values in date order
+    pub fn points(&self) -> &[(NaiveDate, Option<f64>)] {
+        &self.points
+    }
+
+    /// Returns the number of points
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns true if the series has no points
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the value for a date
+    ///
+    /// Returns None if the date is not in the series and Some(None) if its value is missing.
+    pub fn get(&self, date: NaiveDate) -> Option<Option<f64>> {
+        match self.points.binary_search_by_key(&date, |(d, _)| *d) {
+            Ok(idx) => Some(self.points[idx].1),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the first and last date of the series
+    pub fn date_range(&self) -> Option<(NaiveDate, NaiveDate)> {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => Some((first.0, last.0)),
+            _ => None,
+        }
+    }
+
+    /// Aggregates the series to a lower frequency
+    ///
+    /// Each output point is dated according to FRED's period conventions (see the [module documentation](index.html)).  Aggregating to a frequency at or above the frequency of the data regroups the points by period without changing them.
+    ///
+    /// # Arguments
+    /// * `frequency` - the frequency of the result
+    /// * `method` - how the values in each period are combined
+    pub fn resample(&self, frequency: Frequency, method: AggregationMethod) -> TimeSeries {
+        let mut points: Vec<(NaiveDate, Option<f64>)> = Vec::new();
+        let mut current: Option<NaiveDate> = None;
+        let mut values: Vec<f64> = Vec::new();
+
+        for (date, value) in &self.points {
+            let period = period_date(*date, frequency);
+            if current != Some(period) {
+                if let Some(p) = current {
+                    points.push((p, aggregate(&values, method)));
+                }
+                current = Some(period);
+                values.clear();
+            }
+            if let Some(v) = value {
+                values.push(*v);
+            }
+        }
+        if let Some(p) = current {
+            points.push((p, aggregate(&values, method)));
+        }
+
+        TimeSeries { points }
+    }
+
+    /// Applies a units transformation using FRED's formulas
+    ///
+    /// With x(t) the value at t, x(t-1) the previous point and n the number of observations per year of `frequency`:
+    /// * `LIN`: x(t)
+    /// * `CHG`: x(t) - x(t-1)
+    /// * `CH1`: x(t) - x(t-n)
+    /// * `PCH`: (x(t) / x(t-1) - 1) * 100
+    /// * `PC1`: (x(t) / x(t-n) - 1) * 100
+    /// * `PCA`: ((x(t) / x(t-1))^n - 1) * 100
+    /// * `CCH`: (ln(x(t)) - ln(x(t-1))) * 100
+    /// * `CCA`: (ln(x(t)) - ln(x(t-1))) * 100 * n
+    /// * `LOG`: ln(x(t))
+    ///
+    /// For monthly, quarterly, semiannual and annual data the year-ago value x(t-n) is the value dated one year earlier; for daily, weekly and biweekly data it is the value n points earlier.  The result keeps every date.  A value is missing if any input it needs is missing or if the formula is undefined (e.g. the log of a negative number).
+    ///
+    /// # Arguments
+    /// * `units` - the transformation to apply
+    /// * `frequency` - the frequency of the data, used for annualization and the year-ago value
+    pub fn transform(&self, units: Units, frequency: Frequency) -> TimeSeries {
+        let n = observations_per_year(frequency) as f64;
+
+        let points = self.points.iter()
+            .enumerate()
+            .map(|(idx, (date, x))| {
+                let prev = match idx {
+                    0 => None,
+                    _ => self.points[idx - 1].1,
+                };
+                let value = match units {
+                    Units::LIN => *x,
+                    Units::LOG => x.and_then(ln),
+                    Units::CHG => pair(*x, prev).map(|(x, p)| x - p),
+                    Units::CH1 => pair(*x, self.year_ago(idx, frequency)).map(|(x, p)| x - p),
+                    Units::PCH => pair(*x, prev).and_then(ratio).map(|r| (r - 1.0) * 100.0),
+                    Units::PC1 => pair(*x, self.year_ago(idx, frequency)).and_then(ratio).map(|r| (r - 1.0) * 100.0),
+                    Units::PCA => pair(*x, prev).and_then(ratio).map(|r| (r.powf(n) - 1.0) * 100.0),
+                    Units::CCH => pair(*x, prev).and_then(log_change).map(|c| c * 100.0),
+                    Units::CCA => pair(*x, prev).and_then(log_change).map(|c| c * 100.0 * n),
+                };
+                (*date, value.filter(|v| v.is_finite()))
+            })
+            .collect();
+
+        TimeSeries { points }
+    }
+
+    /// Returns the value one year before the point at `idx`
+    fn year_ago(&self, idx: usize, frequency: Frequency) -> Option<f64> {
+        match frequency {
+            Frequency::M | Frequency::Q | Frequency::SA | Frequency::A => {
+                let (date, _) = self.points[idx];
+                let year_ago = date.with_year(date.year() - 1)?;
+                self.get(year_ago).flatten()
+            },
+            _ => {
+                let lag = observations_per_year(frequency);
+                if idx < lag {
+                    return None;
+                }
+                self.points[idx - lag].1
+            },
+        }
+    }
+}
+
+/// Returns the number of observations per year FRED uses for a frequency
+///
+/// Daily data counts 260 business days per year.
+pub fn observations_per_year(frequency: Frequency) -> usize {
+    match frequency {
+        Frequency::D => 260,
+        Frequency::W | Frequency::WEF | Frequency::WETH | Frequency::WEW | Frequency::WETU
+            | Frequency::WEM | Frequency::WESU | Frequency::WESA => 52,
+        Frequency::BW | Frequency::BWEW | Frequency::BWEM => 26,
+        Frequency::M => 12,
+        Frequency::Q => 4,
+        Frequency::SA => 2,
+        Frequency::A => 1,
+    }
+}
+
+fn pair(x: Option<f64>, p: Option<f64>) -> Option<(f64, f64)> {
+    match (x, p) {
+        (Some(x), Some(p)) => Some((x, p)),
+        _ => None,
+    }
+}
+
+fn ratio((x, p): (f64, f64)) -> Option<f64> {
+    if p == 0.0 {
+        None
+    } else {
+        Some(x / p)
+    }
+}
+
+fn ln(x: f64) -> Option<f64> {
+    if x > 0.0 {
+        Some(x.ln())
+    } else {
+        None
+    }
+}
+
+fn log_change((x, p): (f64, f64)) -> Option<f64> {
+    Some(ln(x)? - ln(p)?)
+}
+
+impl TryFrom<&Response> for TimeSeries {
+    type Error = Error;
+
+    fn try_from(resp: &Response) -> Result<TimeSeries, Error> {
+        TimeSeries::from_response(resp)
+    }
+}
+
+/// Returns the date FRED assigns to the period containing a date
+///
+/// # Arguments
+/// * `date` - any date in the period
+/// * `frequency` - the frequency that defines the periods
+pub fn period_date(date: NaiveDate, frequency: Frequency) -> NaiveDate {
+    match frequency {
+        Frequency::D => date,
+        Frequency::W | Frequency::WEF => week_ending(date, Weekday::Fri),
+        Frequency::WETH => week_ending(date, Weekday::Thu),
+        Frequency::WEW => week_ending(date, Weekday::Wed),
+        Frequency::WETU => week_ending(date, Weekday::Tue),
+        Frequency::WEM => week_ending(date, Weekday::Mon),
+        Frequency::WESU => week_ending(date, Weekday::Sun),
+        Frequency::WESA => week_ending(date, Weekday::Sat),
+        Frequency::BW | Frequency::BWEW => biweek_ending(date, BIWEEKLY_WEDNESDAY_ANCHOR),
+        Frequency::BWEM => biweek_ending(date, BIWEEKLY_MONDAY_ANCHOR),
+        Frequency::M => first_of_month(date.year(), date.month()),
+        Frequency::Q => first_of_month(date.year(), (date.month() - 1) / 3 * 3 + 1),
+        Frequency::SA => first_of_month(date.year(), (date.month() - 1) / 6 * 6 + 1),
+        Frequency::A => first_of_month(date.year(), 1),
+    }
+}
+
+fn first_of_month(year: i32, month: u32) -> NaiveDate {
+    match NaiveDate::from_ymd_opt(year, month, 1) {
+        Some(d) => d,
+        None => NaiveDate::MIN,
+    }
+}
+
+/// Returns the first date on or after `date` that falls on `end`
+fn week_ending(date: NaiveDate, end: Weekday) -> NaiveDate {
+    let days = (7 + end.num_days_from_monday() - date.weekday().num_days_from_monday()) % 7;
+    date + Duration::days(i64::from(days))
+}
+
+/// Returns the first date on or after `date` that is a whole number of two-week steps from the anchor
+fn biweek_ending(date: NaiveDate, anchor: (i32, u32, u32)) -> NaiveDate {
+    let anchor = match NaiveDate::from_ymd_opt(anchor.0, anchor.1, anchor.2) {
+        Some(a) => a,
+        None => return date,
+    };
+    let days = (anchor - date).num_days().rem_euclid(14);
+    date + Duration::days(days)
+}
+
+fn aggregate(values: &[f64], method: AggregationMethod) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    match method {
+        AggregationMethod::AVG => Some(values.iter().sum::<f64>() / values.len() as f64),
+        AggregationMethod::SUM => Some(values.iter().sum()),
+        AggregationMethod::EOP => values.last().copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::series::observation::DataPoint;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Daily values for January 2020, equal to the day of the month
+    fn january() -> TimeSeries {
+        TimeSeries::new((1..=31).map(|d| (date(2020, 1, d), Some(f64::from(d)))).collect())
+    }
+
+    #[test]
+    fn timeseries_from_response() {
+        let point = |rs: &str, d: &str, v: &str| DataPoint {
+            realtime_start: String::from(rs),
+            realtime_end: String::from("9999-12-31"),
+            date: String::from(d),
+            value: String::from(v),
+        };
+        let resp = Response {
+            observations: vec![
+                point("2019-07-26", "2018-01-01", "18650.9"),
+                point("2019-03-28", "2018-01-01", "18566.7"),
+                point("2019-03-28", "2017-01-01", "."),
+            ],
+            ..Response::default()
+        };
+
+        let ts = TimeSeries::try_from(&resp).unwrap();
+        assert_eq!(ts.points(), &[(date(2017, 1, 1), None), (date(2018, 1, 1), Some(18650.9))]);
+        assert_eq!(ts.get(date(2018, 1, 1)), Some(Some(18650.9)));
+        assert_eq!(ts.get(date(2016, 1, 1)), None);
+
+        let bad = Response { observations: vec![point("2019-03-28", "2017-01-01", "x")], ..Response::default() };
+        assert!(TimeSeries::from_response(&bad).is_err());
+    }
+
+    fn assert_close(ts: &TimeSeries, expected: &[Option<f64>]) {
+        assert_eq!(ts.len(), expected.len());
+        for ((_, value), expected) in ts.points().iter().zip(expected) {
+            match (value, expected) {
+                (Some(v), Some(e)) => assert!((v - e).abs() < 1e-9, "{} != {}", v, e),
+                (None, None) => (),
+                _ => assert_eq!(value, expected),
+            }
+        }
+    }
+
+    #[test]
+    fn timeseries_transform_period_changes() {
+        let quarterly = TimeSeries::new(vec![
+            (date(2019, 1, 1), Some(100.0)),
+            (date(2019, 4, 1), Some(110.0)),
+            (date(2019, 7, 1), None),
+            (date(2019, 10, 1), Some(121.0)),
+            (date(2020, 1, 1), Some(0.0)),
+        ]);
+
+        assert_close(&quarterly.transform(Units::LIN, Frequency::Q), &[Some(100.0), Some(110.0), None, Some(121.0), Some(0.0)]);
+        assert_close(&quarterly.transform(Units::CHG, Frequency::Q), &[None, Some(10.0), None, None, Some(-121.0)]);
+        assert_close(&quarterly.transform(Units::PCH, Frequency::Q), &[None, Some(10.0), None, None, Some(-100.0)]);
+        assert_close(&quarterly.transform(Units::PCA, Frequency::Q), &[None, Some((1.1f64.powi(4) - 1.0) * 100.0), None, None, Some(-100.0)]);
+        assert_close(&quarterly.transform(Units::CCH, Frequency::Q), &[None, Some(1.1f64.ln() * 100.0), None, None, None]);
+        assert_close(&quarterly.transform(Units::CCA, Frequency::Q), &[None, Some(1.1f64.ln() * 400.0), None, None, None]);
+        assert_close(&quarterly.transform(Units::LOG, Frequency::Q), &[Some(100f64.ln()), Some(110f64.ln()), None, Some(121f64.ln()), None]);
+    }
+
+    #[test]
+    fn timeseries_transform_year_ago() {
+        let quarterly = TimeSeries::new(vec![
+            (date(2019, 1, 1), Some(100.0)),
+            (date(2019, 4, 1), Some(110.0)),
+            // 2019-07-01 and 2019-10-01 are absent, which does not shift the year-ago dates
+            (date(2020, 1, 1), Some(105.0)),
+            (date(2020, 4, 1), Some(99.0)),
+        ]);
+        assert_close(&quarterly.transform(Units::CH1, Frequency::Q), &[None, None, Some(5.0), Some(-11.0)]);
+        assert_close(&quarterly.transform(Units::PC1, Frequency::Q), &[None, None, Some(5.0), Some(-10.0)]);
+
+        // weekly data looks back 52 observations
+        let weekly = TimeSeries::new((0..54).map(|w| (date(2019, 1, 4) + Duration::weeks(w), Some(w as f64 + 1.0))).collect());
+        let ch1 = weekly.transform(Units::CH1, Frequency::WEF);
+        assert_eq!(ch1.points()[51].1, None);
+        assert_eq!(ch1.points()[52].1, Some(52.0));
+        assert_eq!(weekly.transform(Units::PC1, Frequency::WEF).points()[53].1, Some(2600.0));
+    }
+
+    #[test]
+    fn timeseries_resample_weekly() {
+        // 2020-01-03 is a Friday
+        let weekly = january().resample(Frequency::WEF, AggregationMethod::SUM);
+        assert_eq!(&weekly.points()[..2], &[
+            (date(2020, 1, 3), Some(6.0)),
+            (date(2020, 1, 10), Some(49.0)),
+        ]);
+        assert_eq!(weekly, january().resample(Frequency::W, AggregationMethod::SUM));
+
+        let weekly = january().resample(Frequency::WESU, AggregationMethod::EOP);
+        assert_eq!(weekly.points()[0], (date(2020, 1, 5), Some(5.0)));
+        assert_eq!(weekly.points().last(), Some(&(date(2020, 2, 2), Some(31.0))));
+
+        let weekly = january().resample(Frequency::WETU, AggregationMethod::AVG);
+        assert_eq!(weekly.points()[0], (date(2020, 1, 7), Some(4.0)));
+    }
+
+    #[test]
+    fn timeseries_resample_biweekly() {
+        // 2020-01-01 is 1304 two-week steps after 1970-01-07
+        let biweekly = january().resample(Frequency::BW, AggregationMethod::EOP);
+        assert_eq!(&biweekly.points()[..2], &[
+            (date(2020, 1, 1), Some(1.0)),
+            (date(2020, 1, 15), Some(15.0)),
+        ]);
+
+        let biweekly = january().resample(Frequency::BWEM, AggregationMethod::EOP);
+        assert_eq!(biweekly.points()[0], (date(2020, 1, 13), Some(13.0)));
+    }
+
+    #[test]
+    fn timeseries_resample_calendar_periods() {
+        let monthly = TimeSeries::new(vec![
+            (date(2019, 11, 1), Some(1.0)),
+            (date(2019, 12, 1), None),
+            (date(2020, 1, 1), Some(3.0)),
+            (date(2020, 2, 1), Some(5.0)),
+            (date(2020, 5, 1), None),
+            (date(2020, 8, 1), Some(7.0)),
+        ]);
+
+        assert_eq!(monthly.resample(Frequency::Q, AggregationMethod::AVG).points(), &[
+            (date(2019, 10, 1), Some(1.0)),
+            (date(2020, 1, 1), Some(4.0)),
+            (date(2020, 4, 1), None),
+            (date(2020, 7, 1), Some(7.0)),
+        ]);
+        assert_eq!(monthly.resample(Frequency::SA, AggregationMethod::SUM).points(), &[
+            (date(2019, 7, 1), Some(1.0)),
+            (date(2020, 1, 1), Some(8.0)),
+            (date(2020, 7, 1), Some(7.0)),
+        ]);
+        assert_eq!(monthly.resample(Frequency::A, AggregationMethod::EOP).points(), &[
+            (date(2019, 1, 1), Some(1.0)),
+            (date(2020, 1, 1), Some(7.0)),
+        ]);
+    }
+}
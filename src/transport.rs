@@ -0,0 +1,399 @@
+//! Pluggable HTTP transports used by the FRED clients
+//!
+//! The clients build each request URL, hand it to a transport and decode the body the transport returns.  [Transport](trait.Transport.html) is used by the blocking [FredClient](../client/struct.FredClient.html) and [AsyncTransport](trait.AsyncTransport.html) by the [AsyncFredClient](../async_client/struct.AsyncFredClient.html).  By default the clients use reqwest, but any HTTP stack can be plugged in with `FredClientBuilder::transport()` or `FredClientBuilder::async_transport()`.
+//!
+//! [MemoryTransport](struct.MemoryTransport.html) answers requests from fixture bodies held in memory, so endpoint parsing can be tested without network access.
+//!
+//! ```
+//! use fred_rs::client::FredClient;
+//! use fred_rs::transport::MemoryTransport;
+//!
+//! let transport = MemoryTransport::new();
+//! transport.route(
+//!     "category?category_id=125&",
+//!     r#"{"categories":[{"id":125,"name":"Trade Balance","parent_id":13}]}"#
+//! );
+//!
+//! let client = FredClient::builder()
+//!     .transport(transport.clone())
+//!     .build()
+//!     .unwrap();
+//!
+//! let resp = client.category(125).unwrap();
+//! assert_eq!(resp.categories[0].name, "Trade Balance");
+//! assert_eq!(transport.requests().len(), 1);
+//! ```
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Error, TransportErrorKind};
+
+/// Future returned by an [AsyncTransport](trait.AsyncTransport.html)
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse, Error>> + Send + 'a>>;
+
+#[derive(Clone, Debug)]
+/// A GET request to send to the FRED API
+pub struct HttpRequest {
+    /// The full request URL including the API key
+    pub url: String,
+    /// The request URL without the API key, safe to log or store
+    pub redacted_url: String,
+}
+
+#[derive(Clone, Debug, Default)]
+/// The response returned by a transport
+pub struct HttpResponse {
+    /// The HTTP status code
+    pub status: u16,
+    /// Response headers as (name, value) pairs
+    pub headers: Vec<(String, String)>,
+    /// The response body
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Creates a response without headers
+    pub fn new(status: u16, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: String::from(body),
+        }
+    }
+
+    /// Returns the value of a header, ignoring the case of the name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Sends requests for the blocking [FredClient](../client/struct.FredClient.html)
+///
+/// A transport only moves bytes: it returns the status and body for any response it receives and returns an `Error::Transport` only when no response could be obtained.
+pub trait Transport: Debug + Send + Sync {
+    /// Sends a GET request and returns the response
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, Error>;
+}
+
+/// Sends requests for the [AsyncFredClient](../async_client/struct.AsyncFredClient.html)
+///
+/// See [Transport](trait.Transport.html).
+pub trait AsyncTransport: Debug + Send + Sync {
+    /// Sends a GET request and returns the response
+    fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a>;
+}
+
+#[derive(Clone, Debug)]
+/// The default blocking transport, built on `reqwest::blocking::Client`
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    /// Wraps a configured reqwest client
+    pub fn new(client: reqwest::blocking::Client) -> ReqwestTransport {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        let resp = match self.client.get(request.url.as_str()).send() {
+            Ok(r) => r,
+            Err(e) => return Err(Error::transport(e)),
+        };
+
+        let status = resp.status().as_u16();
+        let headers = header_pairs(resp.headers());
+        match resp.text() {
+            Ok(body) => Ok(HttpResponse { status, headers, body }),
+            Err(e) => Err(Error::transport(e)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// The default asynchronous transport, built on `reqwest::Client`
+pub struct AsyncReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl AsyncReqwestTransport {
+    /// Wraps a configured reqwest client
+    pub fn new(client: reqwest::Client) -> AsyncReqwestTransport {
+        AsyncReqwestTransport { client }
+    }
+}
+
+impl AsyncTransport for AsyncReqwestTransport {
+    fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let resp = match self.client.get(request.url.as_str()).send().await {
+                Ok(r) => r,
+                Err(e) => return Err(Error::transport(e)),
+            };
+
+            let status = resp.status().as_u16();
+            let headers = header_pairs(resp.headers());
+            match resp.text().await {
+                Ok(body) => Ok(HttpResponse { status, headers, body }),
+                Err(e) => Err(Error::transport(e)),
+            }
+        })
+    }
+}
+
+fn header_pairs(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers.iter()
+        .filter_map(|(name, value)| match value.to_str() {
+            Ok(v) => Some((name.as_str().to_string(), v.to_string())),
+            Err(_) => None,
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, Default)]
+/// An in-memory transport answering requests from fixture bodies
+///
+/// Each route maps a URL pattern to a response.  Patterns are matched against the request URL without the API key; `*` matches any run of characters and a pattern matches if it appears anywhere in the URL.  Routes are tried in the order they were added.  Requests that match no route fail with an `Error::Transport`.
+///
+/// Clones share their routes and the list of received requests.
+pub struct MemoryTransport {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    routes: Vec<(String, HttpResponse)>,
+    requests: Vec<String>,
+}
+
+impl MemoryTransport {
+    /// Creates a transport without routes
+    pub fn new() -> MemoryTransport {
+        MemoryTransport::default()
+    }
+
+    /// Answers requests matching a pattern with status 200 and the given body
+    ///
+    /// # Arguments
+    /// * `pattern` - URL pattern (e.g. `series/observations?series_id=GNPCA&`)
+    /// * `body` - the response body
+    pub fn route(&self, pattern: &str, body: &str) -> &MemoryTransport {
+        self.route_response(pattern, HttpResponse::new(200, body))
+    }
+
+    /// Answers requests matching a pattern with the given response
+    ///
+    /// # Arguments
+    /// * `pattern` - URL pattern
+    /// * `response` - status, headers and body to return
+    pub fn route_response(&self, pattern: &str, response: HttpResponse) -> &MemoryTransport {
+        self.lock().routes.push((String::from(pattern), response));
+        self
+    }
+
+    /// Returns the redacted URL of every request received, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.lock().requests.clone()
+    }
+
+    fn respond(&self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        let mut state = self.lock();
+        state.requests.push(request.redacted_url.clone());
+
+        match state.routes.iter().find(|(pattern, _)| pattern_matches(pattern, &request.redacted_url)) {
+            Some((_, response)) => Ok(response.clone()),
+            None => Err(Error::Transport {
+                kind: TransportErrorKind::Other,
+                message: format!("No fixture matches {}", request.redacted_url),
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        match self.state.lock() {
+            Ok(s) => s,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        self.respond(request)
+    }
+}
+
+impl AsyncTransport for MemoryTransport {
+    fn get<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        let result = self.respond(request);
+        Box::pin(async move { result })
+    }
+}
+
+/// Returns true if the pattern appears in the text, with `*` matching any run of characters
+pub(crate) fn pattern_matches(pattern: &str, text: &str) -> bool {
+    let mut rest = text;
+    for part in pattern.split('*').filter(|p| !p.is_empty()) {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+/// Returns a client with a well formed API key that sends every request to the transport
+pub(crate) fn test_client(transport: &MemoryTransport) -> crate::client::FredClient {
+    crate::client::FredClient::builder()
+        .api_key("abcdefghijklmnopqrstuvwxyz123456")
+        .transport(transport.clone())
+        .build()
+        .unwrap()
+}
+
+#[cfg(test)]
+/// Returns an async client with a well formed API key that sends every request to the transport
+pub(crate) fn test_async_client(transport: &MemoryTransport) -> crate::async_client::AsyncFredClient {
+    crate::client::FredClient::builder()
+        .api_key("abcdefghijklmnopqrstuvwxyz123456")
+        .async_transport(transport.clone())
+        .build_async()
+        .unwrap()
+}
+
+#[cfg(test)]
+/// Returns the body of one page of a list endpoint
+///
+/// # Arguments
+/// * `list` - the name of the result list (e.g. observations)
+/// * `realtime` - the realtime_start and realtime_end of the response
+/// * `count` - the number of results FRED reports
+/// * `offset` - the position of the first result of the page
+/// * `items` - the results of the page as JSON objects
+pub(crate) fn page_body<S: AsRef<str>>(list: &str, realtime: (&str, &str), count: usize, offset: usize, items: &[S]) -> String {
+    let items: Vec<&str> = items.iter().map(|i| i.as_ref()).collect();
+    format!(
+        r#"{{"realtime_start":"{}","realtime_end":"{}","observation_start":"1776-07-04","observation_end":"9999-12-31","units":"lin",
+        "output_type":1,"file_type":"json","order_by":"observation_date","sort_order":"asc","count":{},"offset":{},"limit":{},"{}":[{}]}}"#,
+        realtime.0, realtime.1, count, offset, items.len(), list, items.join(",")
+    )
+}
+
+#[cfg(test)]
+/// Returns a realtime period of an observation as JSON
+pub(crate) fn realtime_point(realtime_start: &str, realtime_end: &str, date: &str, value: &str) -> String {
+    format!(
+        r#"{{"realtime_start":"{}","realtime_end":"{}","date":"{}","value":"{}"}}"#,
+        realtime_start, realtime_end, date, value
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::FredClient;
+    use crate::*;
+
+    const OBSERVATIONS: &str = r#"{
+        "realtime_start": "2020-02-20", "realtime_end": "2020-02-20",
+        "observation_start": "1600-01-01", "observation_end": "9999-12-31",
+        "units": "lin", "output_type": 1, "file_type": "json",
+        "order_by": "observation_date", "sort_order": "desc",
+        "count": 91, "offset": 0, "limit": 2,
+        "observations": [
+            {"realtime_start": "2020-02-20", "realtime_end": "2020-02-20", "date": "2019-01-01", "value": "18950.347"},
+            {"realtime_start": "2020-02-20", "realtime_end": "2020-02-20", "date": "2018-01-01", "value": "."}
+        ]
+    }"#;
+
+    const TAGS: &str = r#"{
+        "realtime_start": "2020-02-20", "realtime_end": "2020-02-20",
+        "order_by": "series_count", "sort_order": "desc",
+        "count": 1, "offset": 0, "limit": 1000,
+        "tags": [
+            {"name": "usa", "group_id": "geo", "notes": "United States of America", "created": "2012-02-27 10:18:19-06", "popularity": 100, "series_count": 654866}
+        ]
+    }"#;
+
+    const RELEASE_DATES: &str = r#"{
+        "realtime_start": "2020-01-01", "realtime_end": "9999-12-31",
+        "order_by": "release_date", "sort_order": "desc",
+        "count": 1, "offset": 0, "limit": 1000,
+        "release_dates": [
+            {"release_id": 9, "release_name": "Advance Monthly Sales for Retail and Food Services", "date": "2020-02-14"}
+        ]
+    }"#;
+
+    #[test]
+    fn memory_transport_pattern_matching() {
+        assert!(pattern_matches("series?series_id=GNPCA&", "https://x/fred/series?series_id=GNPCA&file_type=json"));
+        assert!(!pattern_matches("series?series_id=GNPCA&", "https://x/fred/series?series_id=GNPCAX&file_type=json"));
+        assert!(pattern_matches("category/*?category_id=125", "https://x/fred/category/children?category_id=125&file_type=json"));
+        assert!(!pattern_matches("category/*?category_id=125", "https://x/fred/category?category_id=125&file_type=json"));
+    }
+
+    #[test]
+    fn memory_transport_endpoints() {
+        let transport = MemoryTransport::new();
+        transport
+            .route("series/observations?series_id=GNPCA&", OBSERVATIONS)
+            .route("series/tags?", TAGS)
+            .route("releases/dates?", RELEASE_DATES);
+        let c = test_client(&transport);
+
+        let resp = c.series_observation("GNPCA", None).unwrap();
+        assert_eq!(resp.observations.len(), 2);
+        assert_eq!(resp.observations[1].value.as_str(), ".");
+
+        let resp = c.series_tags("GNPCA", None).unwrap();
+        assert_eq!(resp.tags[0].name.as_str(), "usa");
+
+        let resp = c.releases_dates(None).unwrap();
+        assert_eq!(resp.release_dates[0].release_id, 9);
+
+        // the API key never reaches the recorded requests
+        assert_eq!(transport.requests().len(), 3);
+        assert!(transport.requests().iter().all(|url| !url.contains("api_key")));
+    }
+
+    #[test]
+    fn memory_transport_errors() {
+        let transport = MemoryTransport::new();
+        transport.route_response(
+            "series?series_id=NOPE&",
+            HttpResponse::new(400, r#"{"error_code":400,"error_message":"Bad Request.  The series does not exist."}"#)
+        );
+        let c = test_client(&transport);
+
+        match c.series("NOPE", None) {
+            Err(e) => assert!(e.is_bad_parameter()),
+            Ok(_) => assert_eq!(2, 1),
+        }
+        match c.category(1) {
+            Err(e) => assert!(e.is_transport()),
+            Ok(_) => assert_eq!(2, 1),
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_transport_async() {
+        let transport = MemoryTransport::new();
+        transport.route("series/observations?", OBSERVATIONS);
+
+        let c = FredClient::builder()
+            .async_transport(transport.clone())
+            .build_async()
+            .unwrap();
+
+        let resp: series::observation::Response = c.series_observation("GNPCA", None).await.unwrap();
+        assert_eq!(resp.count, 91);
+    }
+}